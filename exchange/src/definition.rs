@@ -0,0 +1,46 @@
+use crate::ExchangeError;
+use market_sol::sol_market::MarketDefinition;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+/// The SOL markets of an exchange, as stored in a TOML definition file.
+///
+/// Markets of other groups are Rust types, not data: they are added to the
+/// exchange with `Exchange::add_market`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ExchangeDefinition {
+    /// One SOL market for each definition. Their names (`config.name`)
+    /// must differ.
+    #[serde(default)]
+    pub sol_markets: Vec<MarketDefinition>,
+}
+
+impl ExchangeDefinition {
+    /// `count` copies of a SOL market, named after it with their number,
+    /// e.g., `SOL1` and `SOL2`. If the definition has a seed, market `i`
+    /// (from 1) is seeded with `seed + i`.
+    pub fn replicated(definition: &MarketDefinition, count: u32) -> Self {
+        let sol_markets = (1..=count)
+            .map(|i| {
+                let mut definition = definition.clone();
+                definition.config.name = format!("{}{i}", definition.config.name);
+                definition.seed = definition.seed.map(|seed| seed.wrapping_add(i.into()));
+                definition
+            })
+            .collect();
+        ExchangeDefinition { sol_markets }
+    }
+
+    /// Reads a definition from a TOML file, with a `[[sol_markets]]` table
+    /// for each market
+    pub fn from_file(path: &Path) -> Result<Self, ExchangeError> {
+        let contents = fs::read_to_string(path).map_err(|source| ExchangeError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        toml::from_str(&contents).map_err(|e| ExchangeError::Parse {
+            path: path.to_path_buf(),
+            reason: e.to_string(),
+        })
+    }
+}
@@ -0,0 +1,51 @@
+use market_sol::sol_market::SOLMarketError;
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    io,
+    path::PathBuf,
+};
+
+/// Why an exchange could not be built
+#[derive(Debug)]
+pub enum ExchangeError {
+    /// Traders find markets by name, so two markets can not share one
+    DuplicateName { name: String },
+    /// A SOL market could not be created
+    Market(SOLMarketError),
+    /// The definition file could not be read
+    Io { path: PathBuf, source: io::Error },
+    /// The definition file is not a valid exchange definition
+    Parse { path: PathBuf, reason: String },
+}
+
+impl Display for ExchangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExchangeError::DuplicateName { name } => {
+                write!(f, "The exchange already has a market named {name}")
+            }
+            ExchangeError::Market(e) => write!(f, "{e}"),
+            ExchangeError::Io { path, source } => {
+                write!(f, "Could not read {}: {source}", path.display())
+            }
+            ExchangeError::Parse { path, reason } => write!(f, "{}: {reason}", path.display()),
+        }
+    }
+}
+
+impl Error for ExchangeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ExchangeError::Market(e) => Some(e),
+            ExchangeError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<SOLMarketError> for ExchangeError {
+    fn from(e: SOLMarketError) -> Self {
+        ExchangeError::Market(e)
+    }
+}
@@ -0,0 +1,159 @@
+use crate::{subscription::Subscription, ExchangeDefinition, ExchangeError};
+use chrono::Local;
+use market_sol::{sol_market::SimulatedClock, SOLMarket};
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+use unitn_market_2022::{
+    event::event::{Event, EventKind},
+    good::consts::DEFAULT_GOOD_KIND,
+    market::Market,
+};
+
+/// Several markets trading side by side: each one subscribes to all the
+/// others, and the days go by for all of them at once.
+///
+/// SOL markets get a clock that only the exchange moves (see
+/// `SimulatedClock`), so their days are the days of the exchange, whatever
+/// the traders do. Markets of other groups count their days their own way:
+/// the exchange sends them a `Wait` event for each day, see `add_market`.
+///
+/// `markets` hands the markets out to traders, as many as needed: they all
+/// trade on the same markets.
+#[derive(Default)]
+pub struct Exchange {
+    markets: Vec<Listing>,
+    /// The days gone by since the exchange was created
+    days: u32,
+    /// See `dropped_events`
+    dropped_events: Rc<Cell<u64>>,
+}
+
+/// A market of the exchange
+struct Listing {
+    market: Rc<RefCell<dyn Market>>,
+    /// Set for the SOL markets, along with the clock the exchange moves
+    sol: Option<(Rc<RefCell<SOLMarket>>, SimulatedClock)>,
+}
+
+impl Exchange {
+    /// An exchange without markets
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// An exchange with the SOL markets of the definition
+    pub fn try_new_from_definition(definition: &ExchangeDefinition) -> Result<Self, ExchangeError> {
+        let mut exchange = Exchange::new();
+        for market in definition.sol_markets.iter() {
+            exchange.add_sol_market(SOLMarket::try_new_from_definition(market)?)?;
+        }
+        Ok(exchange)
+    }
+
+    /// Adds a SOL market, which from now on changes day with the exchange
+    pub fn add_sol_market(&mut self, market: Rc<RefCell<SOLMarket>>) -> Result<(), ExchangeError> {
+        let clock = SimulatedClock::new(Local::now());
+        let dyn_market: Rc<RefCell<dyn Market>> = market.clone();
+        self.list(dyn_market, Some((market, clock)))
+    }
+
+    /// Adds a market of any kind, e.g., one of another group.
+    ///
+    /// The exchange can not set the clock of such a market: it sends it a
+    /// `Wait` event every day, but the market also sees the events of the
+    /// other markets, and may count a day for each of them. Its days then
+    /// run ahead of `days`, and the exchange can not tell.
+    pub fn add_market(&mut self, market: Rc<RefCell<dyn Market>>) -> Result<(), ExchangeError> {
+        self.list(market, None)
+    }
+
+    /// The markets of the exchange, in the order they were added, for a
+    /// trader to trade on
+    pub fn markets(&self) -> Vec<Rc<RefCell<dyn Market>>> {
+        self.markets.iter().map(|l| l.market.clone()).collect()
+    }
+
+    /// The market with the given name, if any
+    pub fn market(&self, name: &str) -> Option<Rc<RefCell<dyn Market>>> {
+        self.markets
+            .iter()
+            .find(|l| l.market.borrow().get_name() == name)
+            .map(|l| l.market.clone())
+    }
+
+    /// The SOL markets of the exchange, in the order they were added
+    pub fn sol_markets(&self) -> Vec<Rc<RefCell<SOLMarket>>> {
+        self.markets
+            .iter()
+            .filter_map(|l| l.sol.as_ref().map(|(market, _)| market.clone()))
+            .collect()
+    }
+
+    /// The days gone by since the exchange was created
+    pub fn days(&self) -> u32 {
+        self.days
+    }
+
+    /// The events a market sent that another one could not take, because
+    /// it was busy, e.g., borrowed by a trader at the time
+    pub fn dropped_events(&self) -> u64 {
+        self.dropped_events.get()
+    }
+
+    /// Moves every market forward by a day
+    pub fn advance_day(&mut self) {
+        for listing in self.markets.iter() {
+            match &listing.sol {
+                Some((market, clock)) => {
+                    clock.advance_days(1);
+                    market.borrow_mut().sync_clock();
+                }
+                None => listing.market.borrow_mut().on_event(Event {
+                    kind: EventKind::Wait,
+                    good_kind: DEFAULT_GOOD_KIND,
+                    quantity: 0.0,
+                    price: 0.0,
+                }),
+            }
+        }
+        self.days += 1;
+    }
+
+    /// Moves every market forward by `days`
+    pub fn advance_days(&mut self, days: u32) {
+        for _ in 0..days {
+            self.advance_day();
+        }
+    }
+
+    /// Subscribes the market to all the others and the others to it, then
+    /// adds it
+    fn list(
+        &mut self,
+        market: Rc<RefCell<dyn Market>>,
+        sol: Option<(Rc<RefCell<SOLMarket>>, SimulatedClock)>,
+    ) -> Result<(), ExchangeError> {
+        let name = market.borrow().get_name();
+        if self.market(name).is_some() {
+            return Err(ExchangeError::DuplicateName {
+                name: name.to_string(),
+            });
+        }
+        if let Some((sol_market, clock)) = &sol {
+            sol_market.borrow_mut().set_clock(Box::new(clock.clone()));
+        }
+        for other in self.markets.iter() {
+            let other = &other.market;
+            other
+                .borrow_mut()
+                .add_subscriber(Box::new(Subscription::new(&market, &self.dropped_events)));
+            market
+                .borrow_mut()
+                .add_subscriber(Box::new(Subscription::new(other, &self.dropped_events)));
+        }
+        self.markets.push(Listing { market, sol });
+        Ok(())
+    }
+}
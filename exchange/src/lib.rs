@@ -0,0 +1,13 @@
+#![doc = include_str!("../README.md")]
+
+pub mod definition;
+pub mod error;
+pub mod exchange;
+mod subscription;
+
+pub use self::definition::ExchangeDefinition;
+pub use self::error::ExchangeError;
+pub use self::exchange::Exchange;
+
+#[cfg(test)]
+mod tests;
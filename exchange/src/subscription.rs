@@ -0,0 +1,42 @@
+use std::{
+    cell::{Cell, RefCell},
+    rc::{Rc, Weak},
+};
+use unitn_market_2022::{
+    event::{event::Event, notifiable::Notifiable},
+    market::Market,
+};
+
+/// Passes the events of a market on to another one, as a subscriber.
+///
+/// It does not keep the other market alive, so markets that subscribe to
+/// each other can still be dropped. An event is skipped if the other market
+/// is busy, e.g., if it is the one that sent it, and counted in `dropped`.
+pub(crate) struct Subscription {
+    market: Weak<RefCell<dyn Market>>,
+    /// Shared by all the subscriptions of the exchange
+    dropped: Rc<Cell<u64>>,
+}
+
+impl Subscription {
+    pub fn new(market: &Rc<RefCell<dyn Market>>, dropped: &Rc<Cell<u64>>) -> Self {
+        Subscription {
+            market: Rc::downgrade(market),
+            dropped: dropped.clone(),
+        }
+    }
+}
+
+impl Notifiable for Subscription {
+    /// Subscribers should subscribe to the market itself
+    fn add_subscriber(&mut self, _subscriber: Box<dyn Notifiable>) {}
+
+    fn on_event(&mut self, event: Event) {
+        if let Some(market) = self.market.upgrade() {
+            match market.try_borrow_mut() {
+                Ok(mut market) => market.on_event(event),
+                Err(_) => self.dropped.set(self.dropped.get() + 1),
+            }
+        }
+    }
+}
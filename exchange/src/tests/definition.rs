@@ -0,0 +1,71 @@
+use crate::{Exchange, ExchangeDefinition, ExchangeError};
+use market_sol::sol_market::{GoodDefinition, MarketDefinition};
+use std::{env, fs};
+use unitn_market_2022::good::good_kind::GoodKind;
+
+fn definition() -> MarketDefinition {
+    let mut definition = MarketDefinition {
+        goods: [GoodKind::EUR, GoodKind::USD, GoodKind::YEN, GoodKind::YUAN]
+            .into_iter()
+            .map(|kind| GoodDefinition::new(kind, 10000.0))
+            .collect(),
+        seed: Some(10),
+        ..Default::default()
+    };
+    // Keep the logs of the markets out of the crate
+    let log_file = env::temp_dir().join("exchange_definition_test.txt");
+    definition.config.log_file = Some(log_file.to_string_lossy().into_owned());
+    definition
+}
+
+#[test]
+/// Copies of a market should get their own names and seeds
+fn replicated_markets_should_have_their_own_names_and_seeds() {
+    let definition = ExchangeDefinition::replicated(&definition(), 3);
+    let names: Vec<&str> = definition
+        .sol_markets
+        .iter()
+        .map(|m| m.config.name.as_str())
+        .collect();
+    assert_eq!(names, ["SOL1", "SOL2", "SOL3"]);
+    let seeds: Vec<Option<u64>> = definition.sol_markets.iter().map(|m| m.seed).collect();
+    assert_eq!(seeds, [Some(11), Some(12), Some(13)]);
+
+    let exchange = Exchange::try_new_from_definition(&definition).unwrap();
+    let names: Vec<&str> = exchange
+        .markets()
+        .iter()
+        .map(|m| m.borrow().get_name())
+        .collect();
+    assert_eq!(names, ["SOL1", "SOL2", "SOL3"]);
+    assert!(exchange.market("SOL2").is_some());
+    assert!(exchange.market("SOL").is_none());
+}
+
+#[test]
+/// Two markets with the same name should not be in the same exchange
+fn markets_should_not_share_a_name() {
+    let definition = ExchangeDefinition {
+        sol_markets: vec![definition(), definition()],
+    };
+    match Exchange::try_new_from_definition(&definition) {
+        Err(ExchangeError::DuplicateName { name }) => assert_eq!(name, "SOL"),
+        _ => panic!("the exchange should refuse the second market"),
+    }
+}
+
+#[test]
+/// The definition should be read from a TOML file
+fn definitions_should_be_read_from_files() {
+    let definition = ExchangeDefinition::replicated(&definition(), 2);
+    let path = env::temp_dir().join("exchange_definition_test.toml");
+    fs::write(&path, toml::to_string(&definition).unwrap()).unwrap();
+    assert_eq!(ExchangeDefinition::from_file(&path).unwrap(), definition);
+
+    fs::write(&path, "sol_markets = 3").unwrap();
+    assert!(matches!(
+        ExchangeDefinition::from_file(&path),
+        Err(ExchangeError::Parse { .. })
+    ));
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,132 @@
+use crate::Exchange;
+use market_sol::{
+    sol_market::{EventClock, GoodDefinition, LogSink, MarketDefinition},
+    SOLMarket,
+};
+use std::{cell::RefCell, env, rc::Rc};
+use unitn_market_2022::{
+    good::{consts::DEFAULT_GOOD_KIND, good::Good, good_kind::GoodKind},
+    market::Market,
+};
+
+const TRADER_NAME: &str = "foobar";
+
+fn sol_market(name: &str, seed: u64) -> Rc<RefCell<SOLMarket>> {
+    let mut definition = MarketDefinition {
+        goods: [GoodKind::EUR, GoodKind::USD, GoodKind::YEN, GoodKind::YUAN]
+            .into_iter()
+            .map(|kind| GoodDefinition::new(kind, 100000.0))
+            .collect(),
+        seed: Some(seed),
+        ..Default::default()
+    };
+    definition.config.name = name.to_string();
+    // Keep the logs of the markets out of the crate
+    let log_file = env::temp_dir().join("exchange_test.txt");
+    definition.config.log_file = Some(log_file.to_string_lossy().into_owned());
+    let market = SOLMarket::try_new_from_definition(&definition).unwrap();
+    market.borrow_mut().set_log_sink(LogSink::Disabled);
+    market
+}
+
+fn day(market: &Rc<RefCell<SOLMarket>>) -> u32 {
+    market.borrow().event_state().day
+}
+
+/// Locks and buys some USD
+fn buy_usd(market: &Rc<RefCell<dyn Market>>, trader_name: &str) {
+    let mut market = market.borrow_mut();
+    let bid = market.get_buy_price(GoodKind::USD, 1000.0).unwrap();
+    let token = market
+        .lock_buy(GoodKind::USD, 1000.0, bid, trader_name.to_string())
+        .unwrap();
+    market
+        .buy(token, &mut Good::new(DEFAULT_GOOD_KIND, bid))
+        .unwrap();
+}
+
+/// The rate of USD the market sees in the other markets
+fn others_usd_rate(market: &Rc<RefCell<SOLMarket>>) -> f32 {
+    let report = market.borrow().report();
+    let rates = report.rates.iter().find(|r| r.kind == GoodKind::USD);
+    rates.unwrap().others
+}
+
+#[test]
+/// SOL markets should change day with the exchange, and only with it
+fn sol_markets_should_follow_the_days_of_the_exchange() {
+    let (first, second) = (sol_market("SOL1", 1), sol_market("SOL2", 2));
+    let mut exchange = Exchange::new();
+    exchange.add_sol_market(first.clone()).unwrap();
+    exchange.add_sol_market(second.clone()).unwrap();
+    let start = day(&first);
+
+    buy_usd(&exchange.market("SOL1").unwrap(), TRADER_NAME);
+    assert_eq!((day(&first), day(&second)), (start, start));
+
+    exchange.advance_days(3);
+    assert_eq!(exchange.days(), 3);
+    assert_eq!((day(&first), day(&second)), (start + 3, start + 3));
+}
+
+#[test]
+/// What happens on a market should be seen by the others, whatever their
+/// kind
+fn events_should_reach_the_other_markets() {
+    let (first, second) = (sol_market("SOL1", 1), sol_market("SOL2", 2));
+    // Seen by the exchange as a market of another group, with its own days
+    let other = sol_market("OTHER", 3);
+    other.borrow_mut().set_clock(Box::new(EventClock));
+    let mut exchange = Exchange::new();
+    exchange.add_sol_market(first).unwrap();
+    exchange.add_sol_market(second.clone()).unwrap();
+    exchange.add_market(other.clone()).unwrap();
+    let (second_rate, other_rate) = (others_usd_rate(&second), others_usd_rate(&other));
+    let other_day = day(&other);
+
+    buy_usd(&exchange.market("SOL1").unwrap(), TRADER_NAME);
+    assert_ne!(others_usd_rate(&second), second_rate);
+    assert_ne!(others_usd_rate(&other), other_rate);
+    // Each event moves it forward, like any market with an `EventClock`
+    assert!(day(&other) > other_day);
+
+    let other_day = day(&other);
+    exchange.advance_day();
+    assert_eq!(day(&other), other_day + 1);
+}
+
+#[test]
+/// Traders should all trade on the same markets
+fn traders_should_share_the_markets() {
+    let market = sol_market("SOL1", 1);
+    let mut exchange = Exchange::new();
+    exchange.add_sol_market(market.clone()).unwrap();
+    let usd = || market.borrow().event_state().stock["USD"];
+    let before = usd();
+
+    let (alice, bob) = (exchange.markets(), exchange.markets());
+    buy_usd(&alice[0], "alice");
+    buy_usd(&bob[0], "bob");
+    assert_eq!(before - usd(), 2000.0);
+}
+
+#[test]
+/// Events a busy market could not take should be counted
+fn events_to_busy_markets_should_be_counted() {
+    let (first, second) = (sol_market("SOL1", 1), sol_market("SOL2", 2));
+    let mut exchange = Exchange::new();
+    exchange.add_sol_market(first).unwrap();
+    exchange.add_sol_market(second.clone()).unwrap();
+
+    buy_usd(&exchange.market("SOL1").unwrap(), TRADER_NAME);
+    assert_eq!(exchange.dropped_events(), 0);
+    let second_rate = others_usd_rate(&second);
+
+    // A trader is looking at the second market meanwhile
+    let busy = second.borrow();
+    buy_usd(&exchange.market("SOL1").unwrap(), TRADER_NAME);
+    drop(busy);
+    // The lock and the buy
+    assert_eq!(exchange.dropped_events(), 2);
+    assert_eq!(others_usd_rate(&second), second_rate);
+}
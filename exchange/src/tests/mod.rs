@@ -0,0 +1,4 @@
+#[cfg(test)]
+mod definition;
+#[cfg(test)]
+mod exchange;
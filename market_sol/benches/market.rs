@@ -0,0 +1,190 @@
+//! Throughput of the hot paths of the market as it ages: quotes, locks,
+//! trades and events, with more and more outstanding locks and days of
+//! history behind them.
+//!
+//! Run with `cargo bench`, or `cargo bench -- lock` for a single group.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use market_sol::sol_market::{GoodDefinition, LogSink, MarketConfig, MarketDefinition, SOLMarket};
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+use unitn_market_2022::{
+    event::{
+        event::{Event, EventKind},
+        notifiable::Notifiable,
+    },
+    good::{consts::DEFAULT_GOOD_KIND, good::Good, good_kind::GoodKind},
+    market::Market,
+};
+
+/// Outstanding locks the market holds while it is measured
+const LOCK_COUNTS: [usize; 3] = [0, 1_000, 10_000];
+/// Days of trading behind the market while it is measured
+const HISTORY_DAYS: [u32; 3] = [0, 1_000, 10_000];
+/// The traders the locks are spread over
+const TRADERS: usize = 100;
+
+/// A market with plenty of every good, which never runs out of locks
+fn market(token_duration: u32) -> Rc<RefCell<SOLMarket>> {
+    let definition = MarketDefinition {
+        goods: [GoodKind::EUR, GoodKind::USD, GoodKind::YEN, GoodKind::YUAN]
+            .into_iter()
+            .map(|kind| GoodDefinition::new(kind, 1_000_000_000.0))
+            .collect(),
+        config: MarketConfig {
+            lock_limit: u32::MAX,
+            token_duration,
+            ..Default::default()
+        },
+        seed: Some(42),
+        ..Default::default()
+    };
+    let market = SOLMarket::try_new_from_definition(&definition).unwrap();
+    market.borrow_mut().set_log_sink(LogSink::Disabled);
+    market
+}
+
+fn trader(i: usize) -> String {
+    format!("trader{}", i % TRADERS)
+}
+
+/// Locks a little USD at twice its price, and returns the token
+fn lock(market: &mut SOLMarket, trader_name: String) -> String {
+    let bid = market.get_buy_price(GoodKind::USD, 1.0).unwrap() * 2.0;
+    market
+        .lock_buy(GoodKind::USD, 1.0, bid, trader_name)
+        .unwrap()
+}
+
+/// A market holding `locks` outstanding locks
+fn market_with_locks(locks: usize) -> Rc<RefCell<SOLMarket>> {
+    // The locks must not expire while they pile up
+    let market = market(u32::MAX / 2);
+    {
+        let mut market = market.borrow_mut();
+        for i in 0..locks {
+            lock(&mut market, trader(i));
+        }
+    }
+    market
+}
+
+/// A market that traded for `days` days: every day, a lock is redeemed and
+/// another one is left to expire
+fn market_with_history(days: u32) -> Rc<RefCell<SOLMarket>> {
+    let market = market(MarketConfig::default().token_duration);
+    {
+        let mut market = market.borrow_mut();
+        for day in 0..days as usize {
+            trade(&mut market, trader(day));
+            lock(&mut market, trader(day));
+        }
+    }
+    market
+}
+
+/// Locks and buys a little USD
+fn trade(market: &mut SOLMarket, trader_name: String) {
+    let token = lock(market, trader_name);
+    let mut cash = Good::new(DEFAULT_GOOD_KIND, f32::MAX);
+    market.buy(token, &mut cash).unwrap();
+}
+
+fn quote(c: &mut Criterion) {
+    let mut group = c.benchmark_group("quote");
+    for locks in LOCK_COUNTS {
+        let market = market_with_locks(locks);
+        group.bench_with_input(BenchmarkId::new("buy_price", locks), &market, |b, m| {
+            b.iter(|| m.borrow().get_buy_price(GoodKind::YEN, 10.0).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("ladders", locks), &market, |b, m| {
+            b.iter(|| m.borrow().get_quote_ladders(&[1.0, 10.0, 100.0]))
+        });
+    }
+    group.finish();
+}
+
+fn lock_buy(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lock");
+    let markets = LOCK_COUNTS
+        .into_iter()
+        .map(|locks| (BenchmarkId::new("locks", locks), market_with_locks(locks)))
+        .chain(
+            HISTORY_DAYS
+                .into_iter()
+                .map(|days| (BenchmarkId::new("history", days), market_with_history(days))),
+        );
+    for (id, market) in markets {
+        group.bench_with_input(id, &market, |b, m| {
+            // Only the lock is timed: buying it back keeps the number of
+            // outstanding locks the same
+            b.iter_custom(|iterations| {
+                let mut elapsed = Duration::ZERO;
+                for i in 0..iterations {
+                    let mut market = m.borrow_mut();
+                    let start = Instant::now();
+                    let token = lock(&mut market, trader(i as usize));
+                    elapsed += start.elapsed();
+                    let mut cash = Good::new(DEFAULT_GOOD_KIND, f32::MAX);
+                    market.buy(token, &mut cash).unwrap();
+                }
+                elapsed
+            })
+        });
+    }
+    group.finish();
+}
+
+fn lock_and_buy(c: &mut Criterion) {
+    let mut group = c.benchmark_group("trade");
+    for locks in LOCK_COUNTS {
+        let market = market_with_locks(locks);
+        let mut i = 0;
+        group.bench_with_input(BenchmarkId::new("locks", locks), &market, |b, m| {
+            b.iter(|| {
+                i += 1;
+                trade(&mut m.borrow_mut(), trader(i))
+            })
+        });
+    }
+    for days in HISTORY_DAYS {
+        let market = market_with_history(days);
+        let mut i = 0;
+        group.bench_with_input(BenchmarkId::new("history", days), &market, |b, m| {
+            b.iter(|| {
+                i += 1;
+                trade(&mut m.borrow_mut(), trader(i))
+            })
+        });
+    }
+    group.finish();
+}
+
+fn on_event(c: &mut Criterion) {
+    let mut group = c.benchmark_group("on_event");
+    let event = Event {
+        kind: EventKind::Bought,
+        good_kind: GoodKind::YEN,
+        quantity: 100.0,
+        price: 1.0,
+    };
+    for locks in LOCK_COUNTS {
+        let market = market_with_locks(locks);
+        group.bench_with_input(BenchmarkId::new("locks", locks), &market, |b, m| {
+            b.iter(|| m.borrow_mut().on_event(event.clone()))
+        });
+    }
+    for days in HISTORY_DAYS {
+        let market = market_with_history(days);
+        group.bench_with_input(BenchmarkId::new("history", days), &market, |b, m| {
+            b.iter(|| m.borrow_mut().on_event(event.clone()))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, quote, lock_buy, lock_and_buy, on_event);
+criterion_main!(benches);
@@ -0,0 +1,45 @@
+use market_sol::remote::{SOLMarketEndpoint, SOLMarketServer};
+use std::{env, process};
+
+/// Hosts a SOL market that other processes can use through `SOLMarketClient`.
+///
+/// Call with arguments:
+/// - cargo run --bin sol_server [--allow-remote] <Endpoint: host:port | unix:path> <State file>
+///
+/// TCP endpoints must be on the loopback, unless `--allow-remote` is given:
+/// anyone who can reach the endpoint can use the market.
+///
+/// Examples:
+/// - cargo run --bin sol_server                                -> Random market on the default endpoint (or $SOL_MARKET_ENDPOINT)
+/// - cargo run --bin sol_server 127.0.0.1:9000                 -> Random market on TCP port 9000
+/// - cargo run --bin sol_server unix:/tmp/sol.sock state.sol   -> Market restored from state.sol, on a Unix domain socket
+/// - cargo run --bin sol_server --allow-remote 0.0.0.0:9000    -> Random market reachable from other machines
+pub fn main() {
+    let mut args: Vec<String> = env::args().collect();
+    let allow_remote = args.iter().any(|arg| arg == "--allow-remote");
+    args.retain(|arg| arg != "--allow-remote");
+    let endpoint = match args.get(1) {
+        Some(s) => s.parse().unwrap_or_else(|e| {
+            eprintln!("{e}");
+            process::exit(1);
+        }),
+        None => SOLMarketEndpoint::from_env(),
+    };
+    let state_file = args.get(2).map(String::as_str);
+
+    let server = if allow_remote {
+        SOLMarketServer::bind_remote(&endpoint)
+    } else {
+        SOLMarketServer::bind(&endpoint)
+    };
+    let server = server.unwrap_or_else(|e| {
+        eprintln!("Could not listen on {endpoint}: {e}");
+        process::exit(1);
+    });
+    println!("*** SOL market listening on {endpoint} ***");
+
+    if let Err(e) = server.serve(state_file) {
+        eprintln!("The SOL market server stopped: {e}");
+        process::exit(1);
+    }
+}
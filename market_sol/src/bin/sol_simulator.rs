@@ -0,0 +1,130 @@
+use market_sol::{
+    simulation::{simulate, SimulationConfig, SimulationResult, TraderFlow},
+    sol_market::MarketDefinition,
+};
+use std::{env, fs::File, io::BufWriter, path::Path, process, str::FromStr};
+
+const USAGE: &str = "Usage: sol_simulator [--markets N] [--days D] [--seed S] [--definition FILE]
+                     [--trades-per-day T] [--buy-probability P] [--max-trade-value V]
+                     [--format csv|json] [--out PREFIX] [--plot]";
+
+/// Runs SOL markets without traders, and writes the prices of their goods.
+///
+/// With `--format csv` (the default) it writes `PREFIX.csv` and
+/// `PREFIX_summary.csv`, with `--format json` it writes `PREFIX.json`.
+/// `--plot` also draws `PREFIX.svg`, and needs the `plot` feature.
+/// A `--trades-per-day` above 0 adds synthetic trades.
+///
+/// Examples:
+/// - cargo run --bin sol_simulator                                    -> One market for a year, in simulation.csv
+/// - cargo run --bin sol_simulator -- --markets 10 --days 3650 --seed 42
+/// - cargo run --bin sol_simulator --features plot -- --trades-per-day 5 --plot --out flow
+pub fn main() {
+    let mut config = SimulationConfig::default();
+    let mut flow = TraderFlow {
+        trades_per_day: 0,
+        ..Default::default()
+    };
+    let mut json = false;
+    let mut plot = false;
+    let mut out = String::from("simulation");
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--markets" => config.markets = value(&arg, args.next()),
+            "--days" => config.days = value(&arg, args.next()),
+            "--seed" => config.seed = value(&arg, args.next()),
+            "--definition" => {
+                let path: String = value(&arg, args.next());
+                config.definition = MarketDefinition::from_file(Path::new(&path))
+                    .unwrap_or_else(|e| fail(&e.to_string()));
+            }
+            "--trades-per-day" => flow.trades_per_day = value(&arg, args.next()),
+            "--buy-probability" => flow.buy_probability = value(&arg, args.next()),
+            "--max-trade-value" => flow.max_trade_value = value(&arg, args.next()),
+            "--format" => match args.next().as_deref() {
+                Some("csv") => json = false,
+                Some("json") => json = true,
+                _ => fail("--format should be csv or json"),
+            },
+            "--out" => out = value(&arg, args.next()),
+            "--plot" => plot = true,
+            "--help" | "-h" => {
+                println!("{USAGE}");
+                return;
+            }
+            _ => fail(&format!("Unknown argument '{arg}'")),
+        }
+    }
+    if plot && !cfg!(feature = "plot") {
+        fail("--plot needs the plot feature");
+    }
+    if flow.trades_per_day > 0 {
+        config.flow = Some(flow);
+    }
+
+    //`simulate` checks the flow and the definition, and says what is wrong
+    let result = simulate(&config).unwrap_or_else(|e| fail(&e.to_string()));
+    if let Err(e) = write(&result, &out, json, plot) {
+        fail(&e);
+    }
+    println!("good   change     drift      volatility max_drawdown");
+    for s in result.averages() {
+        println!(
+            "{:<6} {:<+10.4} {:<+10.6} {:<10.6} {:.4}",
+            s.kind.to_string(),
+            s.change,
+            s.drift,
+            s.volatility,
+            s.max_drawdown
+        );
+    }
+}
+
+fn write(result: &SimulationResult, out: &str, json: bool, plot: bool) -> Result<(), String> {
+    let create = |path: String| {
+        File::create(&path)
+            .map(BufWriter::new)
+            .map_err(|e| format!("Could not create {path}: {e}"))
+    };
+    if json {
+        let contents = result.to_json().map_err(|e| e.to_string())?;
+        std::fs::write(format!("{out}.json"), contents).map_err(|e| e.to_string())?;
+    } else {
+        result
+            .write_series_csv(create(format!("{out}.csv"))?)
+            .map_err(|e| e.to_string())?;
+        result
+            .write_summary_csv(create(format!("{out}_summary.csv"))?)
+            .map_err(|e| e.to_string())?;
+    }
+    if plot {
+        plot_svg(result, &format!("{out}.svg"))?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "plot")]
+fn plot_svg(result: &SimulationResult, path: &str) -> Result<(), String> {
+    result
+        .plot_svg(Path::new(path))
+        .map_err(|e| format!("Could not plot to {path}: {e}"))
+}
+
+#[cfg(not(feature = "plot"))]
+fn plot_svg(_: &SimulationResult, _: &str) -> Result<(), String> {
+    Err(String::from("--plot needs the plot feature"))
+}
+
+fn value<T: FromStr>(arg: &str, value: Option<String>) -> T {
+    match value.map(|v| v.parse()) {
+        Some(Ok(value)) => value,
+        _ => fail(&format!("{arg} should be followed by a valid value")),
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{message}\n{USAGE}");
+    process::exit(1);
+}
@@ -1,5 +1,7 @@
 #![doc = include_str!("../README.md")]
 
+pub mod remote;
+pub mod simulation;
 pub mod sol_market;
 
 pub use self::sol_market::SOLMarket;
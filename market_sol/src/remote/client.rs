@@ -0,0 +1,281 @@
+use super::{
+    endpoint::{SOLMarketEndpoint, Stream},
+    protocol::{Outcome, Request, Response},
+};
+use std::{
+    cell::RefCell,
+    io::{self, BufRead, BufReader, Write},
+    rc::Rc,
+};
+use unitn_market_2022::{
+    event::{event::Event, notifiable::Notifiable},
+    good::{good::Good, good_kind::GoodKind},
+    market::{
+        good_label::GoodLabel, BuyError, LockBuyError, LockSellError, Market, MarketGetterError,
+        SellError,
+    },
+};
+
+/// A [`Market`] that forwards every call to a [`super::SOLMarketServer`],
+/// so that traders can use a SOL market running in another process.
+///
+/// Since the `Market` trait has no room for I/O errors, this client panics if
+/// the connection to the server breaks.
+///
+/// The constructors of the `Market` trait connect to the endpoint returned by
+/// [`SOLMarketEndpoint::from_env`] and **replace** the market hosted by the
+/// server for every connected client.
+pub struct SOLMarketClient {
+    connection: RefCell<Connection>,
+    /// The name of the remote market, fetched on connection
+    name: &'static str,
+    subscribers: Vec<Box<dyn Notifiable>>,
+}
+
+struct Connection {
+    reader: BufReader<Stream>,
+    writer: Stream,
+}
+
+impl SOLMarketClient {
+    /// Connects to the market server listening on the given endpoint
+    pub fn connect(endpoint: &SOLMarketEndpoint) -> io::Result<Self> {
+        let stream = endpoint.connect()?;
+        let writer = stream.try_clone()?;
+        let connection = Connection {
+            reader: BufReader::new(stream),
+            writer,
+        };
+        let mut client = SOLMarketClient {
+            connection: RefCell::new(connection),
+            name: "",
+            subscribers: vec![],
+        };
+        client.fetch_name()?;
+        Ok(client)
+    }
+
+    /// Connects to the default endpoint and sends it a constructor request
+    fn connect_and_init(request: Request) -> Rc<RefCell<dyn Market>> {
+        let endpoint = SOLMarketEndpoint::from_env();
+        let mut client = SOLMarketClient::connect(&endpoint)
+            .unwrap_or_else(|e| panic!("Could not connect to the SOL market at {endpoint}: {e}"));
+        match client.call(request) {
+            Outcome::Done => {}
+            Outcome::Failure(reason) => panic!("The SOL market server refused to start: {reason}"),
+            _ => unexpected_answer(),
+        }
+        client
+            .fetch_name()
+            .unwrap_or_else(|e| panic!("Lost connection to the SOL market server: {e}"));
+        Rc::new(RefCell::new(client))
+    }
+
+    fn fetch_name(&mut self) -> io::Result<()> {
+        match self.send(Request::GetName)?.outcome {
+            Outcome::Name(name) => {
+                //The trait wants a &'static str. We leak once per connection.
+                self.name = Box::leak(name.into_boxed_str());
+                Ok(())
+            }
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "The server did not send its name",
+            )),
+        }
+    }
+
+    fn send(&self, request: Request) -> io::Result<Response> {
+        let mut connection = self.connection.borrow_mut();
+        let mut message = serde_json::to_string(&request)?;
+        message.push('\n');
+        connection.writer.write_all(message.as_bytes())?;
+        connection.writer.flush()?;
+
+        let mut line = String::new();
+        let read_bytes = connection.reader.read_line(&mut line)?;
+        if read_bytes == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "The server closed the connection",
+            ));
+        }
+        let response = serde_json::from_str(&line)?;
+        Ok(response)
+    }
+
+    /// Sends a request that does not change the market
+    fn query(&self, request: Request) -> Outcome {
+        self.send(request)
+            .unwrap_or_else(|e| panic!("Lost connection to the SOL market server: {e}"))
+            .outcome
+    }
+
+    /// Sends a request and notifies our subscribers of the events it caused
+    fn call(&mut self, request: Request) -> Outcome {
+        let response = self
+            .send(request)
+            .unwrap_or_else(|e| panic!("Lost connection to the SOL market server: {e}"));
+        for event in response.events {
+            for subscriber in &mut self.subscribers {
+                subscriber.on_event(event.0.clone());
+            }
+        }
+        response.outcome
+    }
+}
+
+fn unexpected_answer() -> ! {
+    panic!("The SOL market server sent an unexpected answer")
+}
+
+impl Notifiable for SOLMarketClient {
+    fn add_subscriber(&mut self, subscriber: Box<dyn Notifiable>) {
+        self.subscribers.push(subscriber);
+    }
+
+    fn on_event(&mut self, event: Event) {
+        match self.call(Request::OnEvent(event)) {
+            Outcome::Done => {}
+            _ => unexpected_answer(),
+        }
+    }
+}
+
+impl Market for SOLMarketClient {
+    fn new_random() -> Rc<RefCell<dyn Market>> {
+        Self::connect_and_init(Request::NewRandom)
+    }
+
+    fn new_with_quantities(eur: f32, yen: f32, usd: f32, yuan: f32) -> Rc<RefCell<dyn Market>> {
+        Self::connect_and_init(Request::NewWithQuantities {
+            eur,
+            yen,
+            usd,
+            yuan,
+        })
+    }
+
+    fn new_file(path: &str) -> Rc<RefCell<dyn Market>>
+    where
+        Self: Sized,
+    {
+        Self::connect_and_init(Request::NewFile {
+            path: path.to_string(),
+        })
+    }
+
+    fn get_name(&self) -> &'static str {
+        self.name
+    }
+
+    fn get_budget(&self) -> f32 {
+        match self.query(Request::GetBudget) {
+            Outcome::Budget(budget) => budget,
+            _ => unexpected_answer(),
+        }
+    }
+
+    fn get_buy_price(&self, kind: GoodKind, quantity: f32) -> Result<f32, MarketGetterError> {
+        match self.query(Request::GetBuyPrice { kind, quantity }) {
+            Outcome::Price(price) => Ok(price),
+            Outcome::PriceError(e) => Err(e),
+            _ => unexpected_answer(),
+        }
+    }
+
+    fn get_sell_price(&self, kind: GoodKind, quantity: f32) -> Result<f32, MarketGetterError> {
+        match self.query(Request::GetSellPrice { kind, quantity }) {
+            Outcome::Price(price) => Ok(price),
+            Outcome::PriceError(e) => Err(e),
+            _ => unexpected_answer(),
+        }
+    }
+
+    fn get_goods(&self) -> Vec<GoodLabel> {
+        match self.query(Request::GetGoods) {
+            Outcome::Goods(labels) => labels.into_iter().map(|l| l.0).collect(),
+            _ => unexpected_answer(),
+        }
+    }
+
+    fn lock_buy(
+        &mut self,
+        kind_to_buy: GoodKind,
+        quantity_to_buy: f32,
+        bid: f32,
+        trader_name: String,
+    ) -> Result<String, LockBuyError> {
+        let request = Request::LockBuy {
+            kind_to_buy,
+            quantity_to_buy,
+            bid,
+            trader_name,
+        };
+        match self.call(request) {
+            Outcome::Token(token) => Ok(token),
+            Outcome::LockBuyError(e) => Err(e),
+            _ => unexpected_answer(),
+        }
+    }
+
+    fn buy(&mut self, token: String, cash: &mut Good) -> Result<Good, BuyError> {
+        let request = Request::Buy {
+            token,
+            cash_kind: cash.get_kind(),
+            cash: cash.get_qty(),
+        };
+        match self.call(request) {
+            Outcome::Traded {
+                given_kind,
+                given,
+                left,
+            } => {
+                *cash = Good::new(cash.get_kind(), left);
+                Ok(Good::new(given_kind, given))
+            }
+            Outcome::BuyError(e) => Err(e),
+            _ => unexpected_answer(),
+        }
+    }
+
+    fn lock_sell(
+        &mut self,
+        kind_to_sell: GoodKind,
+        quantity_to_sell: f32,
+        offer: f32,
+        trader_name: String,
+    ) -> Result<String, LockSellError> {
+        let request = Request::LockSell {
+            kind_to_sell,
+            quantity_to_sell,
+            offer,
+            trader_name,
+        };
+        match self.call(request) {
+            Outcome::Token(token) => Ok(token),
+            Outcome::LockSellError(e) => Err(e),
+            _ => unexpected_answer(),
+        }
+    }
+
+    fn sell(&mut self, token: String, good: &mut Good) -> Result<Good, SellError> {
+        let request = Request::Sell {
+            token,
+            good_kind: good.get_kind(),
+            quantity: good.get_qty(),
+        };
+        match self.call(request) {
+            Outcome::Traded {
+                given_kind,
+                given,
+                left,
+            } => {
+                *good = Good::new(good.get_kind(), left);
+                Ok(Good::new(given_kind, given))
+            }
+            Outcome::SellError(e) => Err(e),
+            _ => unexpected_answer(),
+        }
+    }
+}
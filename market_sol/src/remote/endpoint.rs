@@ -0,0 +1,200 @@
+use std::{
+    fmt::Display,
+    io::{self, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    str::FromStr,
+};
+#[cfg(unix)]
+use std::{
+    os::unix::{
+        fs::FileTypeExt,
+        net::{UnixListener, UnixStream},
+    },
+    path::PathBuf,
+};
+
+/// Endpoint used when none is specified
+pub const DEFAULT_ENDPOINT: &str = "127.0.0.1:7654";
+/// Environment variable the client constructors of the `Market` trait read
+/// the endpoint from
+pub const ENDPOINT_ENV_VAR: &str = "SOL_MARKET_ENDPOINT";
+/// Prefix that marks an endpoint as a Unix domain socket
+const UNIX_PREFIX: &str = "unix:";
+
+/// Where a SOL market server listens.
+///
+/// It can be parsed from a string: `unix:/tmp/sol.sock` is a Unix domain
+/// socket, anything else is expected to be a TCP address like `127.0.0.1:7654`.
+/// Unix domain sockets are only available on Unix platforms.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SOLMarketEndpoint {
+    Tcp(SocketAddr),
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl SOLMarketEndpoint {
+    /// Returns the endpoint in the [`ENDPOINT_ENV_VAR`] environment variable,
+    /// or [`DEFAULT_ENDPOINT`] if it is not set
+    pub fn from_env() -> Self {
+        std::env::var(ENDPOINT_ENV_VAR)
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn connect(&self) -> io::Result<Stream> {
+        match self {
+            SOLMarketEndpoint::Tcp(addr) => TcpStream::connect(addr).map(Stream::Tcp),
+            #[cfg(unix)]
+            SOLMarketEndpoint::Unix(path) => UnixStream::connect(path).map(Stream::Unix),
+        }
+    }
+
+    /// Listens on the endpoint. TCP addresses other than the loopback are
+    /// refused unless `allow_remote`, since the protocol has no
+    /// authentication.
+    pub(crate) fn bind(&self, allow_remote: bool) -> io::Result<Listener> {
+        match self {
+            SOLMarketEndpoint::Tcp(addr) if !allow_remote && !addr.ip().is_loopback() => {
+                Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    format!("{addr} can be reached from other machines"),
+                ))
+            }
+            SOLMarketEndpoint::Tcp(addr) => TcpListener::bind(addr).map(Listener::Tcp),
+            #[cfg(unix)]
+            SOLMarketEndpoint::Unix(path) => {
+                //A stale socket file would make the bind fail, anything else
+                //at the path is not ours to remove
+                match std::fs::symlink_metadata(path) {
+                    Ok(metadata) if metadata.file_type().is_socket() => std::fs::remove_file(path)?,
+                    Ok(_) => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::AlreadyExists,
+                            format!("{} exists and is not a socket", path.display()),
+                        ))
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                    Err(e) => return Err(e),
+                }
+                UnixListener::bind(path).map(Listener::Unix)
+            }
+        }
+    }
+}
+
+impl Default for SOLMarketEndpoint {
+    fn default() -> Self {
+        DEFAULT_ENDPOINT
+            .parse()
+            .expect("The default endpoint is valid")
+    }
+}
+
+impl FromStr for SOLMarketEndpoint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_prefix(UNIX_PREFIX) {
+            #[cfg(unix)]
+            Some(path) if !path.is_empty() => Ok(SOLMarketEndpoint::Unix(PathBuf::from(path))),
+            #[cfg(unix)]
+            Some(_) => Err(format!("'{s}' does not specify the socket path")),
+            #[cfg(not(unix))]
+            Some(_) => Err(format!("'{s}': Unix domain sockets are not supported here")),
+            None => s
+                .parse::<SocketAddr>()
+                .map(SOLMarketEndpoint::Tcp)
+                .map_err(|e| format!("'{s}' is not a valid endpoint: {e}")),
+        }
+    }
+}
+
+impl Display for SOLMarketEndpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SOLMarketEndpoint::Tcp(addr) => write!(f, "{addr}"),
+            #[cfg(unix)]
+            SOLMarketEndpoint::Unix(path) => write!(f, "{UNIX_PREFIX}{}", path.display()),
+        }
+    }
+}
+
+/// A connected socket, of either kind
+pub(crate) enum Stream {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl Stream {
+    pub(crate) fn try_clone(&self) -> io::Result<Self> {
+        match self {
+            Stream::Tcp(s) => s.try_clone().map(Stream::Tcp),
+            #[cfg(unix)]
+            Stream::Unix(s) => s.try_clone().map(Stream::Unix),
+        }
+    }
+}
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(s) => s.read(buf),
+            #[cfg(unix)]
+            Stream::Unix(s) => s.read(buf),
+        }
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(s) => s.write(buf),
+            #[cfg(unix)]
+            Stream::Unix(s) => s.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Stream::Tcp(s) => s.flush(),
+            #[cfg(unix)]
+            Stream::Unix(s) => s.flush(),
+        }
+    }
+}
+
+/// A listening socket, of either kind
+pub(crate) enum Listener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixListener),
+}
+
+impl Listener {
+    pub(crate) fn accept(&self) -> io::Result<Stream> {
+        match self {
+            Listener::Tcp(l) => l.accept().map(|(s, _)| Stream::Tcp(s)),
+            #[cfg(unix)]
+            Listener::Unix(l) => l.accept().map(|(s, _)| Stream::Unix(s)),
+        }
+    }
+
+    /// The endpoint this listener is actually bound to. Useful when binding
+    /// TCP on port 0.
+    pub(crate) fn local_endpoint(&self) -> io::Result<SOLMarketEndpoint> {
+        match self {
+            Listener::Tcp(l) => l.local_addr().map(SOLMarketEndpoint::Tcp),
+            #[cfg(unix)]
+            Listener::Unix(l) => {
+                let addr = l.local_addr()?;
+                let path = addr.as_pathname().ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::NotFound, "The socket is not bound to a path")
+                })?;
+                Ok(SOLMarketEndpoint::Unix(path.to_path_buf()))
+            }
+        }
+    }
+}
@@ -0,0 +1,14 @@
+//! Run the SOL market in its own process and talk to it over a local socket.
+//!
+//! [`SOLMarketServer`] hosts a market, [`SOLMarketClient`] implements the
+//! `Market` trait by forwarding every call to it. The messages are
+//! newline-delimited JSON.
+
+mod client;
+mod endpoint;
+mod protocol;
+mod server;
+
+pub use self::client::SOLMarketClient;
+pub use self::endpoint::{SOLMarketEndpoint, DEFAULT_ENDPOINT, ENDPOINT_ENV_VAR};
+pub use self::server::SOLMarketServer;
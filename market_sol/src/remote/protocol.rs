@@ -0,0 +1,226 @@
+//! JSON messages exchanged between [`super::SOLMarketClient`] and
+//! [`super::SOLMarketServer`]. Each message is serialized on a single line.
+//!
+//! The types of the market protocol do not implement serde's traits, so we
+//! mirror them here with serde's
+//! [remote derive](https://serde.rs/remote-derive.html).
+
+use serde::{Deserialize, Serialize};
+use unitn_market_2022::{
+    event::event::{Event, EventKind},
+    good::good_kind::GoodKind,
+    market::{
+        good_label::GoodLabel, BuyError, LockBuyError, LockSellError, MarketGetterError, SellError,
+    },
+};
+
+/// What the client asks the server to do
+#[derive(Serialize, Deserialize)]
+pub(crate) enum Request {
+    NewRandom,
+    NewWithQuantities {
+        eur: f32,
+        yen: f32,
+        usd: f32,
+        yuan: f32,
+    },
+    NewFile {
+        path: String,
+    },
+    GetName,
+    GetBudget,
+    GetBuyPrice {
+        kind: GoodKind,
+        quantity: f32,
+    },
+    GetSellPrice {
+        kind: GoodKind,
+        quantity: f32,
+    },
+    GetGoods,
+    LockBuy {
+        kind_to_buy: GoodKind,
+        quantity_to_buy: f32,
+        bid: f32,
+        trader_name: String,
+    },
+    /// `cash` is the quantity of DEFAULT_GOOD_KIND the trader hands over
+    Buy {
+        token: String,
+        cash_kind: GoodKind,
+        cash: f32,
+    },
+    LockSell {
+        kind_to_sell: GoodKind,
+        quantity_to_sell: f32,
+        offer: f32,
+        trader_name: String,
+    },
+    Sell {
+        token: String,
+        good_kind: GoodKind,
+        quantity: f32,
+    },
+    OnEvent(#[serde(with = "EventDef")] Event),
+}
+
+/// The answer of the server, along with the events the market emitted while
+/// serving the request
+#[derive(Serialize, Deserialize)]
+pub(crate) struct Response {
+    pub(crate) outcome: Outcome,
+    pub(crate) events: Vec<WireEvent>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) enum Outcome {
+    Done,
+    Name(String),
+    Budget(f32),
+    Price(f32),
+    PriceError(#[serde(with = "MarketGetterErrorDef")] MarketGetterError),
+    Goods(Vec<WireGoodLabel>),
+    Token(String),
+    LockBuyError(#[serde(with = "LockBuyErrorDef")] LockBuyError),
+    LockSellError(#[serde(with = "LockSellErrorDef")] LockSellError),
+    /// A trade went through. `given` is the good the market handed back and
+    /// `left` is what remains of the good the trader passed in.
+    Traded {
+        given_kind: GoodKind,
+        given: f32,
+        left: f32,
+    },
+    BuyError(#[serde(with = "BuyErrorDef")] BuyError),
+    SellError(#[serde(with = "SellErrorDef")] SellError),
+    /// The request could not be served at all
+    Failure(String),
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct WireEvent(#[serde(with = "EventDef")] pub(crate) Event);
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct WireGoodLabel(#[serde(with = "GoodLabelDef")] pub(crate) GoodLabel);
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "EventKind")]
+enum EventKindDef {
+    Bought,
+    Sold,
+    LockedBuy,
+    LockedSell,
+    Wait,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Event")]
+struct EventDef {
+    #[serde(with = "EventKindDef")]
+    kind: EventKind,
+    good_kind: GoodKind,
+    quantity: f32,
+    price: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "GoodLabel")]
+struct GoodLabelDef {
+    good_kind: GoodKind,
+    quantity: f32,
+    exchange_rate_buy: f32,
+    exchange_rate_sell: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "MarketGetterError")]
+enum MarketGetterErrorDef {
+    NonPositiveQuantityAsked,
+    InsufficientGoodQuantityAvailable {
+        requested_good_kind: GoodKind,
+        requested_good_quantity: f32,
+        available_good_quantity: f32,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "LockBuyError")]
+enum LockBuyErrorDef {
+    NonPositiveQuantityToBuy {
+        negative_quantity_to_buy: f32,
+    },
+    NonPositiveBid {
+        negative_bid: f32,
+    },
+    MaxAllowedLocksReached,
+    InsufficientGoodQuantityAvailable {
+        requested_good_kind: GoodKind,
+        requested_good_quantity: f32,
+        available_good_quantity: f32,
+    },
+    BidTooLow {
+        requested_good_kind: GoodKind,
+        requested_good_quantity: f32,
+        low_bid: f32,
+        lowest_acceptable_bid: f32,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "BuyError")]
+enum BuyErrorDef {
+    UnrecognizedToken {
+        unrecognized_token: String,
+    },
+    ExpiredToken {
+        expired_token: String,
+    },
+    GoodKindNotDefault {
+        non_default_good_kind: GoodKind,
+    },
+    InsufficientGoodQuantity {
+        contained_quantity: f32,
+        pre_agreed_quantity: f32,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "LockSellError")]
+enum LockSellErrorDef {
+    NonPositiveQuantityToSell {
+        negative_quantity_to_sell: f32,
+    },
+    NonPositiveOffer {
+        negative_offer: f32,
+    },
+    MaxAllowedLocksReached,
+    InsufficientDefaultGoodQuantityAvailable {
+        offered_good_kind: GoodKind,
+        offered_good_quantity: f32,
+        available_good_quantity: f32,
+    },
+    OfferTooHigh {
+        offered_good_kind: GoodKind,
+        offered_good_quantity: f32,
+        high_offer: f32,
+        highest_acceptable_offer: f32,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "SellError")]
+enum SellErrorDef {
+    UnrecognizedToken {
+        unrecognized_token: String,
+    },
+    ExpiredToken {
+        expired_token: String,
+    },
+    WrongGoodKind {
+        wrong_good_kind: GoodKind,
+        pre_agreed_kind: GoodKind,
+    },
+    InsufficientGoodQuantity {
+        contained_quantity: f32,
+        pre_agreed_quantity: f32,
+    },
+}
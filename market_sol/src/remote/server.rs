@@ -0,0 +1,288 @@
+use super::{
+    endpoint::{Listener, SOLMarketEndpoint, Stream},
+    protocol::{Outcome, Request, Response, WireEvent, WireGoodLabel},
+};
+use crate::sol_market::SOLMarket;
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    io::{self, BufRead, BufReader, Write},
+    rc::Rc,
+    sync::mpsc::{self, RecvTimeoutError, Sender},
+    thread,
+    time::Duration,
+};
+use unitn_market_2022::{
+    event::{event::Event, notifiable::Notifiable},
+    good::good::Good,
+    market::Market,
+};
+
+/// A request waiting to be served, along with where to send the response
+type Job = (Request, Sender<Response>);
+
+/// How often an idle server lets the market catch up with its clock
+const CLOCK_SYNC_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Hosts a [`SOLMarket`] and lets other processes use it through a
+/// [`super::SOLMarketClient`].
+///
+/// Every connection is read on its own thread, but all the requests are
+/// served one at a time by the thread that called `serve`, which is the
+/// only one that touches the market.
+pub struct SOLMarketServer {
+    listener: Listener,
+}
+
+impl SOLMarketServer {
+    /// Starts listening on the given endpoint. Requests are not served until
+    /// `serve` is called.
+    ///
+    /// TCP endpoints must be on the loopback: use `bind_remote` to be
+    /// reachable from other machines.
+    pub fn bind(endpoint: &SOLMarketEndpoint) -> io::Result<Self> {
+        let listener = endpoint.bind(false)?;
+        Ok(SOLMarketServer { listener })
+    }
+
+    /// Like `bind`, but also listens on TCP addresses other machines can
+    /// reach. Anyone who reaches the endpoint can use the market: there is
+    /// no authentication.
+    pub fn bind_remote(endpoint: &SOLMarketEndpoint) -> io::Result<Self> {
+        let listener = endpoint.bind(true)?;
+        Ok(SOLMarketServer { listener })
+    }
+
+    /// Returns the endpoint clients should connect to
+    pub fn local_endpoint(&self) -> io::Result<SOLMarketEndpoint> {
+        self.listener.local_endpoint()
+    }
+
+    /// Serves requests forever.
+    ///
+    /// If `state_file` is provided, the market is restored from there (and
+    /// saved there when replaced), otherwise it is initialized randomly.
+    pub fn serve(self, state_file: Option<&str>) -> io::Result<()> {
+        let market = match state_file {
+            Some(path) => SOLMarket::new_file_internal(path),
+            None => SOLMarket::new_random_path(None),
+        };
+        self.serve_market(market)
+    }
+
+    pub(crate) fn serve_market(self, market: Rc<RefCell<SOLMarket>>) -> io::Result<()> {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let listener = self.listener;
+        thread::spawn(move || loop {
+            match listener.accept() {
+                Ok(stream) => {
+                    let sender = sender.clone();
+                    thread::spawn(move || {
+                        if let Err(e) = handle_connection(stream, sender) {
+                            eprintln!("SOL market server dropped a connection: {e}");
+                        }
+                    });
+                }
+                Err(e) => eprintln!("SOL market server could not accept a connection: {e}"),
+            }
+        });
+
+        let mut host = Host::new(market);
+        loop {
+            match receiver.recv_timeout(CLOCK_SYNC_INTERVAL) {
+                Ok((request, reply_to)) => {
+                    let response = host.handle(request);
+                    //The connection may be gone already, nothing to do about it
+                    let _ = reply_to.send(response);
+                }
+                //Keep a real-time market going while nobody trades
+                Err(RecvTimeoutError::Timeout) => {
+                    host.market.borrow_mut().sync_clock();
+                }
+                Err(RecvTimeoutError::Disconnected) => return Ok(()),
+            }
+        }
+    }
+}
+
+/// Reads one request per line and writes back one response per line
+fn handle_connection(stream: Stream, jobs: Sender<Job>) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    let reader = BufReader::new(stream);
+    for line in reader.lines() {
+        let line = line?;
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => {
+                let (reply_to, reply) = mpsc::channel();
+                jobs.send((request, reply_to))
+                    .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "Server stopped"))?;
+                reply
+                    .recv()
+                    .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "Server stopped"))?
+            }
+            Err(e) => Response {
+                outcome: Outcome::Failure(format!("Malformed request: {e}")),
+                events: vec![],
+            },
+        };
+        let mut message = serde_json::to_string(&response)?;
+        message.push('\n');
+        writer.write_all(message.as_bytes())?;
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+/// Owns the hosted market and turns requests into calls on it
+struct Host {
+    market: Rc<RefCell<SOLMarket>>,
+    /// Events the market emitted since the last response
+    emitted: Rc<RefCell<Vec<Event>>>,
+}
+
+impl Host {
+    fn new(market: Rc<RefCell<SOLMarket>>) -> Self {
+        let mut host = Host {
+            market: Rc::clone(&market),
+            emitted: Rc::new(RefCell::new(vec![])),
+        };
+        host.replace_market(market);
+        host
+    }
+
+    /// Hosts a new market, subscribing to its events
+    fn replace_market(&mut self, market: Rc<RefCell<SOLMarket>>) {
+        let recorder = EventRecorder {
+            events: Rc::clone(&self.emitted),
+        };
+        market.borrow_mut().add_subscriber(Box::new(recorder));
+        self.market = market;
+    }
+
+    fn handle(&mut self, request: Request) -> Response {
+        let outcome = match request {
+            Request::NewRandom => {
+                self.replace_market(SOLMarket::new_random_path(None));
+                Outcome::Done
+            }
+            Request::NewWithQuantities {
+                eur,
+                yen,
+                usd,
+                yuan,
+            } => {
+                match SOLMarket::try_new_with_quantities_and_path(
+                    eur,
+                    yen,
+                    usd,
+                    yuan,
+                    None,
+                    HashMap::new(),
+                ) {
+                    Ok(market) => {
+                        self.replace_market(market);
+                        Outcome::Done
+                    }
+                    Err(e) => Outcome::Failure(e.to_string()),
+                }
+            }
+            Request::NewFile { path } => {
+                self.replace_market(SOLMarket::new_file_internal(&path));
+                Outcome::Done
+            }
+            request => self.handle_market_call(request),
+        };
+        let events = self.emitted.borrow_mut().drain(..).map(WireEvent).collect();
+        Response { outcome, events }
+    }
+
+    fn handle_market_call(&mut self, request: Request) -> Outcome {
+        let mut market = self.market.borrow_mut();
+        match request {
+            Request::GetName => Outcome::Name(market.get_name().to_string()),
+            Request::GetBudget => Outcome::Budget(market.get_budget()),
+            Request::GetBuyPrice { kind, quantity } => match market.get_buy_price(kind, quantity) {
+                Ok(price) => Outcome::Price(price),
+                Err(e) => Outcome::PriceError(e),
+            },
+            Request::GetSellPrice { kind, quantity } => {
+                match market.get_sell_price(kind, quantity) {
+                    Ok(price) => Outcome::Price(price),
+                    Err(e) => Outcome::PriceError(e),
+                }
+            }
+            Request::GetGoods => {
+                Outcome::Goods(market.get_goods().into_iter().map(WireGoodLabel).collect())
+            }
+            Request::LockBuy {
+                kind_to_buy,
+                quantity_to_buy,
+                bid,
+                trader_name,
+            } => match market.lock_buy(kind_to_buy, quantity_to_buy, bid, trader_name) {
+                Ok(token) => Outcome::Token(token),
+                Err(e) => Outcome::LockBuyError(e),
+            },
+            Request::Buy {
+                token,
+                cash_kind,
+                cash,
+            } => {
+                let mut cash = Good::new(cash_kind, cash);
+                match market.buy(token, &mut cash) {
+                    Ok(good) => Outcome::Traded {
+                        given_kind: good.get_kind(),
+                        given: good.get_qty(),
+                        left: cash.get_qty(),
+                    },
+                    Err(e) => Outcome::BuyError(e),
+                }
+            }
+            Request::LockSell {
+                kind_to_sell,
+                quantity_to_sell,
+                offer,
+                trader_name,
+            } => match market.lock_sell(kind_to_sell, quantity_to_sell, offer, trader_name) {
+                Ok(token) => Outcome::Token(token),
+                Err(e) => Outcome::LockSellError(e),
+            },
+            Request::Sell {
+                token,
+                good_kind,
+                quantity,
+            } => {
+                let mut good = Good::new(good_kind, quantity);
+                match market.sell(token, &mut good) {
+                    Ok(money) => Outcome::Traded {
+                        given_kind: money.get_kind(),
+                        given: money.get_qty(),
+                        left: good.get_qty(),
+                    },
+                    Err(e) => Outcome::SellError(e),
+                }
+            }
+            Request::OnEvent(event) => {
+                market.on_event(event);
+                Outcome::Done
+            }
+            Request::NewRandom | Request::NewWithQuantities { .. } | Request::NewFile { .. } => {
+                Outcome::Failure(String::from("Constructors are not market calls"))
+            }
+        }
+    }
+}
+
+/// Subscriber that keeps the events of the hosted market, so that they can
+/// be sent back to the client that caused them
+struct EventRecorder {
+    events: Rc<RefCell<Vec<Event>>>,
+}
+
+impl Notifiable for EventRecorder {
+    fn add_subscriber(&mut self, _subscriber: Box<dyn Notifiable>) {}
+
+    fn on_event(&mut self, event: Event) {
+        self.events.borrow_mut().push(event);
+    }
+}
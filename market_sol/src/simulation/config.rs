@@ -0,0 +1,80 @@
+use crate::sol_market::{GoodDefinition, MarketDefinition, SOLMarketError, ALL_GOOD_KINDS};
+
+/// What to simulate
+#[derive(Debug, Clone)]
+pub struct SimulationConfig {
+    /// How many markets to run, one after the other
+    pub markets: u32,
+    /// How many days to run each market for
+    pub days: u32,
+    /// Market `i` (from 0) is seeded with `seed + i`
+    pub seed: u64,
+    /// Goods, weights and configuration of every market. Its seed is
+    /// replaced by the one of each market.
+    pub definition: MarketDefinition,
+    /// Synthetic trades made every day. `None` leaves the markets alone.
+    pub flow: Option<TraderFlow>,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        SimulationConfig {
+            markets: 1,
+            days: 365,
+            seed: 0,
+            definition: MarketDefinition {
+                goods: ALL_GOOD_KINDS
+                    .into_iter()
+                    .map(|kind| GoodDefinition::new(kind, 10000.0))
+                    .collect(),
+                ..Default::default()
+            },
+            flow: None,
+        }
+    }
+}
+
+/// Synthetic trades: every day, a trader picks a good other than
+/// DEFAULT_GOOD_KIND at random and buys or sells some of it at the price the
+/// market asks.
+///
+/// Each lock and each trade moves the market forward by a day, like for any
+/// trader, so a day of the simulation can be more than a day of the market.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TraderFlow {
+    pub trades_per_day: u32,
+    /// Probability that a trade is a buy rather than a sell
+    pub buy_probability: f64,
+    /// The value of a trade, in DEFAULT_GOOD_KIND, is drawn uniformly up to
+    /// this
+    pub max_trade_value: f32,
+}
+
+impl TraderFlow {
+    /// Checks that the trades can be drawn
+    pub(crate) fn validate(&self) -> Result<(), SOLMarketError> {
+        if !(0.0..=1.0).contains(&self.buy_probability) {
+            return Err(SOLMarketError::InvalidConfig {
+                parameter: "flow.buy_probability",
+                value: self.buy_probability as f32,
+            });
+        }
+        if !(self.max_trade_value.is_finite() && self.max_trade_value >= 0.0) {
+            return Err(SOLMarketError::InvalidConfig {
+                parameter: "flow.max_trade_value",
+                value: self.max_trade_value,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Default for TraderFlow {
+    fn default() -> Self {
+        TraderFlow {
+            trades_per_day: 1,
+            buy_probability: 0.5,
+            max_trade_value: 100.0,
+        }
+    }
+}
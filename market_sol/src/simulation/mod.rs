@@ -0,0 +1,16 @@
+//! Run SOL markets without traders attached, to study their prices.
+//!
+//! [`simulate`] runs several markets for some days, optionally with a
+//! [`TraderFlow`] of synthetic trades, and returns the price of every good at
+//! every step along with its [`GoodSummary`]. The `sol_simulator` binary
+//! writes them as CSV or JSON, and as an SVG chart with the `plot` feature.
+
+mod config;
+#[cfg(feature = "plot")]
+mod plot;
+mod result;
+mod simulator;
+
+pub use self::config::{SimulationConfig, TraderFlow};
+pub use self::result::{GoodSummary, MarketSummary, SeriesPoint, SimulationResult};
+pub use self::simulator::simulate;
@@ -0,0 +1,59 @@
+use super::{SeriesPoint, SimulationResult};
+use plotters::{
+    prelude::{ChartBuilder, IntoDrawingArea, SVGBackend},
+    series::LineSeries,
+    style::{RGBColor, WHITE},
+};
+use std::{error::Error, path::Path};
+
+const COLORS: [RGBColor; 5] = [
+    RGBColor(53, 129, 184),
+    RGBColor(246, 22, 52),
+    RGBColor(22, 152, 115),
+    RGBColor(241, 143, 1),
+    RGBColor(84, 13, 110),
+];
+
+impl SimulationResult {
+    /// Draws the price of each good other than DEFAULT_GOOD_KIND to an SVG
+    /// file, one chart per good and one line per market
+    pub fn plot_svg(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let kinds: Vec<_> = self.averages().map(|s| s.kind).collect();
+        let height = 360 * kinds.len().max(1) as u32;
+        let root = SVGBackend::new(path, (1280, height)).into_drawing_area();
+        root.fill(&WHITE)?;
+        let areas = root.split_evenly((kinds.len().max(1), 1));
+        for (kind, area) in kinds.iter().zip(areas.iter()) {
+            let points: Vec<&SeriesPoint> =
+                self.series.iter().filter(|p| p.kind == *kind).collect();
+            let last_step = points.iter().map(|p| p.step).max().unwrap_or(0).max(1);
+            let (mut min, mut max) = points.iter().fold((f32::MAX, f32::MIN), |(min, max), p| {
+                (min.min(p.price), max.max(p.price))
+            });
+            if max <= min {
+                (min, max) = (min - 1.0, min + 1.0);
+            }
+            let mut chart = ChartBuilder::on(area)
+                .caption(kind.to_string(), ("sans-serif", 24))
+                .margin(10)
+                .x_label_area_size(30)
+                .y_label_area_size(60)
+                .build_cartesian_2d(0..last_step, min..max)?;
+            chart
+                .configure_mesh()
+                .x_desc("Day")
+                .y_desc("EUR per unit")
+                .draw()?;
+            for market in self.markets.iter() {
+                let color = COLORS[market.market as usize % COLORS.len()];
+                let line = points
+                    .iter()
+                    .filter(|p| p.market == market.market)
+                    .map(|p| (p.step, p.price));
+                chart.draw_series(LineSeries::new(line, &color))?;
+            }
+        }
+        root.present()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,216 @@
+use serde::{Deserialize, Serialize};
+use std::io::{self, Write};
+use unitn_market_2022::good::{consts::DEFAULT_GOOD_KIND, good_kind::GoodKind};
+
+/// The state of a good in a market at a step of the simulation
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct SeriesPoint {
+    pub market: u32,
+    pub seed: u64,
+    /// Day of the simulation, from 0
+    pub step: u32,
+    /// Day of the market, which trades move forward too
+    pub day: u32,
+    pub kind: GoodKind,
+    /// How much of the good the market has available
+    pub quantity: f32,
+    /// Rate (EUR/kind) applied when a trader buys
+    pub buy_rate: f32,
+    /// Rate (EUR/kind) applied when a trader sells
+    pub sell_rate: f32,
+    /// What a unit of the good costs, in DEFAULT_GOOD_KIND
+    pub price: f32,
+}
+
+/// How a market went
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MarketSummary {
+    pub market: u32,
+    pub seed: u64,
+    /// Day of the market at the end of the simulation
+    pub final_day: u32,
+    /// Synthetic trades made, and refused by the market
+    pub trades: u32,
+    pub rejected_trades: u32,
+}
+
+/// How the price of a good moved, in one market or, if `market` is `None`,
+/// on average over all of them
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct GoodSummary {
+    pub market: Option<u32>,
+    pub kind: GoodKind,
+    pub start_price: f32,
+    pub end_price: f32,
+    /// `end_price / start_price - 1`
+    pub change: f32,
+    /// Mean of the daily log returns
+    pub drift: f32,
+    /// Standard deviation of the daily log returns
+    pub volatility: f32,
+    /// Largest fall from a previous peak, as a share of the peak
+    pub max_drawdown: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SimulationResult {
+    /// Ordered by market, step and good
+    pub series: Vec<SeriesPoint>,
+    pub markets: Vec<MarketSummary>,
+    /// One per market and good other than DEFAULT_GOOD_KIND, then the
+    /// averages of each good
+    pub summaries: Vec<GoodSummary>,
+}
+
+impl SimulationResult {
+    pub(crate) fn new(series: Vec<SeriesPoint>, markets: Vec<MarketSummary>) -> Self {
+        let mut kinds: Vec<GoodKind> = vec![];
+        for point in series.iter() {
+            if point.kind != DEFAULT_GOOD_KIND && !kinds.contains(&point.kind) {
+                kinds.push(point.kind);
+            }
+        }
+        let mut summaries = vec![];
+        for market in markets.iter() {
+            for kind in kinds.iter() {
+                let prices: Vec<f32> = series
+                    .iter()
+                    .filter(|p| p.market == market.market && p.kind == *kind)
+                    .map(|p| p.price)
+                    .collect();
+                if let Some(summary) = summarize(Some(market.market), *kind, &prices) {
+                    summaries.push(summary);
+                }
+            }
+        }
+        let averages: Vec<GoodSummary> = kinds
+            .iter()
+            .filter_map(|kind| average(*kind, &summaries))
+            .collect();
+        summaries.extend(averages);
+        SimulationResult {
+            series,
+            markets,
+            summaries,
+        }
+    }
+
+    /// The averages over all the markets, one per good
+    pub fn averages(&self) -> impl Iterator<Item = &GoodSummary> {
+        self.summaries.iter().filter(|s| s.market.is_none())
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Writes the series as CSV, with a header
+    pub fn write_series_csv(&self, mut writer: impl Write) -> io::Result<()> {
+        writeln!(
+            writer,
+            "market,seed,step,day,kind,quantity,buy_rate,sell_rate,price"
+        )?;
+        for p in self.series.iter() {
+            writeln!(
+                writer,
+                "{},{},{},{},{},{},{},{},{}",
+                p.market,
+                p.seed,
+                p.step,
+                p.day,
+                p.kind,
+                p.quantity,
+                p.buy_rate,
+                p.sell_rate,
+                p.price
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Writes the summaries of the goods as CSV, with a header. The market
+    /// of the averages is `all`.
+    pub fn write_summary_csv(&self, mut writer: impl Write) -> io::Result<()> {
+        writeln!(
+            writer,
+            "market,kind,start_price,end_price,change,drift,volatility,max_drawdown"
+        )?;
+        for s in self.summaries.iter() {
+            let market = match s.market {
+                Some(market) => market.to_string(),
+                None => String::from("all"),
+            };
+            writeln!(
+                writer,
+                "{},{},{},{},{},{},{},{}",
+                market,
+                s.kind,
+                s.start_price,
+                s.end_price,
+                s.change,
+                s.drift,
+                s.volatility,
+                s.max_drawdown
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn summarize(market: Option<u32>, kind: GoodKind, prices: &[f32]) -> Option<GoodSummary> {
+    let start_price = *prices.first()?;
+    let end_price = *prices.last()?;
+    let returns: Vec<f32> = prices.windows(2).map(|w| (w[1] / w[0]).ln()).collect();
+    let drift = mean(&returns);
+    let volatility = if returns.len() > 1 {
+        let variance =
+            returns.iter().map(|r| (r - drift).powi(2)).sum::<f32>() / (returns.len() - 1) as f32;
+        variance.sqrt()
+    } else {
+        0.0
+    };
+    let mut peak = start_price;
+    let mut max_drawdown = 0f32;
+    for price in prices.iter() {
+        peak = peak.max(*price);
+        max_drawdown = max_drawdown.max((peak - price) / peak);
+    }
+    Some(GoodSummary {
+        market,
+        kind,
+        start_price,
+        end_price,
+        change: end_price / start_price - 1.0,
+        drift,
+        volatility,
+        max_drawdown,
+    })
+}
+
+fn average(kind: GoodKind, summaries: &[GoodSummary]) -> Option<GoodSummary> {
+    let of_kind: Vec<&GoodSummary> = summaries.iter().filter(|s| s.kind == kind).collect();
+    if of_kind.is_empty() {
+        return None;
+    }
+    let field = |f: fn(&GoodSummary) -> f32| -> f32 {
+        mean(&of_kind.iter().map(|s| f(s)).collect::<Vec<f32>>())
+    };
+    Some(GoodSummary {
+        market: None,
+        kind,
+        start_price: field(|s| s.start_price),
+        end_price: field(|s| s.end_price),
+        change: field(|s| s.change),
+        drift: field(|s| s.drift),
+        volatility: field(|s| s.volatility),
+        max_drawdown: field(|s| s.max_drawdown),
+    })
+}
+
+fn mean(values: &[f32]) -> f32 {
+    if values.is_empty() {
+        0.0
+    } else {
+        values.iter().sum::<f32>() / values.len() as f32
+    }
+}
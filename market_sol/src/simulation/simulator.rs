@@ -0,0 +1,107 @@
+use super::{MarketSummary, SeriesPoint, SimulationConfig, SimulationResult, TraderFlow};
+use crate::sol_market::{SOLMarket, SOLMarketError};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use std::{cell::RefCell, rc::Rc};
+use unitn_market_2022::{
+    good::{consts::DEFAULT_GOOD_KIND, good::Good, good_kind::GoodKind},
+    market::Market,
+    wait_one_day,
+};
+
+/// The name the synthetic trades are made with
+pub(crate) const FLOW_TRADER_NAME: &str = "sol_simulator";
+
+/// Runs the markets of the simulation and summarizes their prices
+pub fn simulate(config: &SimulationConfig) -> Result<SimulationResult, SOLMarketError> {
+    if let Some(flow) = &config.flow {
+        flow.validate()?;
+    }
+    let mut series = vec![];
+    let mut markets = vec![];
+    for index in 0..config.markets {
+        let seed = config.seed.wrapping_add(index as u64);
+        let mut definition = config.definition.clone();
+        definition.seed = Some(seed);
+        let market = SOLMarket::try_new_from_definition(&definition)?;
+        let mut rng = ChaCha20Rng::seed_from_u64(seed);
+        let mut summary = MarketSummary {
+            market: index,
+            seed,
+            final_day: 0,
+            trades: 0,
+            rejected_trades: 0,
+        };
+        for step in 0..=config.days {
+            record_prices(&market.borrow(), index, seed, step, &mut series);
+            if step == config.days {
+                break;
+            }
+            if let Some(flow) = &config.flow {
+                for _ in 0..flow.trades_per_day {
+                    match trade(&mut market.borrow_mut(), flow, &mut rng) {
+                        Some(_) => summary.trades += 1,
+                        None => summary.rejected_trades += 1,
+                    }
+                }
+            }
+            let dyn_market: Rc<RefCell<dyn Market>> = market.clone();
+            wait_one_day!(dyn_market);
+        }
+        summary.final_day = market.borrow().meta.current_day;
+        markets.push(summary);
+    }
+    Ok(SimulationResult::new(series, markets))
+}
+
+fn record_prices(
+    market: &SOLMarket,
+    index: u32,
+    seed: u64,
+    step: u32,
+    series: &mut Vec<SeriesPoint>,
+) {
+    for (kind, _) in market.goods_registry().traded() {
+        let buy_rate = market.get_good_buy_exchange_rate(kind);
+        series.push(SeriesPoint {
+            market: index,
+            seed,
+            step,
+            day: market.meta.current_day,
+            kind,
+            quantity: market.get_available_quantity(kind),
+            buy_rate,
+            sell_rate: market.get_good_sell_exchange_rate(kind),
+            price: 1.0 / buy_rate,
+        });
+    }
+}
+
+/// Makes a synthetic trade. `None` if the market refused it.
+fn trade(market: &mut SOLMarket, flow: &TraderFlow, rng: &mut ChaCha20Rng) -> Option<()> {
+    let kinds: Vec<GoodKind> = market
+        .goods_registry()
+        .traded()
+        .map(|(kind, _)| kind)
+        .filter(|kind| *kind != DEFAULT_GOOD_KIND)
+        .collect();
+    if kinds.is_empty() {
+        return None;
+    }
+    let kind = kinds[rng.gen_range(0..kinds.len())];
+    let value = rng.gen_range(0.0..=flow.max_trade_value);
+    let quantity = value * market.get_good_buy_exchange_rate(kind);
+    let trader_name = FLOW_TRADER_NAME.to_string();
+    if rng.gen_bool(flow.buy_probability) {
+        let bid = market.get_buy_price(kind, quantity).ok()?;
+        let token = market.lock_buy(kind, quantity, bid, trader_name).ok()?;
+        market
+            .buy(token, &mut Good::new(DEFAULT_GOOD_KIND, bid))
+            .ok()?;
+    } else {
+        let offer = market.get_sell_price(kind, quantity).ok()?;
+        let token = market.lock_sell(kind, quantity, offer, trader_name).ok()?;
+        market.sell(token, &mut Good::new(kind, quantity)).ok()?;
+    }
+    Some(())
+}
@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+use unitn_market_2022::good::good_kind::GoodKind;
+
+/// The call-auction mode: instead of filling every lock at once, the market
+/// collects them and clears them together every `every_days` days. See
+/// `SOLMarket::auction_order`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct AuctionConfig {
+    /// If false, locks are filled at once at the rate of the market
+    pub enabled: bool,
+    /// The auction clears on the days that are a multiple of this. Since
+    /// every lock advances the default clock by a day, 1 clears each lock on
+    /// its own: collect more of them, or use a `RealTimeClock`.
+    pub every_days: u32,
+}
+
+impl Default for AuctionConfig {
+    fn default() -> Self {
+        AuctionConfig {
+            enabled: false,
+            every_days: 1,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuctionSide {
+    /// The trader buys the good, from a `lock_buy`
+    Buy,
+    /// The trader sells the good, from a `lock_sell`
+    Sell,
+}
+
+/// A lock request waiting for (or done with) the auction
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AuctionOrder {
+    /// The token returned to the trader
+    pub token: String,
+    pub trader_name: String,
+    pub side: AuctionSide,
+    pub good_kind: GoodKind,
+    pub quantity: f32,
+    /// The bid or the offer, in DEFAULT_GOOD_KIND, for the whole quantity
+    pub limit: f32,
+    /// The day the order was placed
+    pub day: u32,
+    /// Held until the order is cleared, then on its lock, see
+    /// `LockDepositConfig`
+    pub deposit: f32,
+    pub status: AuctionStatus,
+}
+
+impl AuctionOrder {
+    /// The limit for a unit of the good
+    pub fn unit_limit(&self) -> f32 {
+        self.limit / self.quantity
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum AuctionStatus {
+    /// The auction did not clear yet
+    Pending,
+    /// The order is (maybe partly) filled: its token is now a lock of
+    /// `quantity` at `price`, to redeem with `buy` or `sell`
+    Filled {
+        day: u32,
+        /// The clearing price of a unit of the good, in DEFAULT_GOOD_KIND
+        clearing_price: f32,
+        quantity: f32,
+        /// What the trader pays (or is paid) for `quantity`, fee included
+        price: f32,
+        /// The fee included in `price`, see `FeeSchedule`
+        fee: f32,
+    },
+    /// Nothing could be matched at a price within the limit of the order.
+    /// `clearing_price` is `None` if the good did not trade at all.
+    Unfilled {
+        day: u32,
+        clearing_price: Option<f32>,
+    },
+}
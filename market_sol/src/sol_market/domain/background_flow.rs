@@ -0,0 +1,97 @@
+use crate::sol_market::SOLMarketError;
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+/// Added to the seed of the market, so that the background flow does not
+/// draw the same numbers as the stocastic price
+const BACKGROUND_FLOW_SEED_OFFSET: u64 = 0x5eed_f10e;
+
+/// The synthetic traders that trade against the inventory of the market
+/// every day. Each kind of trader makes its number of trades per day.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct BackgroundFlowConfig {
+    /// If false, there is no background flow
+    pub enabled: bool,
+    /// Trades per day that buy or sell a random good, at random
+    pub noise_trades: u32,
+    /// Trades per day that follow the trend: they buy the goods whose price
+    /// rose over the last `momentum_days` days, and sell the others
+    pub momentum_trades: u32,
+    /// Trades per day that take `liquidity_trade_value` of a random good at
+    /// once, on a random side
+    pub liquidity_trades: u32,
+    /// The noise and momentum trades are worth up to this, in
+    /// DEFAULT_GOOD_KIND at the rate of the market
+    pub max_trade_value: f32,
+    /// The value of each liquidity trade, in DEFAULT_GOOD_KIND
+    pub liquidity_trade_value: f32,
+    /// How many days back the momentum traders look at
+    pub momentum_days: u32,
+}
+
+impl Default for BackgroundFlowConfig {
+    fn default() -> Self {
+        BackgroundFlowConfig {
+            enabled: false,
+            noise_trades: 5,
+            momentum_trades: 2,
+            liquidity_trades: 1,
+            max_trade_value: 100.0,
+            liquidity_trade_value: 1000.0,
+            momentum_days: 5,
+        }
+    }
+}
+
+impl BackgroundFlowConfig {
+    /// Checks that the trades have a value the traders can draw
+    pub(crate) fn validate(&self) -> Result<(), SOLMarketError> {
+        for (parameter, value) in [
+            ("background_flow.max_trade_value", self.max_trade_value),
+            (
+                "background_flow.liquidity_trade_value",
+                self.liquidity_trade_value,
+            ),
+        ] {
+            if !(value.is_finite() && value >= 0.0) {
+                return Err(SOLMarketError::InvalidConfig { parameter, value });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Who made a background trade
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BackgroundTrader {
+    Noise,
+    Momentum,
+    LiquidityTaker,
+}
+
+/// The state of the background flow
+#[derive(Debug, Clone)]
+pub(crate) struct BackgroundFlow {
+    pub rng: ChaCha20Rng,
+    /// The buy rate of each good at the end of the last days, from the
+    /// oldest
+    pub rates: HashMap<String, VecDeque<f32>>,
+}
+
+impl BackgroundFlow {
+    pub fn new(seed: Option<u64>) -> Self {
+        let rng = match seed {
+            Some(seed) => {
+                ChaCha20Rng::seed_from_u64(seed.wrapping_add(BACKGROUND_FLOW_SEED_OFFSET))
+            }
+            None => ChaCha20Rng::from_entropy(),
+        };
+        BackgroundFlow {
+            rng,
+            rates: HashMap::new(),
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use chrono::{DateTime, Local};
+use std::{
+    cell::RefCell,
+    fmt::Debug,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+/// Where a market takes the time from: the wall-clock time written in its
+/// tokens and log lines, and how the market days go by.
+/// See `SOLMarket::set_clock`.
+pub trait Clock: Debug {
+    /// The wall-clock time
+    fn now(&self) -> DateTime<Local>;
+
+    /// How many market days went by since the last call. `on_event` is true
+    /// when the market asks because of an event (a lock, a trade or a wait),
+    /// false when it is only catching up, see `SOLMarket::sync_clock`.
+    fn elapsed_days(&mut self, on_event: bool) -> u32;
+}
+
+/// The default clock: the time of the system, and a day for every event
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EventClock;
+
+impl Clock for EventClock {
+    fn now(&self) -> DateTime<Local> {
+        Local::now()
+    }
+
+    fn elapsed_days(&mut self, on_event: bool) -> u32 {
+        u32::from(on_event)
+    }
+}
+
+/// A clock that only moves when told to, e.g., in tests.
+///
+/// Clones share the same time, so a test can keep one and give the other to
+/// the market. Events do not advance the days: `advance_days` does.
+#[derive(Debug, Clone)]
+pub struct SimulatedClock {
+    state: Rc<RefCell<SimulatedTime>>,
+}
+
+#[derive(Debug)]
+struct SimulatedTime {
+    now: DateTime<Local>,
+    /// Days advanced but not yet seen by the market
+    pending_days: u32,
+}
+
+impl SimulatedClock {
+    /// A clock stopped at `start`
+    pub fn new(start: DateTime<Local>) -> Self {
+        SimulatedClock {
+            state: Rc::new(RefCell::new(SimulatedTime {
+                now: start,
+                pending_days: 0,
+            })),
+        }
+    }
+
+    /// Moves the wall-clock time forward, the day does not change
+    pub fn advance(&self, duration: chrono::Duration) {
+        self.state.borrow_mut().now += duration;
+    }
+
+    /// Moves the market forward by `days`, on its next event or
+    /// `SOLMarket::sync_clock`. The wall-clock time does not change.
+    pub fn advance_days(&self, days: u32) {
+        self.state.borrow_mut().pending_days += days;
+    }
+}
+
+impl Clock for SimulatedClock {
+    fn now(&self) -> DateTime<Local> {
+        self.state.borrow().now
+    }
+
+    fn elapsed_days(&mut self, _on_event: bool) -> u32 {
+        std::mem::take(&mut self.state.borrow_mut().pending_days)
+    }
+}
+
+/// The time of the system, and a market day every `day_length` of it,
+/// whatever the traders do. See `MarketConfig::day_length_ms`.
+#[derive(Debug, Clone)]
+pub struct RealTimeClock {
+    day_length: Duration,
+    /// When the current market day started
+    day_start: Instant,
+}
+
+impl RealTimeClock {
+    /// The first day starts now. `day_length` should not be zero.
+    pub fn new(day_length: Duration) -> Self {
+        RealTimeClock {
+            day_length,
+            day_start: Instant::now(),
+        }
+    }
+
+    pub fn day_length(&self) -> Duration {
+        self.day_length
+    }
+}
+
+impl Clock for RealTimeClock {
+    fn now(&self) -> DateTime<Local> {
+        Local::now()
+    }
+
+    fn elapsed_days(&mut self, _on_event: bool) -> u32 {
+        if self.day_length.is_zero() {
+            return 0;
+        }
+        let elapsed = self.day_start.elapsed().as_nanos() / self.day_length.as_nanos();
+        let days = u32::try_from(elapsed).unwrap_or(u32::MAX);
+        self.day_start += self.day_length * days;
+        days
+    }
+}
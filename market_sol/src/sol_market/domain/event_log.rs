@@ -0,0 +1,259 @@
+use super::market_event::{EventError, MarketEvent, MarketState};
+use crate::sol_market::{SOLMarketError, ALL_GOOD_KINDS};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+/// How often the event log takes a snapshot of the state, how much of its
+/// history it keeps, and where it is saved, see `SOLMarket::events`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct EventLogConfig {
+    /// The events between two snapshots
+    pub snapshot_every: u32,
+    /// The snapshots kept: events before the oldest one are forgotten
+    pub snapshots_to_keep: usize,
+    /// The file every event is appended to, one JSON object per line, see
+    /// `SOLMarket::replay_journal`. `None` keeps the events in memory only.
+    pub journal_file: Option<String>,
+}
+
+impl Default for EventLogConfig {
+    fn default() -> Self {
+        EventLogConfig {
+            snapshot_every: 1000,
+            snapshots_to_keep: 10,
+            journal_file: None,
+        }
+    }
+}
+
+/// A line of the journal of the events
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+enum JournalEntry {
+    /// The market was created (or restored from its state file) in this
+    /// state. The events after it are numbered from 1 again.
+    Started {
+        state: Box<MarketState>,
+    },
+    Recorded {
+        seq: u64,
+        event: MarketEvent,
+    },
+}
+
+/// The events of the market, from its oldest kept snapshot on.
+///
+/// Events are numbered from 1 when the market is created, and a snapshot
+/// numbered `n` is the state after the event `n`. The state of the market
+/// itself is kept folded, so it never needs a replay.
+#[derive(Debug, Clone)]
+pub(crate) struct EventLog {
+    /// The state after the last event
+    state: MarketState,
+    /// The number of the last event
+    last_seq: u64,
+    /// From the oldest to the newest, never empty
+    snapshots: VecDeque<(u64, MarketState)>,
+    /// The events after the oldest snapshot
+    events: VecDeque<MarketEvent>,
+    /// Where the events are appended, see `EventLogConfig::journal_file`
+    journal: Option<PathBuf>,
+}
+
+impl EventLog {
+    /// A log starting from the given state
+    pub fn new(state: MarketState) -> Self {
+        Self {
+            snapshots: VecDeque::from([(0, state.clone())]),
+            state,
+            last_seq: 0,
+            events: VecDeque::new(),
+            journal: None,
+        }
+    }
+
+    /// Appends the events to the journal at `path`, from the current state
+    /// on. `None` keeps them in memory only.
+    pub fn with_journal(mut self, path: Option<&str>) -> Self {
+        self.journal = path.map(PathBuf::from);
+        self.write(&JournalEntry::Started {
+            state: Box::new(self.state.clone()),
+        });
+        self
+    }
+
+    /// A copy of the log that writes no journal, e.g., for a market in paper
+    /// mode
+    pub fn without_journal(&self) -> Self {
+        Self {
+            journal: None,
+            ..self.clone()
+        }
+    }
+
+    /// Appends an event, and takes a snapshot if it is time to. An event the
+    /// state refuses is not appended.
+    pub fn record(
+        &mut self,
+        event: MarketEvent,
+        config: &EventLogConfig,
+    ) -> Result<(), EventError> {
+        self.state.apply(&event)?;
+        self.last_seq += 1;
+        if self.journal.is_some() {
+            self.write(&JournalEntry::Recorded {
+                seq: self.last_seq,
+                event: event.clone(),
+            });
+        }
+        self.events.push_back(event);
+        let newest_seq = self.snapshots[self.snapshots.len() - 1].0;
+        if self.last_seq - newest_seq < u64::from(config.snapshot_every.max(1)) {
+            return Ok(());
+        }
+        self.snapshots
+            .push_back((self.last_seq, self.state.clone()));
+        while self.snapshots.len() > config.snapshots_to_keep.max(1) {
+            self.snapshots.pop_front();
+        }
+        let oldest_seq = self.snapshots[0].0;
+        let forgotten = self.events.len() - (self.last_seq - oldest_seq) as usize;
+        self.events.drain(..forgotten);
+        Ok(())
+    }
+
+    pub fn state(&self) -> &MarketState {
+        &self.state
+    }
+
+    /// The number of the first event kept
+    pub fn first_seq(&self) -> u64 {
+        self.snapshots[0].0 + 1
+    }
+
+    pub fn last_seq(&self) -> u64 {
+        self.last_seq
+    }
+
+    /// The events kept, with their numbers
+    pub fn events(&self) -> impl Iterator<Item = (u64, &MarketEvent)> {
+        (self.first_seq()..).zip(self.events.iter())
+    }
+
+    /// The state after the event `seq`, replayed from the closest snapshot.
+    /// `None` if the event is not kept, or did not happen yet.
+    pub fn state_at(&self, seq: u64) -> Option<MarketState> {
+        if seq > self.last_seq {
+            return None;
+        }
+        let (snapshot_seq, snapshot) = self.snapshots.iter().rev().find(|(s, _)| *s <= seq)?;
+        let mut state = snapshot.clone();
+        let skip = (snapshot_seq + 1 - self.first_seq()) as usize;
+        let replay = (seq - snapshot_seq) as usize;
+        for event in self.events.iter().skip(skip).take(replay) {
+            //The events kept were all applied once
+            state.apply(event).ok()?;
+        }
+        Some(state)
+    }
+
+    /// Appends an entry to the journal, if any
+    fn write(&self, entry: &JournalEntry) {
+        let path = match &self.journal {
+            Some(path) => path,
+            None => return,
+        };
+        let written = serde_json::to_string(entry)
+            .map_err(std::io::Error::from)
+            .and_then(|line| {
+                OpenOptions::new()
+                    .append(true)
+                    .create(true)
+                    .open(path)
+                    .and_then(|mut file| writeln!(file, "{line}"))
+            });
+        if let Err(e) = written {
+            eprintln!("Error while writing to file {}", e);
+        }
+    }
+
+    /// The state after the event `seq` of the last start of the market in
+    /// the journal at `path`, or after its last event if `None`
+    pub fn replay(path: &Path, seq: Option<u64>) -> Result<MarketState, SOLMarketError> {
+        let content = fs::read_to_string(path).map_err(|source| SOLMarketError::Io {
+            path: path.to_path_buf(),
+            action: "read",
+            source,
+        })?;
+        let parse_error = |line: usize, reason: String| SOLMarketError::Parse {
+            path: path.to_path_buf(),
+            line,
+            reason,
+        };
+        //The line of the last start, and the entries after it
+        let mut start: Option<(usize, MarketState)> = None;
+        let mut entries: Vec<(usize, u64, MarketEvent)> = vec![];
+        for (i, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: JournalEntry =
+                serde_json::from_str(line).map_err(|e| parse_error(i + 1, e.to_string()))?;
+            match entry {
+                JournalEntry::Started { state } => {
+                    start = Some((i + 1, *state));
+                    entries.clear();
+                }
+                JournalEntry::Recorded { seq, event } => entries.push((i + 1, seq, event)),
+            }
+        }
+        let (start_line, mut state) = match start {
+            Some(start) => start,
+            None => return Err(parse_error(1, "the market never started".to_string())),
+        };
+
+        let last_seq = entries.last().map(|(_, seq, _)| *seq).unwrap_or(0);
+        let until = seq.unwrap_or(last_seq);
+        if until > last_seq {
+            return Err(SOLMarketError::Replay {
+                path: path.to_path_buf(),
+                line: start_line,
+                reason: format!("event {until} did not happen, the last one is {last_seq}"),
+            });
+        }
+        for (expected, (line, seq, event)) in (1..=until).zip(entries) {
+            let replay_error = |reason: String| SOLMarketError::Replay {
+                path: path.to_path_buf(),
+                line,
+                reason,
+            };
+            if seq != expected {
+                return Err(replay_error(format!(
+                    "expected event {expected}, found {seq}"
+                )));
+            }
+            state
+                .apply(&event)
+                .map_err(|e| replay_error(format!("event {seq} was refused: {e}")))?;
+        }
+        Ok(state)
+    }
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        Self::new(MarketState {
+            day: 1,
+            tickers: ALL_GOOD_KINDS
+                .iter()
+                .map(|kind| (*kind, kind.to_string()))
+                .collect(),
+            ..Default::default()
+        })
+    }
+}
@@ -0,0 +1,136 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+/// The fees the market charges on each trade, on top of the margin of its
+/// rates. See `SOLMarket::get_fee`.
+///
+/// Fees are in DEFAULT_GOOD_KIND: traders pay them on top of the price of
+/// what they buy, and get them off the price of what they sell.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct FeeSchedule {
+    /// The days a trader's volume is summed over, to find its tier
+    pub window_days: u32,
+    // The tables are last, since TOML writes them after plain values
+    /// Charged when a trader buys from the market
+    pub buy: Fee,
+    /// Charged when a trader sells to the market
+    pub sell: Fee,
+    /// Charged, on either side, on the auction orders filled at a clearing:
+    /// they rested in the book and made the clearing price. Without it,
+    /// they pay `buy` or `sell` like any other trade.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maker: Option<Fee>,
+    /// Discounts for the traders that trade the most. The tier with the
+    /// highest `min_volume` within the volume of a trader applies.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tiers: Vec<FeeTier>,
+}
+
+impl Default for FeeSchedule {
+    fn default() -> Self {
+        FeeSchedule {
+            window_days: 30,
+            buy: Default::default(),
+            sell: Default::default(),
+            maker: None,
+            tiers: vec![],
+        }
+    }
+}
+
+/// A percentage of the value of the trade, plus a fixed part
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(default)]
+pub struct Fee {
+    /// Share of the value of the trade, e.g., 0.001 for 0.1%
+    pub percentage: f32,
+    /// In DEFAULT_GOOD_KIND, for every trade
+    pub fixed: f32,
+}
+
+impl Fee {
+    fn of(&self, value: f32, discount: f32) -> f32 {
+        (value * self.percentage + self.fixed) * (1.0 - discount)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct FeeTier {
+    /// The volume, in DEFAULT_GOOD_KIND, a trader needs over the window
+    pub min_volume: f32,
+    /// Share of the fee taken off, e.g., 0.25 for a quarter
+    pub discount: f32,
+}
+
+/// Whether the trader buys or sells
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TradeSide {
+    Buy,
+    Sell,
+}
+
+/// The fees of a trader, see `SOLMarket::trader_fees`
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TraderFees {
+    /// The value the trader traded over the window, in DEFAULT_GOOD_KIND
+    pub volume: f32,
+    /// The discount of the tier of the trader, 0 if none
+    pub discount: f32,
+    /// Fees paid on buys and sells since the market was created
+    pub buy_fees: f32,
+    pub sell_fees: f32,
+}
+
+/// Fees collected by the market, see `MarketReport::fees`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct FeeTotals {
+    pub buy: f32,
+    pub sell: f32,
+}
+
+/// What each trader traded and paid
+#[derive(Debug, Clone, Default)]
+pub(crate) struct FeeLedger {
+    // Key is the name of the trader
+    pub traders: HashMap<String, TraderLedger>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TraderLedger {
+    /// Day and value of the trades, from the oldest
+    pub trades: VecDeque<(u32, f32)>,
+    pub buy_fees: f32,
+    pub sell_fees: f32,
+}
+
+impl FeeSchedule {
+    /// The fee of a trade worth `value` for a trader with the given discount
+    pub fn fee(&self, side: TradeSide, value: f32, discount: f32) -> f32 {
+        let fee = match side {
+            TradeSide::Buy => self.buy,
+            TradeSide::Sell => self.sell,
+        };
+        fee.of(value, discount)
+    }
+
+    /// The fee of an auction order worth `value`, filled at a clearing, for
+    /// a trader with the given discount
+    pub fn maker_fee(&self, side: TradeSide, value: f32, discount: f32) -> f32 {
+        match self.maker {
+            Some(fee) => fee.of(value, discount),
+            None => self.fee(side, value, discount),
+        }
+    }
+
+    /// The discount of a trader that traded `volume` over the window
+    pub fn discount(&self, volume: f32) -> f32 {
+        self.tiers
+            .iter()
+            .filter(|tier| volume >= tier.min_volume)
+            .max_by(|a, b| a.min_volume.total_cmp(&b.min_volume))
+            .map(|tier| tier.discount)
+            .unwrap_or(0.0)
+    }
+}
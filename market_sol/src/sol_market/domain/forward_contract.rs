@@ -0,0 +1,170 @@
+use serde::{Deserialize, Serialize};
+use std::{error::Error, fmt::Display};
+use unitn_market_2022::{good::good_kind::GoodKind, market::MarketGetterError};
+
+/// What the trader does at maturity
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ForwardSide {
+    /// The trader buys the good from the market
+    Buy,
+    /// The trader sells the good to the market
+    Sell,
+}
+
+/// How a forward contract is settled at maturity
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForwardSettlement {
+    /// The good and the price are exchanged. Both sides are deposited in
+    /// full when the contract is made.
+    Physical,
+    /// Only the difference between the agreed price and the price on the
+    /// maturity day is paid. Both the trader and the market deposit a
+    /// margin, which caps losses and gains.
+    Cash,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForwardStatus {
+    /// Waiting for the maturity day
+    Open,
+    /// Settled, the payout can be collected. Once collected, the contract
+    /// is forgotten.
+    Settled,
+}
+
+/// What a trader asks for when entering a forward contract
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForwardRequest {
+    pub trader_name: String,
+    pub side: ForwardSide,
+    pub good_kind: GoodKind,
+    pub quantity: f32,
+    /// The market day the contract is settled on
+    pub maturity_day: u32,
+    pub settlement: ForwardSettlement,
+    /// The price, in DEFAULT_GOOD_KIND, for the whole quantity
+    pub price: f32,
+}
+
+/// A forward contract between a trader and the market
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ForwardContract {
+    pub id: String,
+    pub trader_name: String,
+    pub side: ForwardSide,
+    pub good_kind: GoodKind,
+    pub quantity: f32,
+    /// The price, in DEFAULT_GOOD_KIND, for the whole quantity
+    pub price: f32,
+    pub settlement: ForwardSettlement,
+    pub created_on: u32,
+    pub maturity_day: u32,
+    /// What the trader deposited
+    pub collateral_kind: GoodKind,
+    pub collateral: f32,
+    /// What the market set aside from its stock to honour the contract
+    pub reserved_kind: GoodKind,
+    pub reserved: f32,
+    pub status: ForwardStatus,
+    /// What the trader gets once the contract is settled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payout: Option<ForwardPayout>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ForwardPayout {
+    pub kind: GoodKind,
+    pub quantity: f32,
+}
+
+/// What can go wrong when entering or collecting a forward contract
+#[derive(Debug, Clone, PartialEq)]
+pub enum ForwardError {
+    NonPositiveQuantity {
+        quantity: f32,
+    },
+    /// The maturity day has to be after the current day
+    MaturityNotInTheFuture {
+        maturity_day: u32,
+        current_day: u32,
+    },
+    /// The market can not price the good, e.g., it does not have enough
+    PriceUnavailable(MarketGetterError),
+    /// The market does not have enough of a good to set aside
+    InsufficientMarketQuantity {
+        kind: GoodKind,
+        required: f32,
+        available: f32,
+    },
+    /// The price is worse for the market than its forward price
+    UnacceptablePrice {
+        price: f32,
+        acceptable_price: f32,
+    },
+    WrongCollateralKind {
+        expected: GoodKind,
+        provided: GoodKind,
+    },
+    InsufficientCollateral {
+        required: f32,
+        provided: f32,
+    },
+    UnrecognizedContract {
+        id: String,
+    },
+    /// The contract has not been settled yet
+    NothingToCollect {
+        id: String,
+        status: ForwardStatus,
+    },
+}
+
+impl Display for ForwardError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ForwardError::NonPositiveQuantity { quantity } => {
+                write!(f, "The quantity should be positive, but is {quantity}")
+            }
+            ForwardError::MaturityNotInTheFuture {
+                maturity_day,
+                current_day,
+            } => write!(
+                f,
+                "The maturity day ({maturity_day}) should be after the current day ({current_day})"
+            ),
+            ForwardError::PriceUnavailable(e) => {
+                write!(f, "The market can not price the contract: {e:?}")
+            }
+            ForwardError::InsufficientMarketQuantity {
+                kind,
+                required,
+                available,
+            } => write!(
+                f,
+                "The market needs {required} {kind} to honour the contract, but has {available}"
+            ),
+            ForwardError::UnacceptablePrice {
+                price,
+                acceptable_price,
+            } => write!(
+                f,
+                "The price {price} is not acceptable, the market would agree on {acceptable_price}"
+            ),
+            ForwardError::WrongCollateralKind { expected, provided } => {
+                write!(f, "The collateral should be {expected}, but is {provided}")
+            }
+            ForwardError::InsufficientCollateral { required, provided } => write!(
+                f,
+                "The collateral should be at least {required}, but is {provided}"
+            ),
+            ForwardError::UnrecognizedContract { id } => {
+                write!(f, "There is no contract with id {id}")
+            }
+            ForwardError::NothingToCollect { id, status } => {
+                write!(f, "Contract {id} has nothing to collect, it is {status:?}")
+            }
+        }
+    }
+}
+
+impl Error for ForwardError {}
@@ -7,6 +7,14 @@ pub(crate) struct GoodLockMeta {
     pub price: f32,
     pub quantity: f32,
     pub created_on: u32, // Number of notify calls since creation
+    /// The value of the quantity at the rate of the market when it was
+    /// locked, in DEFAULT_GOOD_KIND
+    pub market_value: f32,
+    /// The fee included in `price`, in DEFAULT_GOOD_KIND
+    pub fee: f32,
+    /// Held out of the margin account of the trader until the lock is
+    /// redeemed or expires, in DEFAULT_GOOD_KIND
+    pub deposit: f32,
     // lock_type: LockType,
     /// The name of the trader who created the lock
     pub trader_name: String,
@@ -22,6 +30,8 @@ impl GoodLockMeta {
         price: f32,
         quantity: f32,
         created_on: u32,
+        market_value: f32,
+        fee: f32,
         trader_name: String,
     ) -> Self {
         Self {
@@ -29,7 +39,16 @@ impl GoodLockMeta {
             price,
             quantity,
             created_on,
+            market_value,
+            fee,
+            deposit: 0.0,
             trader_name,
         }
     }
+
+    /// The same lock, holding a deposit
+    pub fn with_deposit(mut self, deposit: f32) -> Self {
+        self.deposit = deposit;
+        self
+    }
 }
@@ -0,0 +1,156 @@
+use crate::sol_market::{SOLMarketError, ALL_GOOD_KINDS};
+use unitn_market_2022::good::good_kind::GoodKind;
+
+/// Volatility of the stocastic price of a good, unless specified otherwise
+pub(crate) const DEFAULT_VOLATILITY: f32 = 0.25;
+
+/// The parameters the market uses for one of its goods
+#[derive(Debug, Clone, PartialEq)]
+pub struct GoodSpec {
+    /// The name of the good, which the market knows it by
+    pub ticker: String,
+    /// The good of the market protocol this one is traded as, if any.
+    /// Goods without one are only simulated inside the market: traders can
+    /// not see or trade them through the `Market` trait.
+    pub kind: Option<GoodKind>,
+    /// The rate (good/EUR) the prices of the good start from
+    pub default_rate: f32,
+    /// Standard deviation of the random variation of the price of the good
+    pub volatility: f32,
+    /// The quantity the market started with
+    pub initial_stock: f32,
+    /// Interest the good earns each day, e.g., 0.001 for 0.1%.
+    /// Negative rates make the good decay.
+    pub daily_interest_rate: f32,
+}
+
+impl GoodSpec {
+    /// A good traded as `kind`, named after it, with its default exchange
+    /// rate and no initial stock
+    pub fn new(kind: GoodKind) -> Self {
+        GoodSpec {
+            kind: Some(kind),
+            default_rate: kind.get_default_exchange_rate(),
+            ..GoodSpec::instrument(&kind.to_string())
+        }
+    }
+
+    /// A good only simulated inside the market, worth as much as
+    /// DEFAULT_GOOD_KIND at first, with no initial stock
+    pub fn instrument(ticker: &str) -> Self {
+        GoodSpec {
+            ticker: ticker.to_string(),
+            kind: None,
+            default_rate: 1.0,
+            volatility: DEFAULT_VOLATILITY,
+            initial_stock: 0.0,
+            daily_interest_rate: 0.0,
+        }
+    }
+}
+
+/// Every good the market deals with, along with its parameters.
+///
+/// The internals of the market know goods by their ticker and look them up
+/// here, so that a new good only needs a new entry. `GoodKind` is only used
+/// at the boundary with the traders, through `ticker_of` and `kind_of`.
+///
+/// Goods are added with `register`: each one needs its own ticker, and at
+/// most one good can be traded as each `GoodKind`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct GoodsRegistry {
+    /// In the order they were registered
+    specs: Vec<GoodSpec>,
+}
+
+impl GoodsRegistry {
+    /// A registry without goods
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// The goods of the protocol, with their default exchange rates and no
+    /// initial stock
+    pub fn with_protocol_goods() -> Self {
+        let mut registry = GoodsRegistry::new();
+        for kind in ALL_GOOD_KINDS {
+            registry.register(GoodSpec::new(kind));
+        }
+        registry
+    }
+
+    /// Adds a good to the registry.
+    ///
+    /// Panics if the ticker or the kind of the good is already taken, see
+    /// `try_register`
+    pub fn register(&mut self, spec: GoodSpec) {
+        self.try_register(spec).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Adds a good to the registry, unless its ticker is already taken or
+    /// another good is already traded as its kind
+    pub fn try_register(&mut self, spec: GoodSpec) -> Result<(), SOLMarketError> {
+        if let Some(other) = self.specs.iter().find(|other| {
+            other.ticker == spec.ticker || (spec.kind.is_some() && other.kind == spec.kind)
+        }) {
+            return Err(SOLMarketError::DuplicateGood {
+                ticker: spec.ticker,
+                other: other.ticker.clone(),
+            });
+        }
+        self.specs.push(spec);
+        Ok(())
+    }
+
+    /// Returns the spec of the good with the given ticker, if any
+    pub fn get(&self, ticker: &str) -> Option<&GoodSpec> {
+        self.specs.iter().find(|spec| spec.ticker == ticker)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &GoodSpec> {
+        self.specs.iter()
+    }
+
+    /// The goods traders can see and trade, with the kind they are traded as
+    pub fn traded(&self) -> impl Iterator<Item = (GoodKind, &GoodSpec)> {
+        self.specs
+            .iter()
+            .filter_map(|spec| spec.kind.map(|kind| (kind, spec)))
+    }
+
+    /// Returns the ticker of the good traded as `kind`
+    pub fn ticker_of(&self, kind: GoodKind) -> &str {
+        self.traded()
+            .find(|(traded_as, _)| *traded_as == kind)
+            .map(|(_, spec)| spec.ticker.as_str())
+            .expect("Every good of the protocol should be in the registry")
+    }
+
+    /// Returns the kind the good is traded as, if it is traded
+    pub fn kind_of(&self, ticker: &str) -> Option<GoodKind> {
+        self.get(ticker).and_then(|spec| spec.kind)
+    }
+
+    /// Returns how much one unit of the given good becomes after `days` of
+    /// interest
+    pub fn carry_factor(&self, ticker: &str, days: u32) -> f32 {
+        let rate = self.spec(ticker).daily_interest_rate;
+        if rate == 0.0 {
+            1.0
+        } else {
+            (1.0 + rate).powi(days as i32)
+        }
+    }
+
+    /// Returns the value of the given quantity of a good, in DEFAULT_GOOD_KIND,
+    /// according to its default rate
+    pub(crate) fn value_in_default_good(&self, ticker: &str, quantity: f32) -> f32 {
+        quantity / self.spec(ticker).default_rate
+    }
+
+    /// Returns the spec of a good the market has
+    pub(crate) fn spec(&self, ticker: &str) -> &GoodSpec {
+        self.get(ticker)
+            .expect("Every good of the market should be in the registry")
+    }
+}
@@ -0,0 +1,115 @@
+use crate::sol_market::MARKET_NAME;
+use chrono::{DateTime, Local};
+use std::{
+    cell::RefCell,
+    fs::OpenOptions,
+    io::Write,
+    path::PathBuf,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+use unitn_market_2022::event::event::Event;
+
+/// Where a market writes its log codes
+#[derive(Debug, Clone)]
+pub enum LogSink {
+    /// Append them to this file
+    File(PathBuf),
+    /// Keep them in memory, e.g., to look at them in tests
+    Memory(Rc<RefCell<Vec<String>>>),
+    /// Drop them
+    Disabled,
+}
+
+/// An event of the `Market` protocol, along with the market it happened on.
+/// See `SOLMarket::add_event_listener`.
+#[derive(Debug, Clone)]
+pub struct InstanceEvent {
+    /// See `SOLMarket::instance_id`
+    pub instance_id: u64,
+    pub market_name: &'static str,
+    /// The day of the market when the event happened
+    pub day: u32,
+    pub event: Event,
+}
+
+/// Called with every event of a market, see `SOLMarket::add_event_listener`
+pub type EventListener = Box<dyn FnMut(&InstanceEvent)>;
+
+/// Writes the log codes of a market, in the format of the specifications:
+/// `NAME|yy:mm:dd:hh:mm:ss:mmm|CODE`
+#[derive(Debug, Clone)]
+pub(crate) struct Logger {
+    pub market_name: &'static str,
+    pub sink: LogSink,
+}
+
+impl Logger {
+    /// Logs to `log_file`, or to `log_{market_name}.txt` if `None`
+    pub fn new(market_name: &'static str, log_file: Option<&str>) -> Self {
+        let path = match log_file {
+            Some(log_file) => PathBuf::from(log_file),
+            None => PathBuf::from(format!("log_{market_name}.txt")),
+        };
+        Logger {
+            market_name,
+            sink: LogSink::File(path),
+        }
+    }
+
+    /// Append log code to the sink according to specifications, at the
+    /// given time
+    pub fn log(&self, time: DateTime<Local>, log_code: String) {
+        let time = time.format("%y:%m:%d:%H:%M:%S:%3f").to_string();
+        let line = format!("{}|{}|{}", self.market_name, time, log_code);
+        match &self.sink {
+            LogSink::File(path) => {
+                let written = OpenOptions::new()
+                    .append(true)
+                    .create(true)
+                    .open(path)
+                    .and_then(|mut file| writeln!(file, "{line}"));
+                if let Err(e) = written {
+                    eprintln!("Error while writing to file {}", e);
+                }
+            }
+            LogSink::Memory(lines) => lines.borrow_mut().push(line),
+            LogSink::Disabled => {}
+        }
+    }
+}
+
+impl Default for Logger {
+    fn default() -> Self {
+        Logger::new(MARKET_NAME, None)
+    }
+}
+
+static NEXT_INSTANCE_ID: AtomicU64 = AtomicU64::new(1);
+
+/// A new id for a market, unique in the process
+pub(crate) fn next_instance_id() -> u64 {
+    NEXT_INSTANCE_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+static NAMES: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+/// `get_name` returns a `&'static str`, so the names of the markets live
+/// as long as the process. Each distinct name is allocated once.
+pub(crate) fn intern_name(name: &str) -> &'static str {
+    if name == MARKET_NAME {
+        return MARKET_NAME;
+    }
+    let mut names = NAMES.lock().unwrap_or_else(|e| e.into_inner());
+    match names.iter().find(|interned| **interned == name) {
+        Some(interned) => interned,
+        None => {
+            let interned: &'static str = Box::leak(name.to_string().into_boxed_str());
+            names.push(interned);
+            interned
+        }
+    }
+}
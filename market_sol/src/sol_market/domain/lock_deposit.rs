@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// The deposits traders leave on their locks, see `SOLMarket::lock_deposits`.
+///
+/// A lock holds a share of its value out of the DEFAULT_GOOD_KIND collateral
+/// of the margin account of the trader. The deposit goes back to the account
+/// when the lock is redeemed, and to the market when the lock expires. Each
+/// expiry in the last `window_days` days multiplies the rate of the trader
+/// by `escalation`, up to `max_rate`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct LockDepositConfig {
+    /// If false, locks take no deposit
+    pub enabled: bool,
+    /// Share of the value of the lock, at the rate of the market, held for
+    /// a trader with no recent expiry
+    pub rate: f32,
+    /// What each recent expiry multiplies the rate by
+    pub escalation: f32,
+    /// The highest rate, however many locks the trader let expire
+    pub max_rate: f32,
+    /// The days expiries are counted over
+    pub window_days: u32,
+}
+
+impl Default for LockDepositConfig {
+    fn default() -> Self {
+        LockDepositConfig {
+            enabled: false,
+            rate: 0.01,
+            escalation: 2.0,
+            max_rate: 0.25,
+            window_days: 30,
+        }
+    }
+}
+
+impl LockDepositConfig {
+    /// The rate of a trader who let `expiries` locks expire in the window
+    pub fn rate_after(&self, expiries: u32) -> f32 {
+        let rate = self.rate * self.escalation.powi(expiries.min(i32::MAX as u32) as i32);
+        rate.min(self.max_rate)
+    }
+}
+
+/// The deposits of a trader
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LockDeposits {
+    /// The rate the next lock of the trader pays
+    pub rate: f32,
+    /// The locks the trader let expire in the window
+    pub expiries: u32,
+    /// Held on the live locks of the trader, in DEFAULT_GOOD_KIND
+    pub held: f32,
+    /// Kept by the market on expired locks since it was created
+    pub forfeited: f32,
+}
+
+/// The deposits of a trader, as kept in the `MarketState`
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct TraderDeposits {
+    /// The days the locks of the trader expired on, from the oldest
+    pub expiries: VecDeque<u32>,
+    pub held: f32,
+    pub forfeited: f32,
+}
@@ -0,0 +1,72 @@
+use super::good_lock_meta::GoodLockMeta;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Keeps the buy (or sell) locks of the market to their live ones: counts
+/// them per trader, and tells which expire on each day.
+///
+/// Expired locks leave `MarketMeta::locked_buys` (or `locked_sells`), so
+/// that going through the locks does not depend on how many the market ever
+/// made. Only their tokens are kept, so that redeeming one tells the trader
+/// the token expired.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LockIndex {
+    // Key is the name of the trader, value is the number of its live locks
+    counts: HashMap<String, u32>,
+    // Key is the day the locks are reinstated on
+    expiring: BTreeMap<u32, Vec<String>>,
+    /// The tokens of the locks that expired before being redeemed
+    expired: HashSet<String>,
+}
+
+impl LockIndex {
+    /// Adds a lock, which is reinstated on `expires_on`
+    pub fn insert(&mut self, token: &str, trader_name: &str, expires_on: u32) {
+        *self.counts.entry(trader_name.to_string()).or_default() += 1;
+        self.expiring
+            .entry(expires_on)
+            .or_default()
+            .push(token.to_string());
+    }
+
+    /// Removes a lock of the trader, redeemed or expired. Its token is
+    /// skipped when its day comes.
+    pub fn remove(&mut self, trader_name: &str) {
+        if let Some(count) = self.counts.get_mut(trader_name) {
+            *count -= 1;
+            if *count == 0 {
+                self.counts.remove(trader_name);
+            }
+        }
+    }
+
+    /// Takes out of `locks` those reinstated on `day` or before, and returns
+    /// them with their tokens
+    pub fn expire(
+        &mut self,
+        day: u32,
+        locks: &mut HashMap<String, GoodLockMeta>,
+    ) -> Vec<(String, GoodLockMeta)> {
+        let still_pending = self.expiring.split_off(&(day + 1));
+        let expiring = std::mem::replace(&mut self.expiring, still_pending);
+        let mut expired = Vec::new();
+        for token in expiring.into_values().flatten() {
+            // Redeemed locks are not there anymore
+            if let Some(lock) = locks.remove(&token) {
+                self.remove(&lock.trader_name);
+                self.expired.insert(token.clone());
+                expired.push((token, lock));
+            }
+        }
+        expired
+    }
+
+    /// The number of live locks of the trader
+    pub fn count(&self, trader_name: &str) -> u32 {
+        self.counts.get(trader_name).copied().unwrap_or(0)
+    }
+
+    /// Whether the token is of a lock that expired
+    pub fn is_expired(&self, token: &str) -> bool {
+        self.expired.contains(token)
+    }
+}
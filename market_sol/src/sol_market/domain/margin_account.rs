@@ -0,0 +1,185 @@
+use serde::{Deserialize, Serialize};
+use std::{error::Error, fmt::Display};
+use unitn_market_2022::good::good_kind::GoodKind;
+
+/// What a margin account holds of a good
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct MarginBalance {
+    pub kind: GoodKind,
+    /// Deposited by the trader, held by the market
+    pub collateral: f32,
+    /// Lent by the market, interest included
+    pub debt: f32,
+}
+
+/// The credit line of a trader: collateral deposited in the market, and
+/// what the trader borrowed against it
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MarginAccount {
+    pub trader_name: String,
+    pub opened_on: u32,
+    /// One balance per good, in the order of the goods registry
+    pub balances: Vec<MarginBalance>,
+}
+
+impl MarginAccount {
+    pub(crate) fn new<'a>(
+        trader_name: &str,
+        opened_on: u32,
+        kinds: impl Iterator<Item = &'a GoodKind>,
+    ) -> Self {
+        let balances = kinds
+            .map(|kind| MarginBalance {
+                kind: *kind,
+                collateral: 0.0,
+                debt: 0.0,
+            })
+            .collect();
+        MarginAccount {
+            trader_name: trader_name.to_string(),
+            opened_on,
+            balances,
+        }
+    }
+
+    /// Returns the balance of the given good
+    pub fn balance(&self, kind: GoodKind) -> MarginBalance {
+        self.balances
+            .iter()
+            .find(|balance| balance.kind == kind)
+            .copied()
+            .unwrap_or(MarginBalance {
+                kind,
+                collateral: 0.0,
+                debt: 0.0,
+            })
+    }
+
+    pub(crate) fn balance_mut(&mut self, kind: GoodKind) -> &mut MarginBalance {
+        let position = self.balances.iter().position(|b| b.kind == kind);
+        let index = match position {
+            Some(index) => index,
+            None => {
+                self.balances.push(MarginBalance {
+                    kind,
+                    collateral: 0.0,
+                    debt: 0.0,
+                });
+                self.balances.len() - 1
+            }
+        };
+        &mut self.balances[index]
+    }
+
+    pub fn has_debt(&self) -> bool {
+        self.balances.iter().any(|balance| balance.debt > 0.0)
+    }
+}
+
+/// The value of a margin account, in DEFAULT_GOOD_KIND, at today's rates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MarginSummary {
+    pub collateral_value: f32,
+    pub debt_value: f32,
+}
+
+impl MarginSummary {
+    /// What the trader would be left with after paying back every debt
+    pub fn equity(&self) -> f32 {
+        self.collateral_value - self.debt_value
+    }
+
+    /// The equity as a share of the debt. Infinite without debt.
+    pub fn margin_ratio(&self) -> f32 {
+        if self.debt_value > 0.0 {
+            self.equity() / self.debt_value
+        } else {
+            f32::INFINITY
+        }
+    }
+}
+
+/// What can go wrong when using a margin account
+#[derive(Debug, Clone, PartialEq)]
+pub enum MarginError {
+    AccountAlreadyOpen {
+        trader_name: String,
+    },
+    NoAccount {
+        trader_name: String,
+    },
+    NonPositiveQuantity {
+        quantity: f32,
+    },
+    /// The market does not have enough of the good to lend
+    InsufficientMarketQuantity {
+        kind: GoodKind,
+        required: f32,
+        available: f32,
+    },
+    /// The debt would be higher than `margin_max_leverage` times the equity
+    LeverageExceeded {
+        debt_value: f32,
+        max_debt_value: f32,
+    },
+    /// There is not that much collateral of the good in the account
+    InsufficientCollateral {
+        kind: GoodKind,
+        required: f32,
+        available: f32,
+    },
+    /// The account can not be closed until every debt is paid back
+    OutstandingDebt {
+        debt_value: f32,
+    },
+    NothingToRepay {
+        kind: GoodKind,
+    },
+}
+
+impl Display for MarginError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MarginError::AccountAlreadyOpen { trader_name } => {
+                write!(f, "{trader_name} already has a margin account")
+            }
+            MarginError::NoAccount { trader_name } => {
+                write!(f, "{trader_name} has no margin account")
+            }
+            MarginError::NonPositiveQuantity { quantity } => {
+                write!(f, "The quantity should be positive, but is {quantity}")
+            }
+            MarginError::InsufficientMarketQuantity {
+                kind,
+                required,
+                available,
+            } => write!(
+                f,
+                "The market can not lend {required} {kind}, it only has {available}"
+            ),
+            MarginError::LeverageExceeded {
+                debt_value,
+                max_debt_value,
+            } => write!(
+                f,
+                "The debt would be worth {debt_value}, but the account allows at most {max_debt_value}"
+            ),
+            MarginError::InsufficientCollateral {
+                kind,
+                required,
+                available,
+            } => write!(
+                f,
+                "The account holds {available} {kind} of collateral, not {required}"
+            ),
+            MarginError::OutstandingDebt { debt_value } => {
+                write!(f, "The account still owes {debt_value}")
+            }
+            MarginError::NothingToRepay { kind } => {
+                write!(f, "The account owes no {kind}")
+            }
+        }
+    }
+}
+
+impl Error for MarginError {}
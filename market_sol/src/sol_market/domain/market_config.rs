@@ -0,0 +1,111 @@
+use crate::sol_market::{
+    AuctionConfig, BackgroundFlowConfig, EventLogConfig, FeeSchedule, LockDepositConfig,
+    SurveillanceConfig, WeightTuningConfig, FORWARD_MARGIN_RATIO, LOCK_LIMIT,
+    MARGIN_DAILY_INTEREST_RATE, MARGIN_MAINTENANCE_RATIO, MARGIN_MAX_LEVERAGE, MARKET_MARGIN,
+    MARKET_NAME, MIN_LOCK_VALUE, QUOTA_WINDOW_DAYS, TOKEN_DURATION,
+};
+use serde::{Deserialize, Serialize};
+
+/// The tunable parameters of a SOL market.
+///
+/// Every field has a default, so a definition file only needs to list the
+/// ones it wants to change.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct MarketConfig {
+    /// The name the market gives traders, and writes in its log. Markets
+    /// that run side by side should have different names.
+    pub name: String,
+    /// The file the market appends its log to. `None` uses
+    /// `log_<name>.txt`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_file: Option<String>,
+    /// How many days a lock stays valid
+    pub token_duration: u32,
+    /// How many unexpired buy (and sell) locks a trader can have
+    pub lock_limit: u32,
+    /// How many locks a trader can ask for in `quota_window_days` days,
+    /// rejected ones included. `None` does not limit them.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_locks_per_window: Option<u32>,
+    /// The number of days `max_locks_per_window` counts lock requests over
+    pub quota_window_days: u32,
+    /// How much value, in DEFAULT_GOOD_KIND, a trader can have in unexpired
+    /// locks. `None` does not limit it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_locked_value: Option<f32>,
+    /// Locks of goods worth less than this, in DEFAULT_GOOD_KIND at their
+    /// default rate, are rejected
+    pub min_lock_value: f32,
+    /// The margin this market applies on the rate it pays when buying from traders
+    pub market_margin: f32,
+    /// The share of the price that the trader and the market deposit for a
+    /// cash-settled forward contract
+    pub forward_margin_ratio: f32,
+    /// How many times its equity a margin account can borrow. 0 disables
+    /// borrowing.
+    pub margin_max_leverage: f32,
+    /// A margin account is liquidated when its equity falls below this
+    /// share of its debt
+    pub margin_maintenance_ratio: f32,
+    /// Interest added to the debts of margin accounts each day
+    pub margin_daily_interest_rate: f32,
+    /// Save a snapshot of the state file every this many days.
+    /// `None` saves only when the market is dropped.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub autosave_every_days: Option<u32>,
+    /// How many old snapshots to keep next to the state file.
+    /// `None` keeps all of them.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snapshots_to_keep: Option<usize>,
+    /// If set, the market runs in real time: a market day lasts this many
+    /// milliseconds, however many events happen in it. `None` advances a
+    /// day on every event. See `SOLMarket::set_clock`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub day_length_ms: Option<u64>,
+    // The tables are last, since TOML writes them after plain values
+    /// How suspicious traders are detected and penalized
+    pub surveillance: SurveillanceConfig,
+    /// How the weights of the pricing strategies tune themselves
+    pub weight_tuning: WeightTuningConfig,
+    /// The synthetic traders that trade with the market every day
+    pub background_flow: BackgroundFlowConfig,
+    /// Whether locks are filled at once or by a periodic call auction
+    pub auction: AuctionConfig,
+    /// The fees charged on each trade
+    pub fees: FeeSchedule,
+    /// The deposits held on locks, and kept when they expire
+    pub lock_deposits: LockDepositConfig,
+    /// The snapshots of the event log of the market
+    pub event_log: EventLogConfig,
+}
+
+impl Default for MarketConfig {
+    fn default() -> Self {
+        MarketConfig {
+            name: MARKET_NAME.to_string(),
+            log_file: None,
+            token_duration: TOKEN_DURATION,
+            lock_limit: LOCK_LIMIT,
+            max_locks_per_window: None,
+            quota_window_days: QUOTA_WINDOW_DAYS,
+            max_locked_value: None,
+            min_lock_value: MIN_LOCK_VALUE,
+            market_margin: MARKET_MARGIN,
+            forward_margin_ratio: FORWARD_MARGIN_RATIO,
+            margin_max_leverage: MARGIN_MAX_LEVERAGE,
+            margin_maintenance_ratio: MARGIN_MAINTENANCE_RATIO,
+            margin_daily_interest_rate: MARGIN_DAILY_INTEREST_RATE,
+            autosave_every_days: None,
+            snapshots_to_keep: None,
+            day_length_ms: None,
+            surveillance: Default::default(),
+            weight_tuning: Default::default(),
+            background_flow: Default::default(),
+            auction: Default::default(),
+            fees: Default::default(),
+            lock_deposits: Default::default(),
+            event_log: Default::default(),
+        }
+    }
+}
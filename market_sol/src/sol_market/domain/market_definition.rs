@@ -0,0 +1,172 @@
+use super::{
+    forward_contract::ForwardContract,
+    margin_account::MarginAccount,
+    market_config::MarketConfig,
+    option_contract::OptionContract,
+    strategy_name::{StrategyName, ALL_STRATEGY_NAMES},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use unitn_market_2022::good::good_kind::GoodKind;
+
+/// The version of [`MarketDefinition`] this crate reads and writes
+pub const MARKET_DEFINITION_SCHEMA_VERSION: u32 = 1;
+
+/// Everything needed to create a SOL market, as stored in a TOML or JSON
+/// definition file. It can be converted to and from the legacy `.sol` format,
+/// which only keeps goods and weights.
+///
+/// Fields are added over time; new ones are optional, so older files are
+/// still valid.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MarketDefinition {
+    pub schema_version: u32,
+    /// Seed for the random number generators of the market. If missing, the
+    /// market gets a different random behaviour every time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    /// The day the market was at when it was saved. If missing, the market
+    /// starts from day 1.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_day: Option<u32>,
+    pub goods: Vec<GoodDefinition>,
+    #[serde(default)]
+    pub weights: WeightsDefinition,
+    #[serde(default)]
+    pub config: MarketConfig,
+    /// The forward contracts that were not collected yet. The goods they
+    /// set aside are not part of `goods`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub forward_contracts: Vec<ForwardContract>,
+    /// The options that were not collected yet. As for forward contracts,
+    /// what they set aside is not part of `goods`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub option_contracts: Vec<OptionContract>,
+    /// The margin accounts of the traders. Their collateral is not part of
+    /// `goods`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub margin_accounts: Vec<MarginAccount>,
+}
+
+/// A good of the market. Goods of the protocol are declared by their `kind`
+/// and named after it, unless they have a `ticker`. Other goods only have a
+/// `ticker`: they are only simulated inside the market.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GoodDefinition {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<GoodKind>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ticker: Option<String>,
+    pub quantity: f32,
+    /// The rate (EUR/kind) the market was offering when it was saved.
+    /// Only informative, it is ignored when loading.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exchange_rate: Option<f32>,
+    /// Overrides the rate the prices of the good start from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_rate: Option<f32>,
+    /// Overrides how much the price of the good varies randomly
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volatility: Option<f32>,
+    /// Interest the good earns each day. If missing, the good earns nothing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily_interest_rate: Option<f32>,
+}
+
+/// The weight of each pricing strategy. Missing ones are drawn at random.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct WeightsDefinition {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stocastic: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quantity: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub others: Option<f32>,
+}
+
+impl WeightsDefinition {
+    pub(crate) fn get(&self, strategy: &StrategyName) -> Option<f32> {
+        match strategy {
+            StrategyName::Stocastic => self.stocastic,
+            StrategyName::Quantity => self.quantity,
+            StrategyName::Others => self.others,
+        }
+    }
+
+    pub(crate) fn set(&mut self, strategy: &StrategyName, weight: f32) {
+        let field = match strategy {
+            StrategyName::Stocastic => &mut self.stocastic,
+            StrategyName::Quantity => &mut self.quantity,
+            StrategyName::Others => &mut self.others,
+        };
+        *field = Some(weight);
+    }
+
+    pub(crate) fn to_map(&self) -> HashMap<StrategyName, f32> {
+        let mut map = HashMap::with_capacity(ALL_STRATEGY_NAMES.len());
+        for strategy in ALL_STRATEGY_NAMES {
+            if let Some(weight) = self.get(&strategy) {
+                map.insert(strategy, weight);
+            }
+        }
+        map
+    }
+
+    pub(crate) fn from_map(map: &HashMap<StrategyName, f32>) -> Self {
+        let mut weights = WeightsDefinition::default();
+        for (strategy, weight) in map {
+            weights.set(strategy, *weight);
+        }
+        weights
+    }
+}
+
+impl Default for MarketDefinition {
+    /// An empty definition in the current schema. It declares no goods, so
+    /// they have to be added before creating a market from it.
+    fn default() -> Self {
+        MarketDefinition {
+            schema_version: MARKET_DEFINITION_SCHEMA_VERSION,
+            seed: None,
+            current_day: None,
+            goods: vec![],
+            weights: Default::default(),
+            config: Default::default(),
+            forward_contracts: vec![],
+            option_contracts: vec![],
+            margin_accounts: vec![],
+        }
+    }
+}
+
+impl GoodDefinition {
+    /// A good of the protocol with the given quantity and default parameters
+    pub fn new(kind: GoodKind, quantity: f32) -> Self {
+        GoodDefinition {
+            kind: Some(kind),
+            ticker: None,
+            quantity,
+            exchange_rate: None,
+            default_rate: None,
+            volatility: None,
+            daily_interest_rate: None,
+        }
+    }
+
+    /// A good only simulated inside the market, with the given quantity and
+    /// default parameters
+    pub fn instrument(ticker: &str, quantity: f32) -> Self {
+        GoodDefinition {
+            kind: None,
+            ticker: Some(ticker.to_string()),
+            ..GoodDefinition::new(GoodKind::EUR, quantity)
+        }
+    }
+
+    /// The name of the good: its ticker, or else its kind
+    pub fn ticker(&self) -> Option<String> {
+        self.ticker
+            .clone()
+            .or_else(|| self.kind.map(|kind| kind.to_string()))
+    }
+}
@@ -0,0 +1,573 @@
+use super::{
+    auction::{AuctionOrder, AuctionSide, AuctionStatus},
+    fees::TradeSide,
+    forward_contract::{ForwardContract, ForwardPayout, ForwardStatus},
+    lock_deposit::TraderDeposits,
+    margin_account::MarginAccount,
+    option_contract::{OptionContract, OptionStatus},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    fmt::Display,
+};
+use unitn_market_2022::good::{consts::DEFAULT_GOOD_KIND, good_kind::GoodKind};
+
+/// A change to the state of the market: its stock, its locks, its day, its
+/// contracts, its margin accounts, its auction orders and the deposits on
+/// its locks.
+///
+/// Every such change goes through an event, see `SOLMarket::events`: the
+/// state of the market is the fold of its events, see `MarketState`. Goods
+/// are named by their ticker, see `GoodsRegistry`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum MarketEvent {
+    /// A lock took some stock out of the market: `quantity` of `good` for a
+    /// buy lock, `price` of DEFAULT_GOOD_KIND for a sell lock
+    GoodReserved {
+        token: String,
+        side: TradeSide,
+        trader_name: String,
+        good: String,
+        quantity: f32,
+        price: f32,
+    },
+    /// A buy lock was redeemed: the trader paid `cash` of DEFAULT_GOOD_KIND
+    /// for the reserved good
+    CashReceived { token: String, cash: f32 },
+    /// A sell lock was redeemed: the trader gave `quantity` of `good` for
+    /// the reserved cash
+    GoodReceived {
+        token: String,
+        good: String,
+        quantity: f32,
+    },
+    /// A lock expired, and its reserved stock (`quantity` of `good`) went
+    /// back to the market
+    LockExpired {
+        token: String,
+        good: String,
+        quantity: f32,
+    },
+    /// The market exchanged `value` of one of its goods for another one, see
+    /// `SOLMarket::internal_trade_if_needed`
+    InternalTradeDone {
+        sold: String,
+        sold_quantity: f32,
+        bought: String,
+        bought_quantity: f32,
+        value: f32,
+    },
+    /// Any other change to the stock, e.g., carry
+    StockAdjusted {
+        good: String,
+        quantity: f32,
+        reason: AdjustmentReason,
+    },
+    /// The market moved on to `day`
+    DayAdvanced { day: u32 },
+    /// A trader entered a forward contract: the market set aside its side
+    ForwardEntered { contract: ForwardContract },
+    /// A forward contract reached maturity: the market keeps the collateral
+    /// and what it set aside, less the payout of the trader
+    ForwardSettled { id: String, payout: ForwardPayout },
+    /// The trader collected the payout of a settled forward contract
+    ForwardCollected { id: String },
+    /// The market wrote an option: it got the premium, and set aside its side
+    OptionWritten { contract: OptionContract },
+    /// An option was in the money on its expiry day: the market keeps the
+    /// deposit of the trader
+    OptionExercised { id: String },
+    /// An option was out of the money on its expiry day: what the market set
+    /// aside goes back to the stock
+    OptionExpired { id: String },
+    /// The trader collected the payout of an option
+    OptionCollected { id: String },
+    /// A trader opened a margin account
+    MarginAccountOpened { account: MarginAccount },
+    /// A trader added some collateral to its margin account
+    CollateralDeposited {
+        trader_name: String,
+        kind: GoodKind,
+        quantity: f32,
+    },
+    /// A trader took some collateral back from its margin account
+    CollateralWithdrawn {
+        trader_name: String,
+        kind: GoodKind,
+        quantity: f32,
+    },
+    /// The market lent some of its stock to a trader
+    MarginBorrowed {
+        trader_name: String,
+        kind: GoodKind,
+        quantity: f32,
+    },
+    /// A trader paid back some of its debt
+    MarginRepaid {
+        trader_name: String,
+        kind: GoodKind,
+        quantity: f32,
+    },
+    /// A day of interest, at `rate`, was added to the debts of an account
+    MarginInterestCharged { trader_name: String, rate: f32 },
+    /// The market took the collateral of an account into its stock and
+    /// cancelled its debts. `left` of DEFAULT_GOOD_KIND, what was left of
+    /// the equity, went back to the account.
+    MarginAccountLiquidated { trader_name: String, left: f32 },
+    /// A trader closed its margin account and took its collateral back
+    MarginAccountClosed { trader_name: String },
+    /// A lock (or an auction order) took its deposit out of the
+    /// DEFAULT_GOOD_KIND collateral of the trader
+    DepositHeld { trader_name: String, deposit: f32 },
+    /// A redeemed lock (or an unfilled order) gave its deposit back to the
+    /// margin account of the trader, opened again if it was closed
+    DepositRefunded { trader_name: String, deposit: f32 },
+    /// A lock of the trader expired, and left its deposit to the market.
+    /// The expiries on `forget_until` or before are forgotten.
+    DepositForfeited {
+        trader_name: String,
+        deposit: f32,
+        forget_until: u32,
+    },
+    /// A trader placed an order for the next clearing of the auction
+    AuctionOrderPlaced { order: AuctionOrder },
+    /// The auction cleared an order, see `AuctionStatus`
+    AuctionOrderCleared {
+        token: String,
+        status: AuctionStatus,
+    },
+    /// The lock of a cleared order can not be redeemed anymore, and the
+    /// order is forgotten
+    AuctionOrderForgotten { token: String },
+}
+
+/// Why the stock of a good changed outside of the other events
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdjustmentReason {
+    /// Daily interest on the stock, or on a settled lock
+    Carry,
+    BackgroundFlow,
+}
+
+/// Why the state of the market can not take an event
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventError {
+    /// The event would take the stock of `good` below zero
+    InsufficientStock {
+        good: String,
+        stock: f32,
+        change: f32,
+    },
+    /// The event is about a forward or option contract the market does not
+    /// have
+    UnknownContract { id: String },
+    /// The event is about a margin account the market does not have
+    UnknownAccount { trader_name: String },
+    /// The event is about an auction order the market does not have
+    UnknownOrder { token: String },
+}
+
+impl Display for EventError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EventError::InsufficientStock {
+                good,
+                stock,
+                change,
+            } => write!(
+                f,
+                "The market has {stock} {good}, it can not give {}",
+                -change
+            ),
+            EventError::UnknownContract { id } => write!(f, "There is no contract {id}"),
+            EventError::UnknownAccount { trader_name } => {
+                write!(f, "{trader_name} has no margin account")
+            }
+            EventError::UnknownOrder { token } => write!(f, "There is no auction order {token}"),
+        }
+    }
+}
+
+impl Error for EventError {}
+
+/// A live lock, as seen by the events
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Reservation {
+    pub side: TradeSide,
+    pub trader_name: String,
+    pub good: String,
+    pub quantity: f32,
+    pub price: f32,
+    /// The day the lock was made
+    pub day: u32,
+}
+
+/// The state of the market, as the fold of its events
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct MarketState {
+    pub day: u32,
+    /// The goods of the protocol with their ticker, in the order of the
+    /// goods registry
+    pub tickers: Vec<(GoodKind, String)>,
+    /// Key is the ticker of the good
+    pub stock: HashMap<String, f32>,
+    /// Key is the token of the lock
+    pub locks: BTreeMap<String, Reservation>,
+    /// Key is the id of the contract. Collected contracts are not there.
+    pub forward_contracts: HashMap<String, ForwardContract>,
+    /// Key is the id of the option. Collected options are not there.
+    pub option_contracts: HashMap<String, OptionContract>,
+    /// Key is the name of the trader
+    pub margin_accounts: HashMap<String, MarginAccount>,
+    /// Key is the token of the order. Cleared orders are kept for
+    /// `token_duration` days, so that traders can look at them.
+    pub auction_orders: HashMap<String, AuctionOrder>,
+    /// Key is the name of the trader
+    pub deposits: HashMap<String, TraderDeposits>,
+}
+
+impl MarketState {
+    /// Moves on to the state after the event. An event that would take the
+    /// stock of a good below zero, or that is about a contract, an account
+    /// or an order the market does not have, is refused, and the state does
+    /// not change.
+    pub fn apply(&mut self, event: &MarketEvent) -> Result<(), EventError> {
+        self.check_subject(event)?;
+        let mut stock_after: Vec<(String, f32)> = vec![];
+        for (good, change) in self.stock_changes(event) {
+            match stock_after.iter_mut().find(|(g, _)| *g == good) {
+                Some((_, stock)) => *stock += change,
+                None => {
+                    let stock = self.stock.get(&good).copied().unwrap_or(0.0);
+                    stock_after.push((good, stock + change));
+                }
+            }
+        }
+        if let Some((good, after)) = stock_after.iter().find(|(_, after)| *after < 0.0) {
+            let stock = self.stock.get(good).copied().unwrap_or(0.0);
+            return Err(EventError::InsufficientStock {
+                good: good.clone(),
+                stock,
+                change: after - stock,
+            });
+        }
+        for (good, after) in stock_after {
+            self.stock.insert(good, after);
+        }
+        self.fold(event);
+        Ok(())
+    }
+
+    /// Returns the ticker of the good of the protocol
+    pub fn ticker(&self, kind: GoodKind) -> &str {
+        self.tickers
+            .iter()
+            .find(|(k, _)| *k == kind)
+            .map(|(_, ticker)| ticker.as_str())
+            .expect("Every good of the protocol has a ticker")
+    }
+
+    /// The orders of a trader waiting for the next clearing
+    pub fn pending_orders_of<'a>(
+        &'a self,
+        trader_name: &'a str,
+    ) -> impl Iterator<Item = &'a AuctionOrder> {
+        self.auction_orders.values().filter(move |order| {
+            order.status == AuctionStatus::Pending && order.trader_name == trader_name
+        })
+    }
+
+    /// The number of orders of a trader on the given side waiting for the
+    /// next clearing
+    pub fn num_of_pending_orders(&self, trader_name: &str, side: AuctionSide) -> u32 {
+        self.pending_orders_of(trader_name)
+            .filter(|order| order.side == side)
+            .count() as u32
+    }
+
+    /// Checks that the contract, the account or the order the event is
+    /// about exists
+    fn check_subject(&self, event: &MarketEvent) -> Result<(), EventError> {
+        let exists = match event {
+            MarketEvent::ForwardSettled { id, .. } | MarketEvent::ForwardCollected { id } => {
+                self.forward_contracts.contains_key(id)
+            }
+            MarketEvent::OptionExercised { id }
+            | MarketEvent::OptionExpired { id }
+            | MarketEvent::OptionCollected { id } => self.option_contracts.contains_key(id),
+            MarketEvent::CollateralDeposited { trader_name, .. }
+            | MarketEvent::CollateralWithdrawn { trader_name, .. }
+            | MarketEvent::MarginBorrowed { trader_name, .. }
+            | MarketEvent::MarginRepaid { trader_name, .. }
+            | MarketEvent::MarginInterestCharged { trader_name, .. }
+            | MarketEvent::MarginAccountLiquidated { trader_name, .. }
+            | MarketEvent::MarginAccountClosed { trader_name } => {
+                self.margin_accounts.contains_key(trader_name)
+            }
+            MarketEvent::AuctionOrderCleared { token, .. }
+            | MarketEvent::AuctionOrderForgotten { token } => {
+                self.auction_orders.contains_key(token)
+            }
+            _ => true,
+        };
+        if exists {
+            return Ok(());
+        }
+        Err(match event {
+            MarketEvent::ForwardSettled { id, .. }
+            | MarketEvent::ForwardCollected { id }
+            | MarketEvent::OptionExercised { id }
+            | MarketEvent::OptionExpired { id }
+            | MarketEvent::OptionCollected { id } => EventError::UnknownContract { id: id.clone() },
+            MarketEvent::AuctionOrderCleared { token, .. }
+            | MarketEvent::AuctionOrderForgotten { token } => EventError::UnknownOrder {
+                token: token.clone(),
+            },
+            MarketEvent::CollateralDeposited { trader_name, .. }
+            | MarketEvent::CollateralWithdrawn { trader_name, .. }
+            | MarketEvent::MarginBorrowed { trader_name, .. }
+            | MarketEvent::MarginRepaid { trader_name, .. }
+            | MarketEvent::MarginInterestCharged { trader_name, .. }
+            | MarketEvent::MarginAccountLiquidated { trader_name, .. }
+            | MarketEvent::MarginAccountClosed { trader_name } => EventError::UnknownAccount {
+                trader_name: trader_name.clone(),
+            },
+            _ => unreachable!("Only these events have a subject"),
+        })
+    }
+
+    /// What the event adds to (or, if negative, removes from) the stock of
+    /// each good. The subject of the event exists, see `check_subject`.
+    fn stock_changes(&self, event: &MarketEvent) -> Vec<(String, f32)> {
+        let ticker = |kind: GoodKind| self.ticker(kind).to_string();
+        match event {
+            MarketEvent::GoodReserved {
+                side: TradeSide::Buy,
+                good,
+                quantity,
+                ..
+            } => vec![(good.clone(), -quantity)],
+            MarketEvent::GoodReserved {
+                side: TradeSide::Sell,
+                price,
+                ..
+            } => vec![(ticker(DEFAULT_GOOD_KIND), -price)],
+            MarketEvent::CashReceived { cash, .. } => vec![(ticker(DEFAULT_GOOD_KIND), *cash)],
+            MarketEvent::GoodReceived { good, quantity, .. }
+            | MarketEvent::LockExpired { good, quantity, .. }
+            | MarketEvent::StockAdjusted { good, quantity, .. } => vec![(good.clone(), *quantity)],
+            MarketEvent::InternalTradeDone {
+                sold,
+                sold_quantity,
+                bought,
+                bought_quantity,
+                ..
+            } => vec![
+                (sold.clone(), -sold_quantity),
+                (bought.clone(), *bought_quantity),
+            ],
+            MarketEvent::ForwardEntered { contract } => {
+                vec![(ticker(contract.reserved_kind), -contract.reserved)]
+            }
+            MarketEvent::ForwardSettled { id, payout } => {
+                let contract = &self.forward_contracts[id];
+                vec![
+                    (ticker(contract.collateral_kind), contract.collateral),
+                    (ticker(contract.reserved_kind), contract.reserved),
+                    (ticker(payout.kind), -payout.quantity),
+                ]
+            }
+            MarketEvent::OptionWritten { contract } => vec![
+                (ticker(DEFAULT_GOOD_KIND), contract.premium),
+                (ticker(contract.reserved_kind), -contract.reserved),
+            ],
+            MarketEvent::OptionExercised { id } => {
+                let contract = &self.option_contracts[id];
+                vec![(ticker(contract.deposit_kind), contract.deposit)]
+            }
+            MarketEvent::OptionExpired { id } => {
+                let contract = &self.option_contracts[id];
+                vec![(ticker(contract.reserved_kind), contract.reserved)]
+            }
+            MarketEvent::MarginBorrowed { kind, quantity, .. } => {
+                vec![(ticker(*kind), -quantity)]
+            }
+            MarketEvent::MarginRepaid { kind, quantity, .. } => vec![(ticker(*kind), *quantity)],
+            MarketEvent::MarginAccountLiquidated { trader_name, left } => {
+                let account = &self.margin_accounts[trader_name];
+                account
+                    .balances
+                    .iter()
+                    .filter(|balance| balance.collateral > 0.0)
+                    .map(|balance| (ticker(balance.kind), balance.collateral))
+                    .chain([(ticker(DEFAULT_GOOD_KIND), -left)])
+                    .collect()
+            }
+            MarketEvent::DepositForfeited { deposit, .. } if *deposit > 0.0 => {
+                vec![(ticker(DEFAULT_GOOD_KIND), *deposit)]
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Changes everything but the stock as the event says. The subject of
+    /// the event exists, see `check_subject`.
+    fn fold(&mut self, event: &MarketEvent) {
+        match event {
+            MarketEvent::GoodReserved {
+                token,
+                side,
+                trader_name,
+                good,
+                quantity,
+                price,
+            } => {
+                let reservation = Reservation {
+                    side: *side,
+                    trader_name: trader_name.clone(),
+                    good: good.clone(),
+                    quantity: *quantity,
+                    price: *price,
+                    day: self.day,
+                };
+                self.locks.insert(token.clone(), reservation);
+            }
+            MarketEvent::CashReceived { token, .. }
+            | MarketEvent::GoodReceived { token, .. }
+            | MarketEvent::LockExpired { token, .. } => {
+                self.locks.remove(token);
+            }
+            MarketEvent::DayAdvanced { day } => self.day = *day,
+            MarketEvent::InternalTradeDone { .. } | MarketEvent::StockAdjusted { .. } => {}
+            MarketEvent::ForwardEntered { contract } => {
+                self.forward_contracts
+                    .insert(contract.id.clone(), contract.clone());
+            }
+            MarketEvent::ForwardSettled { id, payout } => {
+                let contract = self.forward_contracts.get_mut(id).unwrap();
+                contract.status = ForwardStatus::Settled;
+                contract.payout = Some(*payout);
+            }
+            MarketEvent::ForwardCollected { id } => {
+                self.forward_contracts.remove(id);
+            }
+            MarketEvent::OptionWritten { contract } => {
+                self.option_contracts
+                    .insert(contract.id.clone(), contract.clone());
+            }
+            MarketEvent::OptionExercised { id } => {
+                self.option_contracts.get_mut(id).unwrap().status = OptionStatus::Exercised;
+            }
+            MarketEvent::OptionExpired { id } => {
+                self.option_contracts.get_mut(id).unwrap().status = OptionStatus::Expired;
+            }
+            MarketEvent::OptionCollected { id } => {
+                self.option_contracts.remove(id);
+            }
+            MarketEvent::MarginAccountOpened { account } => {
+                self.margin_accounts
+                    .insert(account.trader_name.clone(), account.clone());
+            }
+            MarketEvent::CollateralDeposited {
+                trader_name,
+                kind,
+                quantity,
+            } => {
+                let account = self.margin_accounts.get_mut(trader_name).unwrap();
+                account.balance_mut(*kind).collateral += quantity;
+            }
+            MarketEvent::CollateralWithdrawn {
+                trader_name,
+                kind,
+                quantity,
+            } => {
+                let account = self.margin_accounts.get_mut(trader_name).unwrap();
+                account.balance_mut(*kind).collateral -= quantity;
+            }
+            MarketEvent::MarginBorrowed {
+                trader_name,
+                kind,
+                quantity,
+            } => {
+                let account = self.margin_accounts.get_mut(trader_name).unwrap();
+                account.balance_mut(*kind).debt += quantity;
+            }
+            MarketEvent::MarginRepaid {
+                trader_name,
+                kind,
+                quantity,
+            } => {
+                let account = self.margin_accounts.get_mut(trader_name).unwrap();
+                let balance = account.balance_mut(*kind);
+                balance.debt = (balance.debt - quantity).max(0.0);
+            }
+            MarketEvent::MarginInterestCharged { trader_name, rate } => {
+                let account = self.margin_accounts.get_mut(trader_name).unwrap();
+                for balance in account.balances.iter_mut() {
+                    balance.debt *= 1.0 + rate;
+                }
+            }
+            MarketEvent::MarginAccountLiquidated { trader_name, left } => {
+                let account = self.margin_accounts.get_mut(trader_name).unwrap();
+                for balance in account.balances.iter_mut() {
+                    balance.collateral = 0.0;
+                    balance.debt = 0.0;
+                }
+                account.balance_mut(DEFAULT_GOOD_KIND).collateral = *left;
+            }
+            MarketEvent::MarginAccountClosed { trader_name } => {
+                self.margin_accounts.remove(trader_name);
+            }
+            MarketEvent::DepositHeld {
+                trader_name,
+                deposit,
+            } => {
+                if let Some(account) = self.margin_accounts.get_mut(trader_name) {
+                    account.balance_mut(DEFAULT_GOOD_KIND).collateral -= deposit;
+                }
+                self.deposits.entry(trader_name.clone()).or_default().held += deposit;
+            }
+            MarketEvent::DepositRefunded {
+                trader_name,
+                deposit,
+            } => {
+                let (day, kinds) = (self.day, self.tickers.iter().map(|(kind, _)| kind));
+                let account = self
+                    .margin_accounts
+                    .entry(trader_name.clone())
+                    .or_insert_with(|| MarginAccount::new(trader_name, day, kinds));
+                account.balance_mut(DEFAULT_GOOD_KIND).collateral += deposit;
+                if let Some(deposits) = self.deposits.get_mut(trader_name) {
+                    deposits.held -= deposit;
+                }
+            }
+            MarketEvent::DepositForfeited {
+                trader_name,
+                deposit,
+                forget_until,
+            } => {
+                let deposits = self.deposits.entry(trader_name.clone()).or_default();
+                deposits.expiries.push_back(self.day);
+                while matches!(deposits.expiries.front(), Some(day) if day <= forget_until) {
+                    deposits.expiries.pop_front();
+                }
+                if *deposit > 0.0 {
+                    deposits.held -= deposit;
+                    deposits.forfeited += deposit;
+                }
+            }
+            MarketEvent::AuctionOrderPlaced { order } => {
+                self.auction_orders
+                    .insert(order.token.clone(), order.clone());
+            }
+            MarketEvent::AuctionOrderCleared { token, status } => {
+                self.auction_orders.get_mut(token).unwrap().status = *status;
+            }
+            MarketEvent::AuctionOrderForgotten { token } => {
+                self.auction_orders.remove(token);
+            }
+        }
+    }
+}
@@ -1,35 +1,91 @@
 use super::{
+    auction::AuctionSide,
+    background_flow::BackgroundFlow,
+    clock::{Clock, EventClock, RealTimeClock, SimulatedClock},
+    event_log::EventLog,
+    fees::FeeLedger,
     good_lock_meta::GoodLockMeta,
+    goods_registry::GoodsRegistry,
+    instance::{intern_name, next_instance_id, LogSink, Logger},
+    lock_index::LockIndex,
+    market_config::MarketConfig,
+    quota::QuotaError,
+    rate_cache::RateCache,
+    report::MarketStats,
     strategy_name::{StrategyName, ALL_STRATEGY_NAMES},
+    surveillance::Surveillance,
+    weight_tuning::WeightTuner,
 };
-use crate::sol_market::{
-    market::price_strategies::{
-        other_markets::OtherMarketsPrice, quantity::QuantityPrice, stocastic::StocasticPrice,
-    },
-    TOKEN_DURATION,
+use crate::sol_market::market::price_strategies::{
+    other_markets::OtherMarketsPrice, quantity::QuantityPrice, stocastic::StocasticPrice,
+};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use std::{
+    cell::RefCell,
+    collections::{HashMap, VecDeque},
+    time::Duration,
 };
-use rand::Rng;
-use std::{cell::RefCell, collections::HashMap};
-use unitn_market_2022::good::good::Good;
 
 #[derive(Debug)]
 pub(crate) struct MarketMeta {
-    // Key is token
+    /// Unique in the process, see `SOLMarket::instance_id`
+    pub instance_id: u64,
+    pub logger: Logger,
+    /// The wall-clock time and the passing of the days
+    pub clock: Box<dyn Clock>,
+    // Key is token. Only the live locks, expired ones are in the index.
     pub locked_buys: HashMap<String, GoodLockMeta>,
-    // Key is token
+    // Key is token. Only the live locks, expired ones are in the index.
     pub locked_sells: HashMap<String, GoodLockMeta>,
+    pub buy_lock_index: LockIndex,
+    pub sell_lock_index: LockIndex,
+    /// Forwards and options made by this instance, so that contracts on
+    /// the same terms at the same time still get their own id
+    pub contracts_created: u64,
+    /// Auction orders placed by this instance, to tell their tokens apart
+    pub auction_orders_placed: u64,
+    // Key is the name of the trader, values are the days of its lock requests
+    // and trades
+    pub lock_requests: HashMap<String, VecDeque<u32>>,
+    // Key is the name of the trader
+    pub quota_rejections: HashMap<String, QuotaError>,
+    pub surveillance: Surveillance,
+    pub stats: MarketStats,
+    pub weight_tuner: WeightTuner,
+    pub background_flow: BackgroundFlow,
+    pub fee_ledger: FeeLedger,
+    /// Every change to the state of the market, which is their fold: the
+    /// stock, the locks, the day, the contracts, the margin accounts, the
+    /// auction orders and the deposits. See `SOLMarket::events`.
+    pub event_log: EventLog,
     pub current_day: u32,
     pub file_path: Option<String>,
     pub stocastic_price: RefCell<StocasticPrice>,
     pub quantity_price: QuantityPrice,
     pub other_markets: OtherMarketsPrice,
+    /// The rates computed today, see `RateCache`
+    pub rate_cache: RefCell<RateCache>,
     /// The priority of each pricing strategy relative to the others
     pub weights: HashMap<StrategyName, f32>,
+    pub config: MarketConfig,
+    /// The seed of the random number generators, if the market was given one
+    pub seed: Option<u64>,
+    pub goods_registry: GoodsRegistry,
 }
 
 impl MarketMeta {
-    pub fn new(goods: Vec<Good>, path: Option<&str>, weights: HashMap<StrategyName, f32>) -> Self {
-        let mut r = rand::thread_rng();
+    pub fn new(
+        goods_registry: GoodsRegistry,
+        path: Option<&str>,
+        weights: HashMap<StrategyName, f32>,
+        config: MarketConfig,
+        seed: Option<u64>,
+    ) -> Self {
+        let mut r = match seed {
+            Some(seed) => ChaCha20Rng::seed_from_u64(seed),
+            None => ChaCha20Rng::from_entropy(),
+        };
         let range = 0.0..=1.0;
         let mut all_weights: HashMap<StrategyName, f32> = weights;
         for strategy in ALL_STRATEGY_NAMES {
@@ -37,41 +93,138 @@ impl MarketMeta {
                 .entry(strategy)
                 .or_insert_with(|| r.gen_range(range.clone()));
         }
+        let name = intern_name(&config.name);
+        let clock: Box<dyn Clock> = match config.day_length_ms {
+            Some(day_length) => Box::new(RealTimeClock::new(Duration::from_millis(day_length))),
+            None => Box::new(EventClock),
+        };
         Self {
+            instance_id: next_instance_id(),
+            logger: Logger::new(name, config.log_file.as_deref()),
+            clock,
             locked_buys: Default::default(),
             locked_sells: Default::default(),
+            buy_lock_index: Default::default(),
+            sell_lock_index: Default::default(),
+            contracts_created: 0,
+            auction_orders_placed: 0,
+            lock_requests: Default::default(),
+            quota_rejections: Default::default(),
+            surveillance: Default::default(),
+            stats: Default::default(),
+            weight_tuner: Default::default(),
+            background_flow: BackgroundFlow::new(seed),
+            fee_ledger: Default::default(),
+            event_log: Default::default(),
             current_day: 1,
             file_path: path.map(String::from),
-            stocastic_price: RefCell::new(StocasticPrice::new()),
-            quantity_price: QuantityPrice::new(goods),
+            stocastic_price: RefCell::new(StocasticPrice::new(seed, &goods_registry)),
+            quantity_price: QuantityPrice::new(&goods_registry),
             other_markets: OtherMarketsPrice::new(),
+            rate_cache: Default::default(),
             weights: all_weights,
+            config,
+            seed,
+            goods_registry,
         }
     }
 
-    /// Return the number of buy locks that are not expired
+    /// A copy of the state, for a market in paper mode: nothing is saved nor
+    /// logged, and the days stand still at the current time
+    pub fn paper_copy(&self) -> Self {
+        Self {
+            instance_id: next_instance_id(),
+            logger: Logger {
+                market_name: self.logger.market_name,
+                sink: LogSink::Disabled,
+            },
+            clock: Box::new(SimulatedClock::new(self.clock.now())),
+            locked_buys: self.locked_buys.clone(),
+            locked_sells: self.locked_sells.clone(),
+            buy_lock_index: self.buy_lock_index.clone(),
+            sell_lock_index: self.sell_lock_index.clone(),
+            contracts_created: self.contracts_created,
+            auction_orders_placed: self.auction_orders_placed,
+            lock_requests: self.lock_requests.clone(),
+            quota_rejections: self.quota_rejections.clone(),
+            surveillance: self.surveillance.clone(),
+            stats: self.stats.clone(),
+            weight_tuner: self.weight_tuner.clone(),
+            background_flow: self.background_flow.clone(),
+            fee_ledger: self.fee_ledger.clone(),
+            event_log: self.event_log.without_journal(),
+            current_day: self.current_day,
+            file_path: None,
+            stocastic_price: self.stocastic_price.clone(),
+            quantity_price: self.quantity_price.clone(),
+            other_markets: self.other_markets.clone(),
+            rate_cache: self.rate_cache.clone(),
+            weights: self.weights.clone(),
+            config: self.config.clone(),
+            seed: self.seed,
+            goods_registry: self.goods_registry.clone(),
+        }
+    }
+
+    /// Return the number of sell locks that are not expired
     pub fn num_of_locked_sells(&self, trader_name: &str) -> u32 {
-        let locks_of_this_trader = self
-            .locked_sells
-            .values()
-            .filter(|lock| lock.trader_name == trader_name);
-        let not_expired_locks = locks_of_this_trader.filter(|lock| {
-            let days_since = self.current_day - lock.created_on;
-            days_since <= TOKEN_DURATION
-        });
-        not_expired_locks.count().try_into().unwrap()
+        self.sell_lock_index.count(trader_name)
+            + self
+                .event_log
+                .state()
+                .num_of_pending_orders(trader_name, AuctionSide::Sell)
     }
 
     /// Return the number of buy locks that are not expired
     pub fn num_of_buy_locks(&self, trader_name: &str) -> u32 {
-        let locks_of_this_trader = self
-            .locked_buys
-            .values()
-            .filter(|lock| lock.trader_name == trader_name);
-        let not_expired_locks = locks_of_this_trader.filter(|lock| {
-            let days_since = self.current_day - lock.created_on;
-            days_since <= TOKEN_DURATION
-        });
-        not_expired_locks.count().try_into().unwrap()
+        self.buy_lock_index.count(trader_name)
+            + self
+                .event_log
+                .state()
+                .num_of_pending_orders(trader_name, AuctionSide::Buy)
+    }
+
+    /// Adds a buy lock, and indexes it until it expires
+    pub fn insert_buy_lock(&mut self, token: String, lock: GoodLockMeta) {
+        let expires_on = lock.created_on + self.config.token_duration;
+        self.buy_lock_index
+            .insert(&token, &lock.trader_name, expires_on);
+        self.locked_buys.insert(token, lock);
+    }
+
+    /// Adds a sell lock, and indexes it until it expires
+    pub fn insert_sell_lock(&mut self, token: String, lock: GoodLockMeta) {
+        let expires_on = lock.created_on + self.config.token_duration;
+        self.sell_lock_index
+            .insert(&token, &lock.trader_name, expires_on);
+        self.locked_sells.insert(token, lock);
+    }
+
+    /// Removes a redeemed buy lock
+    pub fn remove_buy_lock(&mut self, token: &str) -> Option<GoodLockMeta> {
+        let lock = self.locked_buys.remove(token)?;
+        self.buy_lock_index.remove(&lock.trader_name);
+        Some(lock)
+    }
+
+    /// Removes a redeemed sell lock
+    pub fn remove_sell_lock(&mut self, token: &str) -> Option<GoodLockMeta> {
+        let lock = self.locked_sells.remove(token)?;
+        self.sell_lock_index.remove(&lock.trader_name);
+        Some(lock)
+    }
+
+    /// Removes the buy locks that expire today, and returns them with their
+    /// tokens
+    pub fn expire_buy_locks(&mut self) -> Vec<(String, GoodLockMeta)> {
+        self.buy_lock_index
+            .expire(self.current_day, &mut self.locked_buys)
+    }
+
+    /// Removes the sell locks that expire today, and returns them with their
+    /// tokens
+    pub fn expire_sell_locks(&mut self) -> Vec<(String, GoodLockMeta)> {
+        self.sell_lock_index
+            .expire(self.current_day, &mut self.locked_sells)
     }
 }
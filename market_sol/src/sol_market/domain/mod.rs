@@ -1,3 +1,26 @@
+pub(crate) mod auction;
+pub(crate) mod background_flow;
+pub(crate) mod clock;
+pub(crate) mod event_log;
+pub(crate) mod fees;
+pub(crate) mod forward_contract;
 pub(crate) mod good_lock_meta;
+pub(crate) mod goods_registry;
+pub(crate) mod instance;
+pub(crate) mod lock_deposit;
+pub(crate) mod lock_index;
+pub(crate) mod margin_account;
+pub(crate) mod market_config;
+pub(crate) mod market_definition;
+pub(crate) mod market_event;
 pub(crate) mod market_meta;
+pub(crate) mod option_contract;
+pub(crate) mod paper;
+pub(crate) mod quota;
+pub(crate) mod quote;
+pub(crate) mod rate_cache;
+pub(crate) mod report;
+pub(crate) mod sol_market_error;
 pub(crate) mod strategy_name;
+pub(crate) mod surveillance;
+pub(crate) mod weight_tuning;
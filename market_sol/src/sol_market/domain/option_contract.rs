@@ -0,0 +1,193 @@
+use serde::{Deserialize, Serialize};
+use std::{error::Error, fmt::Display};
+use unitn_market_2022::{
+    good::{consts::DEFAULT_GOOD_KIND, good_kind::GoodKind},
+    market::MarketGetterError,
+};
+
+/// The right the trader buys from the market
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OptionKind {
+    /// The right to buy the good at the strike price
+    Call,
+    /// The right to sell the good at the strike price
+    Put,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptionStatus {
+    /// Waiting for the expiry day
+    Open,
+    /// The option was worth exercising, the trader gets the other side
+    Exercised,
+    /// The option was not worth exercising, the trader gets the deposit back
+    Expired,
+}
+
+/// What a trader asks for when buying an option from the market
+#[derive(Debug, Clone, PartialEq)]
+pub struct OptionRequest {
+    pub trader_name: String,
+    pub kind: OptionKind,
+    /// Any good but DEFAULT_GOOD_KIND
+    pub good_kind: GoodKind,
+    pub quantity: f32,
+    /// The price, in DEFAULT_GOOD_KIND, for the whole quantity
+    pub strike: f32,
+    /// The market day the option is exercised on, if it is worth it
+    pub expiry_day: u32,
+    /// The most the trader pays for the option, in DEFAULT_GOOD_KIND
+    pub premium: f32,
+}
+
+/// A European option written by the market
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OptionContract {
+    pub id: String,
+    pub trader_name: String,
+    pub kind: OptionKind,
+    pub good_kind: GoodKind,
+    pub quantity: f32,
+    /// The price, in DEFAULT_GOOD_KIND, for the whole quantity
+    pub strike: f32,
+    /// What the trader paid, in DEFAULT_GOOD_KIND
+    pub premium: f32,
+    pub created_on: u32,
+    pub expiry_day: u32,
+    /// What the trader deposited: the strike for a call, the good for a put
+    pub deposit_kind: GoodKind,
+    pub deposit: f32,
+    /// What the market set aside: the good for a call, the strike for a put
+    pub reserved_kind: GoodKind,
+    pub reserved: f32,
+    pub status: OptionStatus,
+}
+
+impl OptionContract {
+    /// What the trader gets once the option is exercised or expired
+    pub fn payout(&self) -> Option<(GoodKind, f32)> {
+        match self.status {
+            OptionStatus::Open => None,
+            OptionStatus::Exercised => Some((self.reserved_kind, self.reserved)),
+            OptionStatus::Expired => Some((self.deposit_kind, self.deposit)),
+        }
+    }
+}
+
+/// What can go wrong when buying or collecting an option
+#[derive(Debug, Clone, PartialEq)]
+pub enum OptionError {
+    /// Options are only written on goods other than DEFAULT_GOOD_KIND
+    UnsupportedGood {
+        good_kind: GoodKind,
+    },
+    NonPositiveQuantity {
+        quantity: f32,
+    },
+    NonPositiveStrike {
+        strike: f32,
+    },
+    /// The expiry day has to be after the current day
+    ExpiryNotInTheFuture {
+        expiry_day: u32,
+        current_day: u32,
+    },
+    /// The market can not price the good, e.g., it does not have enough
+    PriceUnavailable(MarketGetterError),
+    /// The market does not have enough of a good to set aside
+    InsufficientMarketQuantity {
+        kind: GoodKind,
+        required: f32,
+        available: f32,
+    },
+    /// The trader offers less than the premium the market asks
+    PremiumTooLow {
+        offered: f32,
+        required: f32,
+    },
+    WrongPaymentKind {
+        provided: GoodKind,
+    },
+    InsufficientPayment {
+        required: f32,
+        provided: f32,
+    },
+    WrongDepositKind {
+        expected: GoodKind,
+        provided: GoodKind,
+    },
+    InsufficientDeposit {
+        required: f32,
+        provided: f32,
+    },
+    UnrecognizedContract {
+        id: String,
+    },
+    /// The option has not reached its expiry day yet
+    NothingToCollect {
+        id: String,
+    },
+}
+
+impl Display for OptionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OptionError::UnsupportedGood { good_kind } => {
+                write!(f, "The market does not write options on {good_kind}")
+            }
+            OptionError::NonPositiveQuantity { quantity } => {
+                write!(f, "The quantity should be positive, but is {quantity}")
+            }
+            OptionError::NonPositiveStrike { strike } => {
+                write!(f, "The strike should be positive, but is {strike}")
+            }
+            OptionError::ExpiryNotInTheFuture {
+                expiry_day,
+                current_day,
+            } => write!(
+                f,
+                "The expiry day ({expiry_day}) should be after the current day ({current_day})"
+            ),
+            OptionError::PriceUnavailable(e) => {
+                write!(f, "The market can not price the option: {e:?}")
+            }
+            OptionError::InsufficientMarketQuantity {
+                kind,
+                required,
+                available,
+            } => write!(
+                f,
+                "The market needs {required} {kind} to write the option, but has {available}"
+            ),
+            OptionError::PremiumTooLow { offered, required } => write!(
+                f,
+                "The premium offered ({offered}) is lower than the one asked ({required})"
+            ),
+            OptionError::WrongPaymentKind { provided } => {
+                write!(
+                    f,
+                    "The premium should be paid in {DEFAULT_GOOD_KIND}, not {provided}"
+                )
+            }
+            OptionError::InsufficientPayment { required, provided } => write!(
+                f,
+                "The premium is {required}, but only {provided} was provided"
+            ),
+            OptionError::WrongDepositKind { expected, provided } => {
+                write!(f, "The deposit should be {expected}, but is {provided}")
+            }
+            OptionError::InsufficientDeposit { required, provided } => write!(
+                f,
+                "The deposit should be at least {required}, but is {provided}"
+            ),
+            OptionError::UnrecognizedContract { id } => {
+                write!(f, "There is no option with id {id}")
+            }
+            OptionError::NothingToCollect { id } => {
+                write!(f, "Option {id} has not expired yet")
+            }
+        }
+    }
+}
+
+impl Error for OptionError {}
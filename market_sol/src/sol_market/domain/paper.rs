@@ -0,0 +1,49 @@
+use super::{fees::TradeSide, quote::QuoteLadder};
+use std::collections::HashMap;
+use unitn_market_2022::{
+    good::good_kind::GoodKind,
+    market::{BuyError, LockBuyError, LockSellError, SellError},
+};
+
+/// A trade tried on a copy of the market, see `SOLMarket::paper_buy` and
+/// `SOLMarket::paper_sell`
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaperTrade {
+    pub good_kind: GoodKind,
+    pub side: TradeSide,
+    pub quantity: f32,
+    /// What the trader would pay (or get), in DEFAULT_GOOD_KIND, fee included
+    pub price: f32,
+    /// The fee included in `price`
+    pub fee: f32,
+    /// The prices of every good for `quantity`, before the trade
+    pub before: HashMap<GoodKind, QuoteLadder>,
+    /// The prices of every good for `quantity`, once the trade is done
+    pub after: HashMap<GoodKind, QuoteLadder>,
+}
+
+impl PaperTrade {
+    /// How much the rate of the good, on the side of the trade, moved
+    /// because of it, e.g., 0.02 if the next trade gets 2% more goods for
+    /// the same EUR
+    pub fn rate_impact(&self) -> f32 {
+        let rate = |ladders: &HashMap<GoodKind, QuoteLadder>| {
+            let ladder = &ladders[&self.good_kind];
+            match self.side {
+                TradeSide::Buy => ladder.exchange_rate_buy,
+                TradeSide::Sell => ladder.exchange_rate_sell,
+            }
+        };
+        rate(&self.after) / rate(&self.before) - 1.0
+    }
+}
+
+/// Why a paper trade could not be done: what the market would have
+/// answered to the real one
+#[derive(Debug, Clone, PartialEq)]
+pub enum PaperError {
+    LockBuy(LockBuyError),
+    Buy(BuyError),
+    LockSell(LockSellError),
+    Sell(SellError),
+}
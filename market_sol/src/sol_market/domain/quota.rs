@@ -0,0 +1,130 @@
+use std::{error::Error, fmt::Display};
+use unitn_market_2022::{
+    good::good_kind::GoodKind,
+    market::{LockBuyError, LockSellError},
+};
+
+/// Why the quotas of the market rejected a lock.
+///
+/// The errors of the `Market` trait can not be extended, so `lock_buy` and
+/// `lock_sell` return the closest one they have: `NonPositiveQuantityTo*`
+/// for a lock of nothing, `MaxAllowedLocksReached` for everything else,
+/// dust included. The full reason is logged and kept until the next
+/// rejection, see `SOLMarket::last_quota_rejection`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum QuotaError {
+    /// The lock is worth less than `MarketConfig::min_lock_value`
+    DustLock {
+        kind: GoodKind,
+        quantity: f32,
+        value: f32,
+        min_value: f32,
+    },
+    /// The trader already asked for `MarketConfig::max_locks_per_window`
+    /// locks in the last `MarketConfig::quota_window_days` days
+    TooManyLocks {
+        trader_name: String,
+        limit: u32,
+        window_days: u32,
+    },
+    /// The lock would take the value locked by the trader above
+    /// `MarketConfig::max_locked_value`
+    LockedValueExceeded {
+        trader_name: String,
+        locked_value: f32,
+        requested_value: f32,
+        limit: f32,
+    },
+    /// The DEFAULT_GOOD_KIND collateral of the margin account of the trader
+    /// does not cover the deposit of the lock, see `LockDepositConfig`
+    DepositNotCovered {
+        trader_name: String,
+        deposit: f32,
+        collateral: f32,
+    },
+}
+
+/// How much of its quotas a trader is using
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuotaUsage {
+    /// Lock requests and trades made in the last
+    /// `MarketConfig::quota_window_days` days, including the rejected locks
+    pub locks_in_window: u32,
+    /// Value, in DEFAULT_GOOD_KIND, of the unexpired locks of the trader
+    pub locked_value: f32,
+}
+
+impl Display for QuotaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QuotaError::DustLock {
+                kind,
+                quantity,
+                value,
+                min_value,
+            } => write!(
+                f,
+                "{quantity} {kind} is worth {value}, locks should be worth at least {min_value}"
+            ),
+            QuotaError::TooManyLocks {
+                trader_name,
+                limit,
+                window_days,
+            } => write!(
+                f,
+                "{trader_name} already asked for {limit} locks in the last {window_days} days"
+            ),
+            QuotaError::LockedValueExceeded {
+                trader_name,
+                locked_value,
+                requested_value,
+                limit,
+            } => write!(
+                f,
+                "{trader_name} has {locked_value} locked, another {requested_value} would exceed {limit}"
+            ),
+            QuotaError::DepositNotCovered {
+                trader_name,
+                deposit,
+                collateral,
+            } => write!(
+                f,
+                "{trader_name} has {collateral} of collateral, the lock needs a deposit of {deposit}"
+            ),
+        }
+    }
+}
+
+impl Error for QuotaError {}
+
+impl From<QuotaError> for LockBuyError {
+    fn from(e: QuotaError) -> Self {
+        match e {
+            QuotaError::DustLock { quantity, .. } if quantity <= 0.0 => {
+                LockBuyError::NonPositiveQuantityToBuy {
+                    negative_quantity_to_buy: quantity,
+                }
+            }
+            QuotaError::DustLock { .. }
+            | QuotaError::TooManyLocks { .. }
+            | QuotaError::LockedValueExceeded { .. }
+            | QuotaError::DepositNotCovered { .. } => LockBuyError::MaxAllowedLocksReached,
+        }
+    }
+}
+
+impl From<QuotaError> for LockSellError {
+    fn from(e: QuotaError) -> Self {
+        match e {
+            QuotaError::DustLock { quantity, .. } if quantity <= 0.0 => {
+                LockSellError::NonPositiveQuantityToSell {
+                    negative_quantity_to_sell: quantity,
+                }
+            }
+            QuotaError::DustLock { .. }
+            | QuotaError::TooManyLocks { .. }
+            | QuotaError::LockedValueExceeded { .. }
+            | QuotaError::DepositNotCovered { .. } => LockSellError::MaxAllowedLocksReached,
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use unitn_market_2022::{good::good_kind::GoodKind, market::MarketGetterError};
+
+/// The prices of one good for one quantity
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quote {
+    pub quantity: f32,
+    /// What `get_buy_price` would return for this quantity
+    pub buy_price: Result<f32, MarketGetterError>,
+    /// What `get_sell_price` would return for this quantity
+    pub sell_price: Result<f32, MarketGetterError>,
+    /// The fee added to `buy_price`, without the discount of the trader
+    pub buy_fee: f32,
+    /// The fee taken off `sell_price`, without the discount of the trader
+    pub sell_fee: f32,
+}
+
+/// The prices of one good for several quantities, all computed from the same
+/// state of the market
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuoteLadder {
+    pub good_kind: GoodKind,
+    /// In how many days the goods are delivered. 0 for spot prices.
+    pub days_ahead: u32,
+    /// Rate (EUR/good_kind) applied when the trader buys
+    pub exchange_rate_buy: f32,
+    /// Rate (EUR/good_kind) applied when the trader sells
+    pub exchange_rate_sell: f32,
+    /// How much of the good is available (not locked)
+    pub available_quantity: f32,
+    /// One quote per requested quantity, in the same order
+    pub quotes: Vec<Quote>,
+}
@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+
+/// The exchange rates of the market computed today, see
+/// `SOLMarket::get_exchange_rate`.
+///
+/// The stocastic rate only changes with the day, but the quantity rate
+/// follows the stock of the good and the other markets rate follows their
+/// trades: a rate is reused only while all three are the same. The weights
+/// of the strategies are not part of the key, so the cache is cleared when
+/// they change.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RateCache {
+    /// Key is the ticker of the good
+    rates: HashMap<String, CachedRate>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct CachedRate {
+    day: u32,
+    /// The stock of the good the rate was computed with
+    stock: f32,
+    /// The rate of the other markets the rate was computed with
+    other_rate: f32,
+    rate: f32,
+}
+
+impl RateCache {
+    /// Returns the rate of the good, if it was computed with the same inputs
+    pub fn get(&self, ticker: &str, day: u32, stock: f32, other_rate: f32) -> Option<f32> {
+        self.rates
+            .get(ticker)
+            .filter(|cached| {
+                cached.day == day && cached.stock == stock && cached.other_rate == other_rate
+            })
+            .map(|cached| cached.rate)
+    }
+
+    pub fn insert(&mut self, ticker: &str, day: u32, stock: f32, other_rate: f32, rate: f32) {
+        self.rates.insert(
+            ticker.to_string(),
+            CachedRate {
+                day,
+                stock,
+                other_rate,
+                rate,
+            },
+        );
+    }
+
+    pub fn clear(&mut self) {
+        self.rates.clear();
+    }
+}
@@ -0,0 +1,174 @@
+use crate::sol_market::FeeTotals;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::Display,
+};
+use unitn_market_2022::good::good_kind::GoodKind;
+
+/// How many days of total value the market remembers
+pub(crate) const VALUE_HISTORY_DAYS: usize = 1000;
+
+/// The statistics and health of a market at some day. See
+/// `SOLMarket::report`.
+///
+/// Rendered as text with `Display`, or as JSON with `to_json`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MarketReport {
+    pub market_name: String,
+    pub day: u32,
+    /// Value of all the goods of the market, locked ones included, in
+    /// DEFAULT_GOOD_KIND at the default rates
+    pub total_value: f32,
+    /// Total value at the end of each of the last days, from the oldest
+    pub value_history: Vec<ValuePoint>,
+    /// What the trades earned over the rate of the market at the time of
+    /// the lock, in DEFAULT_GOOD_KIND. Buys earn the bid above the rate,
+    /// sells the offer below it.
+    pub realized_margin: f32,
+    pub volumes: Vec<GoodVolume>,
+    pub locks: LockCounts,
+    pub internal_trades: u32,
+    /// Value moved by the internal trades, in DEFAULT_GOOD_KIND
+    pub internal_traded_value: f32,
+    /// Trades of the background flow, see `BackgroundFlowConfig`
+    #[serde(default)]
+    pub background_trades: u32,
+    /// What the background trades paid and were paid, in DEFAULT_GOOD_KIND
+    #[serde(default)]
+    pub background_traded_value: f32,
+    /// Fees paid by the traders, see `FeeSchedule`
+    #[serde(default)]
+    pub fees: FeeTotals,
+    pub weights: Vec<StrategyWeight>,
+    pub rates: Vec<GoodRates>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ValuePoint {
+    pub day: u32,
+    pub value: f32,
+}
+
+/// What the traders traded of one good
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct GoodVolume {
+    pub kind: GoodKind,
+    pub trades: u32,
+    /// Quantity the traders bought from the market
+    pub bought: f32,
+    /// Quantity the traders sold to the market
+    pub sold: f32,
+    /// What the traders paid and were paid, in DEFAULT_GOOD_KIND
+    pub bought_value: f32,
+    pub sold_value: f32,
+}
+
+/// Buy and sell locks, counted together
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LockCounts {
+    pub created: u32,
+    /// Not redeemed in time, their goods went back to the market
+    pub expired: u32,
+    /// Turned into a buy or a sell
+    pub redeemed: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StrategyWeight {
+    pub strategy: String,
+    pub weight: f32,
+}
+
+/// The rates (EUR/kind) of one good, per strategy and combined
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct GoodRates {
+    pub kind: GoodKind,
+    pub stocastic: f32,
+    pub quantity: f32,
+    pub others: f32,
+    /// Applied when the trader buys
+    pub buy: f32,
+    /// Applied when the trader sells
+    pub sell: f32,
+}
+
+impl MarketReport {
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+impl Display for MarketReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} market, day {}", self.market_name, self.day)?;
+        writeln!(f, "Total value: {:.2}", self.total_value)?;
+        if let (Some(first), Some(last)) = (self.value_history.first(), self.value_history.last()) {
+            writeln!(
+                f,
+                "Value since day {}: {:.2} -> {:.2}",
+                first.day, first.value, last.value
+            )?;
+        }
+        writeln!(f, "Realized margin: {:.2}", self.realized_margin)?;
+        writeln!(
+            f,
+            "Locks: {} created, {} redeemed, {} expired",
+            self.locks.created, self.locks.redeemed, self.locks.expired
+        )?;
+        writeln!(
+            f,
+            "Internal trades: {} ({:.2} moved)",
+            self.internal_trades, self.internal_traded_value
+        )?;
+        if self.background_trades > 0 {
+            writeln!(
+                f,
+                "Background trades: {} ({:.2} traded)",
+                self.background_trades, self.background_traded_value
+            )?;
+        }
+        if self.fees != FeeTotals::default() {
+            writeln!(
+                f,
+                "Fees: {:.2} on buys, {:.2} on sells",
+                self.fees.buy, self.fees.sell
+            )?;
+        }
+        writeln!(f, "Volumes:")?;
+        for v in self.volumes.iter() {
+            writeln!(
+                f,
+                "  {}: {} trades, bought {} for {:.2}, sold {} for {:.2}",
+                v.kind, v.trades, v.bought, v.bought_value, v.sold, v.sold_value
+            )?;
+        }
+        writeln!(f, "Weights:")?;
+        for w in self.weights.iter() {
+            writeln!(f, "  {}: {}", w.strategy, w.weight)?;
+        }
+        writeln!(f, "Rates (stocastic/quantity/others -> buy/sell):")?;
+        for r in self.rates.iter() {
+            writeln!(
+                f,
+                "  {}: {}/{}/{} -> {}/{}",
+                r.kind, r.stocastic, r.quantity, r.others, r.buy, r.sell
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The counters behind the report, updated as the market runs
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MarketStats {
+    pub value_history: VecDeque<ValuePoint>,
+    pub realized_margin: f32,
+    pub volumes: HashMap<GoodKind, GoodVolume>,
+    pub locks: LockCounts,
+    pub internal_trades: u32,
+    pub internal_traded_value: f32,
+    pub background_trades: u32,
+    pub background_traded_value: f32,
+    pub fees: FeeTotals,
+}
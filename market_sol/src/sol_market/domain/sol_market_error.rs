@@ -0,0 +1,141 @@
+use std::{error::Error, fmt::Display, io, path::PathBuf};
+use unitn_market_2022::good::good_kind::GoodKind;
+
+/// What can go wrong when creating a [`crate::SOLMarket`] or dealing with its
+/// state file
+#[derive(Debug)]
+pub enum SOLMarketError {
+    /// The market was asked to start with a negative quantity of a good
+    NegativeQuantity { ticker: String, quantity: f32 },
+    /// A parameter of a good, e.g., its volatility, is out of its range
+    InvalidGoodParameter {
+        ticker: String,
+        parameter: &'static str,
+        value: f32,
+    },
+    /// An I/O operation on the state file failed
+    Io {
+        path: PathBuf,
+        /// What we were doing, e.g., "read"
+        action: &'static str,
+        source: io::Error,
+    },
+    /// A line of a state file, or of a journal of events, could not be
+    /// understood. `line` starts from 1.
+    Parse {
+        path: PathBuf,
+        line: usize,
+        reason: String,
+    },
+    /// The market definition does not declare the quantity of a good.
+    /// `path` is `None` if the definition was not read from a file.
+    MissingGood {
+        path: Option<PathBuf>,
+        good_kind: GoodKind,
+    },
+    /// The operation needs a state file, but the market was not created
+    /// from one
+    NoStateFile,
+    /// The state file was written with a schema this version cannot read
+    UnsupportedSchemaVersion { path: PathBuf, found: u32 },
+    /// The configured name of the market is empty, or would break its log
+    InvalidName { name: String },
+    /// A parameter of the configuration of the market, or of a simulation,
+    /// is out of its range. `parameter` is its path in the configuration,
+    /// e.g., `weight_tuning.min_weight`.
+    InvalidConfig { parameter: &'static str, value: f32 },
+    /// A good was registered with the ticker or the kind of the good
+    /// `other`, already in the registry
+    DuplicateGood { ticker: String, other: String },
+    /// The good at `index` of the market definition has neither a kind nor a
+    /// ticker
+    UnnamedGood { index: usize },
+    /// The market was to be saved in the `.sol` format, which would lose
+    /// `lost`, e.g., its forward contracts
+    LossySolFile { path: PathBuf, lost: String },
+    /// The events of a journal could not be replayed, e.g., one of them was
+    /// refused. `line` starts from 1.
+    Replay {
+        path: PathBuf,
+        line: usize,
+        reason: String,
+    },
+}
+
+impl Display for SOLMarketError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SOLMarketError::NegativeQuantity { ticker, quantity } => write!(
+                f,
+                "Tried to initialize the market with a negative quantity of {ticker}: {quantity}"
+            ),
+            SOLMarketError::InvalidGoodParameter {
+                ticker,
+                parameter,
+                value,
+            } => write!(f, "The {parameter} of {ticker} is out of range: {value}"),
+            SOLMarketError::Io {
+                path,
+                action,
+                source,
+            } => write!(f, "Could not {action} {}: {source}", path.display()),
+            SOLMarketError::Parse { path, line, reason } => {
+                write!(f, "{}:{line}: {reason}", path.display())
+            }
+            SOLMarketError::MissingGood {
+                path: Some(path),
+                good_kind,
+            } => write!(
+                f,
+                "{} does not declare the quantity of {good_kind}",
+                path.display()
+            ),
+            SOLMarketError::MissingGood {
+                path: None,
+                good_kind,
+            } => write!(
+                f,
+                "The market definition does not declare the quantity of {good_kind}"
+            ),
+            SOLMarketError::NoStateFile => write!(f, "The market has no state file"),
+            SOLMarketError::UnsupportedSchemaVersion { path, found } => write!(
+                f,
+                "{} uses schema version {found}, but only version {} is supported",
+                path.display(),
+                crate::sol_market::MARKET_DEFINITION_SCHEMA_VERSION
+            ),
+            SOLMarketError::InvalidName { name } => {
+                write!(f, "The market can not be named {name:?}")
+            }
+            SOLMarketError::InvalidConfig { parameter, value } => {
+                write!(
+                    f,
+                    "The {parameter} of the configuration is out of range: {value}"
+                )
+            }
+            SOLMarketError::DuplicateGood { ticker, other } => {
+                write!(f, "{ticker} can not be registered, {other} is in its place")
+            }
+            SOLMarketError::UnnamedGood { index } => {
+                write!(f, "The good number {index} has neither a kind nor a ticker")
+            }
+            SOLMarketError::LossySolFile { path, lost } => write!(
+                f,
+                "{} is a .sol file, which can not keep {lost}: use .toml or .json",
+                path.display()
+            ),
+            SOLMarketError::Replay { path, line, reason } => {
+                write!(f, "{}:{line}: can not replay: {reason}", path.display())
+            }
+        }
+    }
+}
+
+impl Error for SOLMarketError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SOLMarketError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
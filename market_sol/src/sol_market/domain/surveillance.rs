@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::Display,
+};
+use unitn_market_2022::good::good_kind::GoodKind;
+
+/// How the market watches the locks and trades of each trader, and what it
+/// does with the suspicious ones. See `SOLMarket::surveillance_flags`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct SurveillanceConfig {
+    pub enabled: bool,
+    /// How many days of activity are looked at
+    pub window_days: u32,
+    /// Wash trading: at least this many buys and sells of a good...
+    pub min_wash_trades: u32,
+    /// ...ending up with a net quantity below this share of the traded one
+    pub wash_net_ratio: f32,
+    /// Lock spamming: at least this many locks...
+    pub spam_min_locks: u32,
+    /// ...of which at most this share turned into a trade
+    pub spam_max_fill_ratio: f32,
+    /// Pump and dump: buying at least this share of the stock of a good...
+    pub pump_min_stock_share: f32,
+    /// ...and selling it for at least this much more than it cost
+    pub pump_min_gain: f32,
+    /// Penalize flagged traders. If false, flags are only logged.
+    pub penalties: bool,
+    /// How much worse the prices of a flagged trader are
+    pub penalty_spread: f32,
+    /// How long the worse prices last
+    pub penalty_days: u32,
+    /// A trader flagged this many times in the window is banned
+    pub ban_after_flags: u32,
+    /// How long a ban lasts. Banned traders can not lock.
+    pub ban_days: u32,
+}
+
+impl Default for SurveillanceConfig {
+    fn default() -> Self {
+        SurveillanceConfig {
+            enabled: true,
+            window_days: 30,
+            min_wash_trades: 4,
+            wash_net_ratio: 0.1,
+            spam_min_locks: 10,
+            spam_max_fill_ratio: 0.2,
+            pump_min_stock_share: 0.05,
+            pump_min_gain: 0.2,
+            penalties: false,
+            penalty_spread: 0.05,
+            penalty_days: 30,
+            ban_after_flags: 3,
+            ban_days: 30,
+        }
+    }
+}
+
+/// The kinds of manipulation the market looks for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuspiciousPattern {
+    /// Buying and selling the same good back and forth, to fake volume
+    WashTrading,
+    /// Locking without trading, to move prices or hold goods hostage
+    LockSpam,
+    /// Buying a large share of a good, then selling it at a higher price
+    PumpAndDump,
+}
+
+/// A trader caught in a suspicious pattern
+#[derive(Debug, Clone, PartialEq)]
+pub struct SurveillanceFlag {
+    pub trader_name: String,
+    pub pattern: SuspiciousPattern,
+    pub day: u32,
+    /// What triggered the flag
+    pub details: String,
+}
+
+impl Display for SuspiciousPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            SuspiciousPattern::WashTrading => "WASH_TRADING",
+            SuspiciousPattern::LockSpam => "LOCK_SPAM",
+            SuspiciousPattern::PumpAndDump => "PUMP_AND_DUMP",
+        };
+        write!(f, "{name}")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ActivityKind {
+    LockBuy,
+    LockSell,
+    Buy,
+    Sell,
+}
+
+/// A lock or a trade of a trader
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Activity {
+    pub day: u32,
+    pub kind: ActivityKind,
+    pub good_kind: GoodKind,
+    pub quantity: f32,
+    /// In DEFAULT_GOOD_KIND, for the whole quantity
+    pub price: f32,
+    /// The quantity of the good the market had
+    pub market_stock: f32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TraderRecord {
+    /// Within the window, from the oldest
+    pub activity: VecDeque<Activity>,
+    pub flags: Vec<SurveillanceFlag>,
+    pub penalized_until: Option<u32>,
+    pub banned_until: Option<u32>,
+}
+
+/// What the market knows about the behaviour of each trader
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Surveillance {
+    // Key is the name of the trader
+    pub traders: HashMap<String, TraderRecord>,
+}
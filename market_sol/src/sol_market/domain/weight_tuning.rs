@@ -0,0 +1,120 @@
+use crate::sol_market::SOLMarketError;
+use serde::{Deserialize, Serialize};
+use unitn_market_2022::good::good_kind::GoodKind;
+
+/// How the market tunes the weights of its pricing strategies while it runs.
+/// See `SOLMarket::weight_changes`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct WeightTuningConfig {
+    /// If false, the weights never change
+    pub enabled: bool,
+    /// What the tuning tries to improve
+    pub objective: TuningObjective,
+    /// How much a weight moves in a step, as a share of
+    /// `max_weight - min_weight`
+    pub learning_rate: f32,
+    /// The tuning never moves a weight below this, which must be positive...
+    pub min_weight: f32,
+    /// ...or above this
+    pub max_weight: f32,
+    /// How many days each step is evaluated for
+    pub every_days: u32,
+    /// The inventory tracked by `TuningObjective::Inventory`. Goods that are
+    /// not listed are tracked to their `GoodSpec::initial_stock`.
+    pub target_inventory: Vec<InventoryTarget>,
+}
+
+impl Default for WeightTuningConfig {
+    fn default() -> Self {
+        WeightTuningConfig {
+            enabled: false,
+            objective: TuningObjective::Margin,
+            learning_rate: 0.1,
+            min_weight: 0.05,
+            max_weight: 1.0,
+            every_days: 20,
+            target_inventory: vec![],
+        }
+    }
+}
+
+impl WeightTuningConfig {
+    /// Checks that the weights can be kept between `min_weight` and
+    /// `max_weight`, and that they can not all be 0
+    pub(crate) fn validate(&self) -> Result<(), SOLMarketError> {
+        for (parameter, value) in [
+            ("weight_tuning.learning_rate", self.learning_rate),
+            ("weight_tuning.min_weight", self.min_weight),
+            ("weight_tuning.max_weight", self.max_weight),
+        ] {
+            if !(value.is_finite() && value >= 0.0) {
+                return Err(SOLMarketError::InvalidConfig { parameter, value });
+            }
+        }
+        //The rates are an average weighted by the weights, which needs at
+        //least one of them to be positive
+        if self.min_weight == 0.0 {
+            return Err(SOLMarketError::InvalidConfig {
+                parameter: "weight_tuning.min_weight",
+                value: self.min_weight,
+            });
+        }
+        if self.max_weight < self.min_weight {
+            return Err(SOLMarketError::InvalidConfig {
+                parameter: "weight_tuning.max_weight",
+                value: self.max_weight,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// What the weight tuning tries to improve
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TuningObjective {
+    /// The margin realized by the trades of each period, see
+    /// `MarketReport::realized_margin`
+    Margin,
+    /// How close the inventory is to `WeightTuningConfig::target_inventory`,
+    /// by value
+    Inventory,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct InventoryTarget {
+    pub kind: GoodKind,
+    pub quantity: f32,
+}
+
+/// A step of the weight tuning
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeightChange {
+    pub day: u32,
+    /// Name of the strategy, as in the state file
+    pub strategy: String,
+    pub old_weight: f32,
+    pub new_weight: f32,
+    /// The score of the period that led to the change
+    pub score: f32,
+    /// Whether the change undoes the previous step, which made the score
+    /// worse
+    pub reverted: bool,
+}
+
+/// The state of the hill climbing: one weight moves at a time, and the move
+/// is kept only if the next period scores at least as well as the one before
+#[derive(Debug, Clone, Default)]
+pub(crate) struct WeightTuner {
+    /// Index in ALL_STRATEGY_NAMES of the next weight to move
+    pub next_strategy: usize,
+    /// Per strategy, whether the next move is down rather than up
+    pub downwards: [bool; 3],
+    /// The strategy and the delta of the last move, still to be evaluated
+    pub pending: Option<(usize, f32)>,
+    pub last_score: Option<f32>,
+    /// The realized margin at the start of the period
+    pub period_start_margin: f32,
+    pub changes: Vec<WeightChange>,
+}
@@ -0,0 +1,385 @@
+use crate::sol_market::{
+    domain::{good_lock_meta::GoodLockMeta, surveillance::ActivityKind},
+    AuctionOrder, AuctionSide, AuctionStatus, MarketEvent, SOLMarket, TradeSide,
+};
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+use unitn_market_2022::{
+    event::event::{Event, EventKind},
+    good::{consts::DEFAULT_GOOD_KIND, good_kind::GoodKind},
+};
+
+/// This block contains the call-auction mode, enabled by
+/// `AuctionConfig::enabled`.
+///
+/// `lock_buy` and `lock_sell` do not fill the lock at once: they check the
+/// trader as usual, then place an order whose bid (or offer) is a limit, and
+/// return its token. Every `every_days` days, the orders of each good are
+/// cleared together at a single price, the one that matches the most volume
+/// among the limits of the orders and the rates of the market. Buyers and
+/// sellers are matched with each other, and the market takes the imbalance
+/// along its inventory curve: the more of its stock it sells (or the more it
+/// buys), the higher (or lower) the price it asks.
+///
+/// The orders within the clearing price share the matched volume pro rata.
+/// A filled order becomes a lock of the filled quantity at the clearing
+/// price, so its token works with `buy` and `sell` as usual, until
+/// `token_duration` days after the clearing.
+impl SOLMarket {
+    /// Returns the order of the auction with the given token, if it was
+    /// placed in the last `token_duration` days
+    pub fn auction_order(&self, token: &str) -> Option<&AuctionOrder> {
+        self.event_state().auction_orders.get(token)
+    }
+
+    /// Returns the orders waiting for the next clearing, from the oldest
+    pub fn pending_auction_orders(&self) -> Vec<&AuctionOrder> {
+        let mut orders: Vec<&AuctionOrder> = self
+            .event_state()
+            .auction_orders
+            .values()
+            .filter(|order| order.status == AuctionStatus::Pending)
+            .collect();
+        orders.sort_by(|a, b| (a.day, &a.token).cmp(&(b.day, &b.token)));
+        orders
+    }
+
+    /// Places an order for the next clearing, and returns its token
+    pub(crate) fn place_auction_order(
+        &mut self,
+        side: AuctionSide,
+        good_kind: GoodKind,
+        quantity: f32,
+        limit: f32,
+        deposit: f32,
+        trader_name: String,
+    ) -> String {
+        let mut hasher = DefaultHasher::new();
+        (
+            good_kind,
+            quantity.to_string(),
+            limit.to_string(),
+            self.meta.clock.now(),
+            self.meta.auction_orders_placed,
+            trader_name.clone(),
+        )
+            .hash(&mut hasher);
+        let token = hasher.finish().to_string();
+        self.meta.auction_orders_placed += 1;
+
+        let order = AuctionOrder {
+            token: token.clone(),
+            trader_name: trader_name.clone(),
+            side,
+            good_kind,
+            quantity,
+            limit,
+            day: self.meta.current_day,
+            deposit,
+            status: AuctionStatus::Pending,
+        };
+        self.apply(MarketEvent::AuctionOrderPlaced { order })
+            .expect("An order does not take from the stock");
+        self.hold_lock_deposit(&trader_name, deposit);
+        let (activity, event_kind, code) = match side {
+            AuctionSide::Buy => (ActivityKind::LockBuy, EventKind::LockedBuy, "BUY"),
+            AuctionSide::Sell => (ActivityKind::LockSell, EventKind::LockedSell, "SELL"),
+        };
+        self.watch(&trader_name, activity, good_kind, quantity, limit);
+
+        self.notify_everyone(Event {
+            kind: event_kind,
+            good_kind,
+            quantity,
+            price: limit,
+        });
+
+        self.log(format!("AUCTION_{code}-{trader_name}-KIND:{good_kind}-QUANTITY:{quantity:+e}-LIMIT:{limit:+e}-TOKEN:{token}"));
+        token
+    }
+
+    /// Clears the pending orders, if the auction clears today
+    pub(crate) fn clear_auction_if_needed(&mut self) {
+        // Forget the orders whose lock can not be redeemed anymore
+        let today = self.meta.current_day;
+        let token_duration = self.meta.config.token_duration;
+        let mut forgotten: Vec<String> = self
+            .event_state()
+            .auction_orders
+            .values()
+            .filter(|order| match order.status {
+                AuctionStatus::Pending => false,
+                AuctionStatus::Filled { day, .. } | AuctionStatus::Unfilled { day, .. } => {
+                    today - day > token_duration
+                }
+            })
+            .map(|order| order.token.clone())
+            .collect();
+        forgotten.sort();
+        for token in forgotten {
+            self.apply_or_log(MarketEvent::AuctionOrderForgotten { token });
+        }
+
+        let config = &self.meta.config.auction;
+        if !config.enabled || config.every_days == 0 {
+            return;
+        }
+        let days_in_period = today % config.every_days;
+        if days_in_period != 0 {
+            return;
+        }
+        let kinds: Vec<GoodKind> = self
+            .meta
+            .goods_registry
+            .traded()
+            .map(|(kind, _)| kind)
+            .collect();
+        for kind in kinds {
+            self.clear_auction(kind);
+        }
+    }
+
+    /// Clears the pending orders of a good
+    fn clear_auction(&mut self, kind: GoodKind) {
+        let orders: Vec<AuctionOrder> = self
+            .pending_auction_orders()
+            .into_iter()
+            .filter(|order| order.good_kind == kind)
+            .cloned()
+            .collect();
+        if orders.is_empty() {
+            return;
+        }
+        let today = self.meta.current_day;
+        let curve = InventoryCurve {
+            ask: 1.0 / self.get_good_buy_exchange_rate(kind),
+            bid: 1.0 / self.get_good_sell_exchange_rate(kind),
+            stock: self.get_available_quantity(kind),
+            cash: self.get_available_quantity(DEFAULT_GOOD_KIND),
+        };
+
+        // Try the limits of the orders and the rates of the market, and keep
+        // the price that matches the most, or the closest to the rates
+        let reference = (curve.ask + curve.bid) / 2.0;
+        let candidates = orders
+            .iter()
+            .filter(|order| order.quantity > 0.0)
+            .map(AuctionOrder::unit_limit)
+            .chain([curve.ask, curve.bid])
+            .filter(|price| price.is_finite() && *price > 0.0);
+        let mut best: Option<Clearing> = None;
+        for price in candidates {
+            let clearing = curve.clear(&orders, price);
+            let better = match &best {
+                None => true,
+                Some(best) => {
+                    clearing.volume() > best.volume()
+                        || (clearing.volume() == best.volume()
+                            && (price - reference).abs() < (best.price - reference).abs())
+                }
+            };
+            if better {
+                best = Some(clearing);
+            }
+        }
+        let clearing = best.filter(|clearing| clearing.volume() > 0.0);
+
+        match &clearing {
+            Some(c) => self.log(format!(
+                "AUCTION_CLEARING-KIND:{kind}-PRICE:{:+e}-BOUGHT:{:+e}-SOLD:{:+e}",
+                c.price, c.bought, c.sold
+            )),
+            None => self.log(format!("AUCTION_CLEARING-KIND:{kind}-NO_TRADE")),
+        }
+        for order in orders {
+            let filled = match &clearing {
+                Some(c) => c.fill(&order),
+                None => 0.0,
+            };
+            let clearing_price = clearing.as_ref().map(|c| c.price);
+            let status = match clearing_price {
+                Some(clearing_price) if filled > 0.0 => {
+                    // Fees come on top of the clearing price
+                    let value = filled * clearing_price;
+                    let side = match order.side {
+                        AuctionSide::Buy => TradeSide::Buy,
+                        AuctionSide::Sell => TradeSide::Sell,
+                    };
+                    let fee = self
+                        .get_maker_fee(&order.trader_name, side, value)
+                        .min(value);
+                    let price = match order.side {
+                        AuctionSide::Buy => value + fee,
+                        AuctionSide::Sell => value - fee,
+                    };
+                    // What the market would have asked (or offered) for it
+                    let market_value = match order.side {
+                        AuctionSide::Buy => filled * curve.ask,
+                        AuctionSide::Sell => filled * curve.bid,
+                    };
+                    let lock = GoodLockMeta::new(
+                        kind,
+                        price,
+                        filled,
+                        today,
+                        market_value,
+                        fee,
+                        order.trader_name.clone(),
+                    )
+                    .with_deposit(order.deposit);
+                    // Rounding in the pro rata fills may ask for more than the stock
+                    let reserved = self.apply_or_log(MarketEvent::GoodReserved {
+                        token: order.token.clone(),
+                        side,
+                        trader_name: order.trader_name.clone(),
+                        good: self.ticker(kind).to_string(),
+                        quantity: filled,
+                        price,
+                    });
+                    if reserved {
+                        match order.side {
+                            AuctionSide::Buy => {
+                                self.meta.insert_buy_lock(order.token.clone(), lock)
+                            }
+                            AuctionSide::Sell => {
+                                self.meta.insert_sell_lock(order.token.clone(), lock)
+                            }
+                        }
+                        self.meta.stats.locks.created += 1;
+                        Some(AuctionStatus::Filled {
+                            day: today,
+                            clearing_price,
+                            quantity: filled,
+                            price,
+                            fee,
+                        })
+                    } else {
+                        None
+                    }
+                }
+                _ => None,
+            };
+            let status = match status {
+                Some(status) => status,
+                None => {
+                    // No lock to leave the deposit on
+                    self.refund_lock_deposit(&order.trader_name, order.deposit);
+                    AuctionStatus::Unfilled {
+                        day: today,
+                        clearing_price,
+                    }
+                }
+            };
+            self.apply_or_log(MarketEvent::AuctionOrderCleared {
+                token: order.token,
+                status,
+            });
+        }
+    }
+}
+
+/// What the market is willing to trade of a good at each price (of a unit,
+/// in DEFAULT_GOOD_KIND), given its rates and stock
+struct InventoryCurve {
+    /// The price of a unit at the buy rate of the market
+    ask: f32,
+    /// The price of a unit at the sell rate of the market
+    bid: f32,
+    stock: f32,
+    cash: f32,
+}
+
+impl InventoryCurve {
+    /// Above the ask, the market sells from its stock until the price of
+    /// the next unit, `ask * stock / (stock - sold)`, reaches `price`
+    fn supply(&self, price: f32) -> f32 {
+        if price > self.ask {
+            self.stock * (1.0 - self.ask / price)
+        } else {
+            0.0
+        }
+    }
+
+    /// Below the bid, the market buys until the price of the next unit,
+    /// `bid * stock / (stock + bought)`, falls to `price`
+    fn demand(&self, price: f32) -> f32 {
+        if price < self.bid {
+            self.stock * (self.bid / price - 1.0)
+        } else {
+            0.0
+        }
+    }
+
+    /// How much the orders can trade at `price`. The market fronts every
+    /// trade, so the traders can not buy more than its stock, nor sell more
+    /// than its cash can pay for.
+    fn clear(&self, orders: &[AuctionOrder], price: f32) -> Clearing {
+        let mut clearing = Clearing {
+            price,
+            demand: 0.0,
+            supply: 0.0,
+            bought: 0.0,
+            sold: 0.0,
+        };
+        for order in orders {
+            if clearing.within_limit(order) {
+                match order.side {
+                    AuctionSide::Buy => clearing.demand += order.quantity,
+                    AuctionSide::Sell => clearing.supply += order.quantity,
+                }
+            }
+        }
+        clearing.bought = clearing
+            .demand
+            .min(clearing.supply + self.supply(price))
+            .min(self.stock);
+        clearing.sold = clearing
+            .supply
+            .min(clearing.demand + self.demand(price))
+            .min(self.cash / price);
+        clearing
+    }
+}
+
+/// The outcome of clearing the orders of a good at a price
+struct Clearing {
+    price: f32,
+    /// Quantity asked by the buy orders within the price
+    demand: f32,
+    /// Quantity offered by the sell orders within the price
+    supply: f32,
+    /// Quantity the traders buy
+    bought: f32,
+    /// Quantity the traders sell
+    sold: f32,
+}
+
+impl Clearing {
+    fn volume(&self) -> f32 {
+        self.bought + self.sold
+    }
+
+    fn within_limit(&self, order: &AuctionOrder) -> bool {
+        if order.quantity <= 0.0 {
+            return false;
+        }
+        match order.side {
+            AuctionSide::Buy => order.unit_limit() >= self.price,
+            AuctionSide::Sell => order.unit_limit() <= self.price,
+        }
+    }
+
+    /// The quantity filled for the order, its share of the volume of its
+    /// side
+    fn fill(&self, order: &AuctionOrder) -> f32 {
+        if !self.within_limit(order) {
+            return 0.0;
+        }
+        match order.side {
+            AuctionSide::Buy => order.quantity * self.bought / self.demand,
+            AuctionSide::Sell => order.quantity * self.sold / self.supply,
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use crate::sol_market::{domain::background_flow::BackgroundTrader, AdjustmentReason, SOLMarket};
+use rand::Rng;
+use unitn_market_2022::good::consts::DEFAULT_GOOD_KIND;
+
+/// This block contains the background flow, enabled by
+/// `BackgroundFlowConfig::enabled`: synthetic noise traders, momentum
+/// traders and liquidity takers that trade against the inventory of the
+/// market every day, at its rates.
+///
+/// Their trades move the stock of the market, and so the `Quantity` price,
+/// and are seen by the `Others` price like the trades of other markets.
+/// They are not notified to anyone and do not count as locks. They trade
+/// every good but DEFAULT_GOOD_KIND, including the ones traders can not see.
+impl SOLMarket {
+    /// Makes the trades of the background traders for the day, if enabled
+    pub(crate) fn background_flow_if_needed(&mut self) {
+        let config = self.meta.config.background_flow.clone();
+        if !config.enabled {
+            return;
+        }
+        let cash = self.ticker(DEFAULT_GOOD_KIND).to_string();
+        let tickers: Vec<String> = self
+            .meta
+            .goods_registry
+            .iter()
+            .map(|spec| spec.ticker.clone())
+            .filter(|ticker| *ticker != cash)
+            .collect();
+        if tickers.is_empty() {
+            return;
+        }
+        let traders = [
+            (BackgroundTrader::Noise, config.noise_trades),
+            (BackgroundTrader::Momentum, config.momentum_trades),
+            (BackgroundTrader::LiquidityTaker, config.liquidity_trades),
+        ];
+        for (trader, trades) in traders {
+            for _ in 0..trades {
+                let rng = &mut self.meta.background_flow.rng;
+                let ticker = &tickers[rng.gen_range(0..tickers.len())];
+                let (buy, value) = match trader {
+                    BackgroundTrader::Noise => (
+                        rng.gen_bool(0.5),
+                        rng.gen_range(0.0..=config.max_trade_value),
+                    ),
+                    BackgroundTrader::Momentum => {
+                        let value = rng.gen_range(0.0..=config.max_trade_value);
+                        (self.price_rose(ticker), value)
+                    }
+                    BackgroundTrader::LiquidityTaker => {
+                        (rng.gen_bool(0.5), config.liquidity_trade_value)
+                    }
+                };
+                self.background_trade(ticker, buy, value);
+            }
+        }
+        self.record_background_rates(&tickers, config.momentum_days);
+    }
+
+    /// Whether the price (in DEFAULT_GOOD_KIND) of the good went up over the
+    /// days the momentum traders look at
+    fn price_rose(&self, ticker: &str) -> bool {
+        let rate = self.buy_rate(ticker);
+        match self.meta.background_flow.rates.get(ticker) {
+            // The rate is goods per DEFAULT_GOOD_KIND: the price rose if it fell
+            Some(rates) => rates.front().map(|oldest| rate < *oldest).unwrap_or(false),
+            None => false,
+        }
+    }
+
+    /// A background trader buys (or sells) up to `value` of the good at the
+    /// rate of the market, as far as the inventory allows
+    fn background_trade(&mut self, ticker: &str, buy: bool, value: f32) {
+        let (rate, quantity, price) = if buy {
+            let rate = self.buy_rate(ticker);
+            let quantity = (value * rate).min(self.stock(ticker));
+            (rate, quantity, quantity / rate)
+        } else {
+            let rate = self.sell_rate(ticker);
+            let price = value.min(self.get_available_quantity(DEFAULT_GOOD_KIND));
+            (rate, price * rate, price)
+        };
+        if quantity <= 0.0 || price <= 0.0 {
+            return;
+        }
+        let (good_change, cash_change) = if buy {
+            (-quantity, price)
+        } else {
+            (quantity, -price)
+        };
+        if !self.adjust_stock(ticker, good_change, AdjustmentReason::BackgroundFlow) {
+            return;
+        }
+        self.add_to_stock(
+            DEFAULT_GOOD_KIND,
+            cash_change,
+            AdjustmentReason::BackgroundFlow,
+        );
+        //Only the goods traders can see are traded in the other markets
+        if let Some(kind) = self.meta.goods_registry.kind_of(ticker) {
+            self.meta.other_markets.update(&kind, rate);
+        }
+        self.meta.stats.background_trades += 1;
+        self.meta.stats.background_traded_value += price;
+    }
+
+    /// Remembers the rates of the day for the momentum traders
+    fn record_background_rates(&mut self, tickers: &[String], momentum_days: u32) {
+        for ticker in tickers {
+            let rate = self.buy_rate(ticker);
+            let rates = self
+                .meta
+                .background_flow
+                .rates
+                .entry(ticker.clone())
+                .or_default();
+            rates.push_back(rate);
+            while rates.len() > momentum_days as usize {
+                rates.pop_front();
+            }
+        }
+    }
+}
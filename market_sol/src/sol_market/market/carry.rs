@@ -0,0 +1,43 @@
+use crate::sol_market::{AdjustmentReason, SOLMarket};
+use unitn_market_2022::good::good_kind::GoodKind;
+
+/// This block contains the carry model: every good has a daily interest rate
+/// (see `GoodSpec::daily_interest_rate`), which makes the stock of the
+/// market grow (or decay, if negative) day after day.
+///
+/// Locked goods are not in the stock, so they accrue carry on the side,
+/// which goes to the market when the lock is settled.
+impl SOLMarket {
+    /// Applies one day of interest to the stock of every good
+    pub(crate) fn apply_daily_carry(&mut self) {
+        let rates: Vec<(String, f32)> = self
+            .meta
+            .goods_registry
+            .iter()
+            .filter(|spec| spec.daily_interest_rate != 0.0)
+            .map(|spec| (spec.ticker.clone(), spec.daily_interest_rate))
+            .collect();
+        for (ticker, rate) in rates {
+            let accrued = self.stock(&ticker) * rate;
+            self.adjust_stock(&ticker, accrued, AdjustmentReason::Carry);
+        }
+    }
+
+    /// Returns the carry accrued by `quantity` of a good locked on
+    /// `created_on`, up to today
+    pub(crate) fn carry_on_lock(&self, kind: GoodKind, quantity: f32, created_on: u32) -> f32 {
+        let days = self.meta.current_day.saturating_sub(created_on);
+        quantity
+            * (self
+                .meta
+                .goods_registry
+                .carry_factor(self.ticker(kind), days)
+                - 1.0)
+    }
+
+    /// Adds the carry accrued by a lock to the stock of the market
+    pub(crate) fn settle_carry(&mut self, kind: GoodKind, quantity: f32, created_on: u32) {
+        let carry = self.carry_on_lock(kind, quantity, created_on);
+        self.add_to_stock(kind, carry, AdjustmentReason::Carry);
+    }
+}
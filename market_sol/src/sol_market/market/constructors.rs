@@ -1,6 +1,11 @@
+use crate::sol_market::domain::event_log::EventLog;
+use crate::sol_market::domain::instance::Logger;
 use crate::sol_market::domain::market_meta::MarketMeta;
 use crate::sol_market::domain::strategy_name::StrategyName;
-use crate::sol_market::{log, SOLMarket, ALL_GOOD_KINDS};
+use crate::sol_market::{
+    GoodDefinition, GoodSpec, GoodsRegistry, MarketDefinition, MarketState, SOLMarket,
+    SOLMarketError, WeightsDefinition, ALL_GOOD_KINDS,
+};
 use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha20Rng;
 use std::cell::RefCell;
@@ -8,7 +13,6 @@ use std::collections::HashMap;
 use std::path::Path;
 use std::rc::Rc;
 use unitn_market_2022::good::consts::STARTING_CAPITAL;
-use unitn_market_2022::good::good::Good;
 use unitn_market_2022::good::good_kind::GoodKind;
 
 impl SOLMarket {
@@ -56,48 +60,51 @@ impl SOLMarket {
         )
     }
 
-    /// Need a constructor that has the SOLMarket type in its signature for our internal tests
-    pub(crate) fn new_file_internal(path_str: &str) -> Rc<RefCell<SOLMarket>> {
+    /// Same as `new_with_quantities`, but returns an error instead of
+    /// panicking if any quantity is negative
+    pub fn try_new_with_quantities(
+        eur: f32,
+        yen: f32,
+        usd: f32,
+        yuan: f32,
+    ) -> Result<Rc<RefCell<SOLMarket>>, SOLMarketError> {
+        Self::try_new_with_quantities_and_path(eur, yen, usd, yuan, None, HashMap::new())
+    }
+
+    /// Same as `new_file`, but returns an error if the file exists and can
+    /// not be read or parsed, instead of falling back to a random market.
+    ///
+    /// If the file does not exist, the market is initialized randomly and
+    /// will be saved there.
+    pub fn try_new_file(path_str: &str) -> Result<Rc<RefCell<SOLMarket>>, SOLMarketError> {
         let path: &Path = Path::new(path_str);
         let path_exists = std::path::Path::exists(path);
         if path_exists {
-            let quantities = Self::read_quantities_from_file(path);
-            return match quantities {
-                Some(goods) => {
-                    let eur = goods
-                        .iter()
-                        .find(|g| g.get_kind() == GoodKind::EUR)
-                        .unwrap()
-                        .get_qty();
-                    let usd = goods
-                        .iter()
-                        .find(|g| g.get_kind() == GoodKind::USD)
-                        .unwrap()
-                        .get_qty();
-                    let yen = goods
-                        .iter()
-                        .find(|g| g.get_kind() == GoodKind::YEN)
-                        .unwrap()
-                        .get_qty();
-                    let yuan = goods
-                        .iter()
-                        .find(|g| g.get_kind() == GoodKind::YUAN)
-                        .unwrap()
-                        .get_qty();
-                    let weights = Self::read_weights_from_file(path);
-                    return Self::new_with_quantities_and_path(
-                        eur,
-                        yen,
-                        usd,
-                        yuan,
-                        Some(path_str),
-                        weights,
-                    );
-                }
-                None => Self::new_random_path(Some(path_str)),
-            };
+            let definition = Self::read_file(path)?;
+            Self::try_new_from_definition_and_path(&definition, Some(path_str)).map_err(|e| match e
+            {
+                SOLMarketError::MissingGood { good_kind, .. } => SOLMarketError::MissingGood {
+                    path: Some(path.to_path_buf()),
+                    good_kind,
+                },
+                e => e,
+            })
         } else {
-            Self::new_random_path(Some(path_str))
+            Ok(Self::new_random_path(Some(path_str)))
+        }
+    }
+
+    /// Need a constructor that has the SOLMarket type in its signature for our internal tests
+    pub(crate) fn new_file_internal(path_str: &str) -> Rc<RefCell<SOLMarket>> {
+        match Self::try_new_file(path_str) {
+            Ok(market) => market,
+            Err(e) => {
+                Logger::default().log(
+                    chrono::Local::now(),
+                    format!("LOAD_FILE-PATH:{path_str}-ERROR:{e}"),
+                );
+                Self::new_random_path(Some(path_str))
+            }
         }
     }
 
@@ -109,39 +116,183 @@ impl SOLMarket {
         optional_path: Option<&str>,
         weights: HashMap<StrategyName, f32>,
     ) -> Rc<RefCell<SOLMarket>> {
-        if eur < 0.0 {
-            panic!("Tried to initialize the market with a negative quantity of eur");
-        }
-        if usd < 0.0 {
-            panic!("Tried to initialize the market with a negative quantity of usd");
-        }
-        if yen < 0.0 {
-            panic!("Tried to initialize the market with a negative quantity of yen");
-        }
-        if yuan < 0.0 {
-            panic!("Tried to initialize the market with a negative quantity of yuan");
+        Self::try_new_with_quantities_and_path(eur, yen, usd, yuan, optional_path, weights)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    pub(crate) fn try_new_with_quantities_and_path(
+        eur: f32,
+        yen: f32,
+        usd: f32,
+        yuan: f32,
+        optional_path: Option<&str>,
+        weights: HashMap<StrategyName, f32>,
+    ) -> Result<Rc<RefCell<SOLMarket>>, SOLMarketError> {
+        let goods = [
+            (GoodKind::EUR, eur),
+            (GoodKind::YEN, yen),
+            (GoodKind::USD, usd),
+            (GoodKind::YUAN, yuan),
+        ]
+        .into_iter()
+        .map(|(kind, quantity)| GoodDefinition::new(kind, quantity))
+        .collect();
+        let definition = MarketDefinition {
+            goods,
+            weights: WeightsDefinition::from_map(&weights),
+            ..Default::default()
+        };
+        Self::try_new_from_definition_and_path(&definition, optional_path)
+    }
+
+    /// Creates a market from a definition, e.g., one built in code or read
+    /// with [`MarketDefinition::from_file`]. The market is not bound to any
+    /// file.
+    pub fn try_new_from_definition(
+        definition: &MarketDefinition,
+    ) -> Result<Rc<RefCell<SOLMarket>>, SOLMarketError> {
+        Self::try_new_from_definition_and_path(definition, None)
+    }
+
+    pub(crate) fn try_new_from_definition_and_path(
+        definition: &MarketDefinition,
+        optional_path: Option<&str>,
+    ) -> Result<Rc<RefCell<SOLMarket>>, SOLMarketError> {
+        let name = &definition.config.name;
+        if name.is_empty() || name.contains(['|', '\n']) {
+            return Err(SOLMarketError::InvalidName { name: name.clone() });
         }
+        definition.config.weight_tuning.validate()?;
+        definition.config.background_flow.validate()?;
+
         //Initialize the market
+        let mut goods_registry = GoodsRegistry::new();
         let mut goods = HashMap::new();
-        //Use a for-match to ensure we always do all of them
-        for gk in ALL_GOOD_KINDS {
-            match gk {
-                GoodKind::EUR => goods.insert(gk, Good::new(gk, eur)),
-                GoodKind::YEN => goods.insert(gk, Good::new(gk, yen)),
-                GoodKind::USD => goods.insert(gk, Good::new(gk, usd)),
-                GoodKind::YUAN => goods.insert(gk, Good::new(gk, yuan)),
+        let tickers = definition
+            .goods
+            .iter()
+            .enumerate()
+            .map(|(index, good)| good.ticker().ok_or(SOLMarketError::UnnamedGood { index }))
+            .collect::<Result<Vec<String>, SOLMarketError>>()?;
+        for (i, good_definition) in definition.goods.iter().enumerate() {
+            let ticker = &tickers[i];
+            //A good declared twice takes its last declaration
+            if tickers[i + 1..].contains(ticker) {
+                continue;
+            }
+            let quantity = good_definition.quantity;
+            if quantity < 0.0 {
+                return Err(SOLMarketError::NegativeQuantity {
+                    ticker: ticker.clone(),
+                    quantity,
+                });
+            }
+            let mut spec = match good_definition.kind {
+                Some(kind) => GoodSpec::new(kind),
+                None => GoodSpec::instrument(ticker),
             };
+            spec.ticker = ticker.clone();
+            spec.initial_stock = quantity;
+            if let Some(default_rate) = good_definition.default_rate {
+                spec.default_rate = default_rate;
+            }
+            if let Some(volatility) = good_definition.volatility {
+                spec.volatility = volatility;
+            }
+            if let Some(daily_interest_rate) = good_definition.daily_interest_rate {
+                spec.daily_interest_rate = daily_interest_rate;
+            }
+            for (parameter, value) in [
+                ("default_rate", spec.default_rate),
+                ("volatility", spec.volatility),
+            ] {
+                if !(value.is_finite() && value > 0.0) {
+                    return Err(SOLMarketError::InvalidGoodParameter {
+                        ticker: ticker.clone(),
+                        parameter,
+                        value,
+                    });
+                }
+            }
+            //A rate of -100% or less would wipe out the good in a day
+            let rate = spec.daily_interest_rate;
+            if !(rate.is_finite() && rate > -1.0) {
+                return Err(SOLMarketError::InvalidGoodParameter {
+                    ticker: ticker.clone(),
+                    parameter: "daily_interest_rate",
+                    value: rate,
+                });
+            }
+            goods_registry.try_register(spec)?;
+            goods.insert(ticker.clone(), quantity);
+        }
+        //Traders can ask the market for any good of the protocol
+        for good_kind in ALL_GOOD_KINDS {
+            if !goods_registry.traded().any(|(kind, _)| kind == good_kind) {
+                return Err(SOLMarketError::MissingGood {
+                    path: None,
+                    good_kind,
+                });
+            }
+        }
+
+        let mut initialization = String::from("MARKET_INITIALIZATION\n");
+        for spec in goods_registry.iter() {
+            initialization += &format!("{}: {:+e}\n", spec.ticker, spec.initial_stock);
         }
+        initialization += "END_MARKET_INITIALIZATION";
 
-        log(format!("MARKET_INITIALIZATION\nEUR: {eur:+e}\nUSD: {usd:+e}\nYEN: {yen:+e}\nYUAN: {yuan:+e}\nEND_MARKET_INITIALIZATION"));
+        let internal_needs = SOLMarket::set_internal_needs(&goods, &goods_registry);
 
-        let goods_vec = Vec::from_iter(goods.values().cloned());
+        let mut meta = MarketMeta::new(
+            goods_registry,
+            optional_path,
+            definition.weights.to_map(),
+            definition.config.clone(),
+            definition.seed,
+        );
+        meta.logger.log(meta.clock.now(), initialization);
+        if let Some(current_day) = definition.current_day {
+            meta.current_day = current_day;
+        }
+        let tickers = meta
+            .goods_registry
+            .traded()
+            .map(|(kind, spec)| (kind, spec.ticker.clone()))
+            .collect();
+        meta.event_log = EventLog::new(MarketState {
+            day: meta.current_day,
+            tickers,
+            stock: goods,
+            forward_contracts: definition
+                .forward_contracts
+                .iter()
+                .map(|contract| (contract.id.clone(), contract.clone()))
+                .collect(),
+            option_contracts: definition
+                .option_contracts
+                .iter()
+                .map(|contract| (contract.id.clone(), contract.clone()))
+                .collect(),
+            margin_accounts: definition
+                .margin_accounts
+                .iter()
+                .map(|account| (account.trader_name.clone(), account.clone()))
+                .collect(),
+            ..Default::default()
+        })
+        .with_journal(definition.config.event_log.journal_file.as_deref());
 
-        Rc::new(RefCell::new(SOLMarket {
-            goods,
+        Ok(Rc::new(RefCell::new(SOLMarket {
             subscribers: vec![],
-            meta: MarketMeta::new(goods_vec.clone(), optional_path, weights),
-            internal_needs: SOLMarket::set_internal_needs(goods_vec),
-        }))
+            event_listeners: vec![],
+            meta,
+            internal_needs,
+        })))
+    }
+
+    /// Returns the goods of this market, along with their parameters
+    pub fn goods_registry(&self) -> &GoodsRegistry {
+        &self.meta.goods_registry
     }
 }
@@ -2,6 +2,12 @@ use crate::sol_market::SOLMarket;
 
 impl Drop for SOLMarket {
     fn drop(&mut self) {
-        self.write_to_file();
+        if self.meta.file_path.is_none() {
+            return;
+        }
+        if let Err(e) = self.save_snapshot() {
+            self.log(format!("WRITE_FILE-ERROR:{e}"));
+        }
+        self.write_report_if_needed();
     }
 }
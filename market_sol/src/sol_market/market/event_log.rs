@@ -0,0 +1,109 @@
+use crate::sol_market::{
+    domain::event_log::EventLog, AdjustmentReason, EventError, MarketEvent, MarketState, SOLMarket,
+    SOLMarketError,
+};
+use std::path::Path;
+use unitn_market_2022::good::good_kind::GoodKind;
+
+/// This block contains the event log of the market: every change to its
+/// stock, its locks, its contracts, margin accounts, auction orders, lock
+/// deposits and its day is a `MarketEvent`, applied by `apply`. All of them
+/// are read from the fold, and an event that would take a good below zero,
+/// or is about a contract, account or order that does not exist, is refused.
+///
+/// The log keeps a snapshot of the state every
+/// `EventLogConfig::snapshot_every` events, and the events after the oldest
+/// snapshot kept, so any state since then can be replayed. The log and its
+/// snapshots are in memory, and start when the market is created. With
+/// `EventLogConfig::journal_file`, the state the market starts from and all
+/// of its events are also appended to a file, which `replay_journal` folds
+/// back into any of the states since.
+impl SOLMarket {
+    /// The events kept in the log, with their numbers, from the oldest
+    pub fn events(&self) -> impl Iterator<Item = (u64, &MarketEvent)> {
+        self.meta.event_log.events()
+    }
+
+    /// The number of the last event, 0 if nothing happened yet
+    pub fn last_event(&self) -> u64 {
+        self.meta.event_log.last_seq()
+    }
+
+    /// The state of the market, as the fold of its events
+    pub fn event_state(&self) -> &MarketState {
+        self.meta.event_log.state()
+    }
+
+    /// The state of the market right after the event `seq`, replayed from
+    /// the closest snapshot. `None` if the event is older than the oldest
+    /// snapshot kept, or did not happen yet.
+    pub fn event_state_at(&self, seq: u64) -> Option<MarketState> {
+        self.meta.event_log.state_at(seq)
+    }
+
+    /// The state right after the event `seq` of the journal at `path`, or
+    /// after its last event if `None`. Only the events since the market was
+    /// last created, or restored from its state file, are replayed.
+    pub fn replay_journal(path: &str, seq: Option<u64>) -> Result<MarketState, SOLMarketError> {
+        EventLog::replay(Path::new(path), seq)
+    }
+
+    /// Changes the state as the event says, and logs it. An event that
+    /// would take the stock of a good below zero is refused, and nothing
+    /// changes.
+    pub(crate) fn apply(&mut self, event: MarketEvent) -> Result<(), EventError> {
+        let new_day = match event {
+            MarketEvent::DayAdvanced { day } => Some(day),
+            _ => None,
+        };
+        self.meta
+            .event_log
+            .record(event, &self.meta.config.event_log)?;
+        if let Some(day) = new_day {
+            self.meta.current_day = day;
+        }
+        Ok(())
+    }
+
+    /// Same as `apply`, for the changes the market makes on its own, e.g.,
+    /// when a day goes by: there is nobody to tell, so a refused event is
+    /// logged. Returns whether the event was applied.
+    pub(crate) fn apply_or_log(&mut self, event: MarketEvent) -> bool {
+        match self.apply(event) {
+            Ok(()) => true,
+            Err(e) => {
+                self.log(format!("EVENT-REJECTED:{e}"));
+                false
+            }
+        }
+    }
+
+    /// Adds (or removes, if negative) some quantity of a good to the stock,
+    /// see `apply_or_log`
+    pub(crate) fn add_to_stock(
+        &mut self,
+        kind: GoodKind,
+        quantity: f32,
+        reason: AdjustmentReason,
+    ) -> bool {
+        let ticker = self.ticker(kind).to_string();
+        self.adjust_stock(&ticker, quantity, reason)
+    }
+
+    /// Same as `add_to_stock`, for the good with the given ticker
+    pub(crate) fn adjust_stock(
+        &mut self,
+        ticker: &str,
+        quantity: f32,
+        reason: AdjustmentReason,
+    ) -> bool {
+        if quantity == 0.0 {
+            return true;
+        }
+        self.apply_or_log(MarketEvent::StockAdjusted {
+            good: ticker.to_string(),
+            quantity,
+            reason,
+        })
+    }
+}
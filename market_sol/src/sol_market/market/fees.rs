@@ -0,0 +1,84 @@
+use crate::sol_market::{SOLMarket, TradeSide, TraderFees};
+
+/// This block contains the fee engine, configured by `MarketConfig::fees`.
+///
+/// A fee is a percentage of the value of the trade plus a fixed part, which
+/// differ for buys and sells, less the discount of the volume tier of the
+/// trader. Auction orders filled at a clearing are the makers of the market,
+/// and can have a fee of their own. The price a lock agrees on includes the fee, and the fee is
+/// counted when the lock is redeemed. The rates of the market, and so the
+/// quote ladders, do not include fees: `Quote` reports them apart.
+impl SOLMarket {
+    /// Returns the fee, in DEFAULT_GOOD_KIND, of a trade worth `value` at the
+    /// rate of the market. Without a trader, the fee has no discount.
+    pub fn get_fee(&self, trader_name: Option<&str>, side: TradeSide, value: f32) -> f32 {
+        let discount = match trader_name {
+            Some(trader_name) => self.trader_fees(trader_name).discount,
+            None => 0.0,
+        };
+        self.meta.config.fees.fee(side, value, discount)
+    }
+
+    /// Returns the fee, in DEFAULT_GOOD_KIND, of an auction order of a trader
+    /// worth `value` at the clearing price, see `FeeSchedule::maker`
+    pub fn get_maker_fee(&self, trader_name: &str, side: TradeSide, value: f32) -> f32 {
+        let discount = self.trader_fees(trader_name).discount;
+        self.meta.config.fees.maker_fee(side, value, discount)
+    }
+
+    /// Returns the volume, tier discount and fees paid of a trader
+    pub fn trader_fees(&self, trader_name: &str) -> TraderFees {
+        let ledger = match self.meta.fee_ledger.traders.get(trader_name) {
+            Some(ledger) => ledger,
+            None => return TraderFees::default(),
+        };
+        let schedule = &self.meta.config.fees;
+        let oldest_day = self.meta.current_day.saturating_sub(schedule.window_days);
+        let volume = ledger
+            .trades
+            .iter()
+            .filter(|(day, _)| *day > oldest_day)
+            .map(|(_, value)| value)
+            .sum();
+        TraderFees {
+            volume,
+            discount: schedule.discount(volume),
+            buy_fees: ledger.buy_fees,
+            sell_fees: ledger.sell_fees,
+        }
+    }
+
+    /// Records a redeemed lock worth `value` (fee excluded), on which the
+    /// trader paid `fee`
+    pub(crate) fn record_fee(&mut self, trader_name: &str, side: TradeSide, value: f32, fee: f32) {
+        let today = self.meta.current_day;
+        let oldest_day = today.saturating_sub(self.meta.config.fees.window_days);
+        let ledger = self
+            .meta
+            .fee_ledger
+            .traders
+            .entry(trader_name.to_string())
+            .or_default();
+        ledger.trades.push_back((today, value));
+        while matches!(ledger.trades.front(), Some((day, _)) if *day <= oldest_day) {
+            ledger.trades.pop_front();
+        }
+        if fee == 0.0 {
+            return;
+        }
+        let (code, totals) = match side {
+            TradeSide::Buy => {
+                ledger.buy_fees += fee;
+                ("BUY", &mut self.meta.stats.fees.buy)
+            }
+            TradeSide::Sell => {
+                ledger.sell_fees += fee;
+                ("SELL", &mut self.meta.stats.fees.sell)
+            }
+        };
+        *totals += fee;
+        self.log(format!(
+            "FEE-{trader_name}-{code}-VALUE:{value:+e}-FEE:{fee:+e}"
+        ));
+    }
+}
@@ -0,0 +1,307 @@
+use crate::sol_market::{
+    ForwardContract, ForwardError, ForwardPayout, ForwardRequest, ForwardSettlement, ForwardSide,
+    ForwardStatus, MarketEvent, SOLMarket,
+};
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+use unitn_market_2022::good::{consts::DEFAULT_GOOD_KIND, good::Good, good_kind::GoodKind};
+
+/// This block contains the forward contracts: a trader and the market agree
+/// today on the price of a good delivered on a future market day.
+///
+/// Since the market can not reach traders, everything is deposited when the
+/// contract is made: the trader hands over the collateral and the market
+/// sets aside its side of the deal. On the maturity day, `on_event` settles
+/// the contract and the trader can `collect_forward` the payout.
+impl SOLMarket {
+    /// Enters a forward contract and returns its id.
+    ///
+    /// The price must be at least `get_forward_buy_price` when the trader
+    /// buys and at most `get_forward_sell_price` when the trader sells.
+    /// The collateral is taken from `collateral`:
+    /// - physical settlement: the whole price (in DEFAULT_GOOD_KIND) when
+    ///   buying, the whole quantity of the good when selling
+    /// - cash settlement: `MarketConfig::forward_margin_ratio` of the price,
+    ///   in DEFAULT_GOOD_KIND
+    pub fn enter_forward(
+        &mut self,
+        request: ForwardRequest,
+        collateral: &mut Good,
+    ) -> Result<String, ForwardError> {
+        let log_error = format!(
+            "ENTER_FORWARD-{}-KIND:{}-QUANTITY:{:+e}-PRICE:{:+e}-MATURITY:{}-ERROR",
+            request.trader_name,
+            request.good_kind,
+            request.quantity,
+            request.price,
+            request.maturity_day
+        );
+        let result = self.check_forward(&request, collateral);
+        let (collateral_kind, collateral_quantity, reserved_kind, reserved) = match result {
+            Ok(amounts) => amounts,
+            Err(e) => {
+                self.log(log_error);
+                return Err(e);
+            }
+        };
+
+        let id = self.new_contract_id(
+            'F',
+            (
+                request.side,
+                request.good_kind,
+                request.quantity.to_string(),
+                request.price.to_string(),
+                &request.trader_name,
+            ),
+        );
+
+        let contract = ForwardContract {
+            id: id.clone(),
+            trader_name: request.trader_name,
+            side: request.side,
+            good_kind: request.good_kind,
+            quantity: request.quantity,
+            price: request.price,
+            settlement: request.settlement,
+            created_on: self.meta.current_day,
+            maturity_day: request.maturity_day,
+            collateral_kind,
+            collateral: collateral_quantity,
+            reserved_kind,
+            reserved,
+            status: ForwardStatus::Open,
+            payout: None,
+        };
+
+        // Set aside the side of the market and take the collateral
+        self.apply(MarketEvent::ForwardEntered { contract })
+            .expect("The quantity available was checked");
+        collateral.split(collateral_quantity).unwrap();
+        self.log(format!("ENTER_FORWARD-ID:{id}-OK"));
+        Ok(id)
+    }
+
+    /// Hands over the payout of a settled contract
+    pub fn collect_forward(&mut self, id: &str) -> Result<Good, ForwardError> {
+        let contract = match self.event_state().forward_contracts.get(id) {
+            Some(contract) => contract,
+            None => {
+                self.log(format!("COLLECT_FORWARD-ID:{id}-ERROR"));
+                return Err(ForwardError::UnrecognizedContract { id: id.to_string() });
+            }
+        };
+        let payout = match (contract.status, contract.payout) {
+            (ForwardStatus::Settled, Some(payout)) => payout,
+            (status, _) => {
+                self.log(format!("COLLECT_FORWARD-ID:{id}-ERROR"));
+                return Err(ForwardError::NothingToCollect {
+                    id: id.to_string(),
+                    status,
+                });
+            }
+        };
+        //Nothing else to do with the contract
+        self.apply(MarketEvent::ForwardCollected { id: id.to_string() })
+            .expect("The contract was checked");
+        self.log(format!("COLLECT_FORWARD-ID:{id}-OK"));
+        Ok(Good::new(payout.kind, payout.quantity))
+    }
+
+    /// Returns the contract with the given id, unless it was collected
+    pub fn forward_contract(&self, id: &str) -> Option<&ForwardContract> {
+        self.event_state().forward_contracts.get(id)
+    }
+
+    /// Returns the contracts of a trader that were not collected yet, from
+    /// the oldest
+    pub fn forward_contracts_of(&self, trader_name: &str) -> Vec<&ForwardContract> {
+        let mut contracts: Vec<&ForwardContract> = self
+            .event_state()
+            .forward_contracts
+            .values()
+            .filter(|contract| contract.trader_name == trader_name)
+            .collect();
+        contracts.sort_by(|a, b| (a.created_on, &a.id).cmp(&(b.created_on, &b.id)));
+        contracts
+    }
+
+    /// Every contract that was not collected yet, from the oldest
+    pub(crate) fn forward_contracts_by_age(&self) -> Vec<ForwardContract> {
+        let mut contracts: Vec<ForwardContract> = self
+            .event_state()
+            .forward_contracts
+            .values()
+            .cloned()
+            .collect();
+        contracts.sort_by(|a, b| (a.created_on, &a.id).cmp(&(b.created_on, &b.id)));
+        contracts
+    }
+
+    /// A new id for a forward or an option: the hash of its terms, of the
+    /// time and of the contracts made before it, never one already in use
+    pub(crate) fn new_contract_id(&mut self, prefix: char, terms: impl Hash) -> String {
+        loop {
+            let mut hasher = DefaultHasher::new();
+            (&terms, self.meta.clock.now(), self.meta.contracts_created).hash(&mut hasher);
+            self.meta.contracts_created += 1;
+            let id = format!("{prefix}{}", hasher.finish());
+            let state = self.event_state();
+            let in_use = state.forward_contracts.contains_key(&id)
+                || state.option_contracts.contains_key(&id);
+            if !in_use {
+                return id;
+            }
+        }
+    }
+
+    /// Settles the contracts that reach maturity today. Called by `on_event`
+    /// once the day has advanced.
+    pub(crate) fn settle_forwards(&mut self) {
+        let today = self.meta.current_day;
+        let mut due: Vec<String> = self
+            .event_state()
+            .forward_contracts
+            .values()
+            .filter(|c| c.status == ForwardStatus::Open && c.maturity_day <= today)
+            .map(|c| c.id.clone())
+            .collect();
+        due.sort();
+        for id in due {
+            let contract = &self.event_state().forward_contracts[&id];
+            let payout = match contract.settlement {
+                // The market keeps the collateral and gives what it set aside
+                ForwardSettlement::Physical => ForwardPayout {
+                    kind: contract.reserved_kind,
+                    quantity: contract.reserved,
+                },
+                ForwardSettlement::Cash => {
+                    let profit = self.forward_profit(contract);
+                    // Losses and gains are capped by the two margins
+                    let quantity = (contract.collateral + profit)
+                        .clamp(0.0, contract.collateral + contract.reserved);
+                    ForwardPayout {
+                        kind: DEFAULT_GOOD_KIND,
+                        quantity,
+                    }
+                }
+            };
+            let settlement = MarketEvent::ForwardSettled {
+                id: id.clone(),
+                payout,
+            };
+            if self.apply_or_log(settlement) {
+                self.log(format!(
+                    "SETTLE_FORWARD-ID:{id}-PAYOUT_KIND:{}-PAYOUT:{:+e}",
+                    payout.kind, payout.quantity
+                ));
+            }
+        }
+    }
+
+    /// Validates a request and returns what the trader deposits and what the
+    /// market sets aside, as (collateral kind, collateral, reserved kind,
+    /// reserved)
+    fn check_forward(
+        &self,
+        request: &ForwardRequest,
+        collateral: &Good,
+    ) -> Result<(GoodKind, f32, GoodKind, f32), ForwardError> {
+        if request.quantity <= 0.0 {
+            return Err(ForwardError::NonPositiveQuantity {
+                quantity: request.quantity,
+            });
+        }
+        let current_day = self.meta.current_day;
+        if request.maturity_day <= current_day {
+            return Err(ForwardError::MaturityNotInTheFuture {
+                maturity_day: request.maturity_day,
+                current_day,
+            });
+        }
+        let days_ahead = request.maturity_day - current_day;
+
+        // Check the price
+        let (acceptable_price, price_is_ok) = match request.side {
+            ForwardSide::Buy => {
+                let forward_price = self
+                    .get_forward_buy_price(request.good_kind, request.quantity, days_ahead)
+                    .map_err(ForwardError::PriceUnavailable)?;
+                (forward_price, request.price >= forward_price)
+            }
+            ForwardSide::Sell => {
+                let forward_price = self
+                    .get_forward_sell_price(request.good_kind, request.quantity, days_ahead)
+                    .map_err(ForwardError::PriceUnavailable)?;
+                (forward_price, request.price <= forward_price)
+            }
+        };
+        if !price_is_ok {
+            return Err(ForwardError::UnacceptablePrice {
+                price: request.price,
+                acceptable_price,
+            });
+        }
+
+        let amounts = match (request.settlement, request.side) {
+            (ForwardSettlement::Physical, ForwardSide::Buy) => (
+                DEFAULT_GOOD_KIND,
+                request.price,
+                request.good_kind,
+                request.quantity,
+            ),
+            (ForwardSettlement::Physical, ForwardSide::Sell) => (
+                request.good_kind,
+                request.quantity,
+                DEFAULT_GOOD_KIND,
+                request.price,
+            ),
+            (ForwardSettlement::Cash, _) => {
+                let margin = request.price * self.meta.config.forward_margin_ratio;
+                (DEFAULT_GOOD_KIND, margin, DEFAULT_GOOD_KIND, margin)
+            }
+        };
+        let (collateral_kind, collateral_quantity, reserved_kind, reserved) = amounts;
+
+        if collateral.get_kind() != collateral_kind {
+            return Err(ForwardError::WrongCollateralKind {
+                expected: collateral_kind,
+                provided: collateral.get_kind(),
+            });
+        }
+        if collateral.get_qty() < collateral_quantity {
+            return Err(ForwardError::InsufficientCollateral {
+                required: collateral_quantity,
+                provided: collateral.get_qty(),
+            });
+        }
+        let available = self.get_available_quantity(reserved_kind);
+        if available < reserved {
+            return Err(ForwardError::InsufficientMarketQuantity {
+                kind: reserved_kind,
+                required: reserved,
+                available,
+            });
+        }
+        Ok(amounts)
+    }
+
+    /// What the trader gains (or loses, if negative) on a cash-settled
+    /// contract, at today's prices
+    fn forward_profit(&self, contract: &ForwardContract) -> f32 {
+        match contract.side {
+            // The trader could sell the good to the market today
+            ForwardSide::Buy => {
+                let rate = self.get_good_sell_exchange_rate(contract.good_kind);
+                contract.quantity / rate - contract.price
+            }
+            // The trader would need to buy the good from the market today
+            ForwardSide::Sell => {
+                let rate = self.get_good_buy_exchange_rate(contract.good_kind);
+                contract.price - contract.quantity / rate
+            }
+        }
+    }
+}
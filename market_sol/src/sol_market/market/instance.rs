@@ -0,0 +1,51 @@
+use crate::sol_market::{Clock, EventListener, LogSink, SOLMarket};
+
+/// This block contains the identity of a market, so that several of them can
+/// run side by side in one process: `MarketConfig::name` is what `get_name`
+/// returns and what the log lines start with, and every market has its own
+/// log sink and instance id.
+///
+/// It also holds the clock of the market (see `Clock`), which timestamps the
+/// tokens and the log, and decides when a day goes by.
+impl SOLMarket {
+    /// Returns the id of the market, unique in the process. Unlike the name,
+    /// it is not saved in the state file.
+    pub fn instance_id(&self) -> u64 {
+        self.meta.instance_id
+    }
+
+    /// Makes the market write its log to the given sink, instead of the
+    /// file in its configuration
+    pub fn set_log_sink(&mut self, sink: LogSink) {
+        self.meta.logger.sink = sink;
+    }
+
+    /// Makes the market take the time from the given clock, instead of the
+    /// one in its configuration. The current day does not change.
+    pub fn set_clock(&mut self, clock: Box<dyn Clock>) {
+        self.meta.clock = clock;
+    }
+
+    /// Moves the market forward by the days its clock says went by, without
+    /// an event. With a `RealTimeClock`, call it every now and then to keep
+    /// the market going while nobody trades; returns the days advanced.
+    pub fn sync_clock(&mut self) -> u32 {
+        let days = self.meta.clock.elapsed_days(false);
+        for _ in 0..days {
+            self.advance_day();
+        }
+        days
+    }
+
+    /// Calls the listener with every event of the market, tagged with the
+    /// id and name of the market. Unlike subscribers, listeners do not have
+    /// to be markets.
+    pub fn add_event_listener(&mut self, listener: EventListener) {
+        self.event_listeners.push(listener);
+    }
+
+    /// Append log code to the log of the market according to specifications
+    pub(crate) fn log(&self, log_code: String) {
+        self.meta.logger.log(self.meta.clock.now(), log_code)
+    }
+}
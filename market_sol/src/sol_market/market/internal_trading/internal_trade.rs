@@ -1,7 +1,6 @@
 use super::trade_role::TradeRole;
-use crate::sol_market::{get_value_good, SOLMarket};
+use crate::sol_market::{GoodsRegistry, MarketEvent, SOLMarket};
 use std::collections::HashMap;
-use unitn_market_2022::good::{good::Good, good_kind::GoodKind};
 
 impl SOLMarket {
     /// Perform an internal trade if needed
@@ -11,15 +10,15 @@ impl SOLMarket {
         // Find good that need a refill and the one capable of refilling
         let mut max_need = 0f32;
         let mut max_ability = 0f32;
-        let mut kind_need_refill: Option<GoodKind> = None;
-        let mut kind_able_refill: Option<GoodKind> = None;
-        for (kind, role) in self.internal_needs.iter() {
+        let mut ticker_need_refill: Option<&str> = None;
+        let mut ticker_able_refill: Option<&str> = None;
+        for (ticker, role) in self.internal_needs.iter() {
             match role {
                 TradeRole::Importer { need } => {
                     let n = *need;
                     if n > max_need {
                         max_need = n;
-                        kind_need_refill = Some(*kind);
+                        ticker_need_refill = Some(ticker);
                     }
                 }
                 TradeRole::Exporter { need } => {
@@ -29,54 +28,59 @@ impl SOLMarket {
                         -need
                     };
                     // Market ability in case the good is locked and need has not been updated yet
-                    let market_ability =
-                        get_value_good(kind, self.goods.get(kind).unwrap().get_qty());
+                    let market_ability = self
+                        .meta
+                        .goods_registry
+                        .value_in_default_good(ticker, self.stock(ticker));
                     if ability > max_ability && market_ability > max_ability {
                         max_ability = market_ability.min(ability);
-                        kind_able_refill = Some(*kind);
+                        ticker_able_refill = Some(ticker);
                     }
                 }
             }
         }
 
         // Refill if possible/needed
-        if let Some(scr_kind) = kind_able_refill {
-            if let Some(dst_kind) = kind_need_refill {
-                let value = (max_ability.min(10_000f32)).min(max_need);
-                self.internal_trade(scr_kind, dst_kind, value);
-            }
+        if let (Some(src), Some(dst)) = (ticker_able_refill, ticker_need_refill) {
+            let (src, dst) = (src.to_string(), dst.to_string());
+            let value = (max_ability.min(10_000f32)).min(max_need);
+            self.internal_trade(&src, &dst, value);
         }
     }
 
     /// Perform an internal trade
-    fn internal_trade(&mut self, src_kind: GoodKind, dst_kind: GoodKind, value_in_eur: f32) {
-        // Decrease good qty from source
-        let src_qty = value_in_eur * src_kind.get_default_exchange_rate();
-        self.goods
-            .get_mut(&src_kind)
-            .unwrap()
-            .split(src_qty)
-            .unwrap();
+    fn internal_trade(&mut self, src: &str, dst: &str, value_in_eur: f32) {
+        // Decrease good qty from source, and increase good qty to
+        // destination (+25% of default exchange rate). Rounding must not
+        // take more than the stock of the source.
+        let src_qty =
+            (value_in_eur * self.meta.goods_registry.spec(src).default_rate).min(self.stock(src));
+        let dst_qty = value_in_eur * self.meta.goods_registry.spec(dst).default_rate * 1.25;
+        let traded = self.apply_or_log(MarketEvent::InternalTradeDone {
+            sold: src.to_string(),
+            sold_quantity: src_qty,
+            bought: dst.to_string(),
+            bought_quantity: dst_qty,
+            value: value_in_eur,
+        });
+        if !traded {
+            return;
+        }
 
         // Increase need to source
         self.internal_needs
-            .get_mut(&src_kind)
+            .get_mut(src)
             .unwrap()
             .increase_need(value_in_eur);
 
-        // Increase good qty to destination (+25% of default exchange rate)
-        let dst_qty = value_in_eur * dst_kind.get_default_exchange_rate() * 1.25;
-        self.goods
-            .get_mut(&dst_kind)
-            .unwrap()
-            .merge(Good::new(dst_kind, dst_qty))
-            .unwrap();
-
         // Decrease need to destination
         self.internal_needs
-            .get_mut(&dst_kind)
+            .get_mut(dst)
             .unwrap()
             .decrease_need(value_in_eur);
+
+        self.meta.stats.internal_trades += 1;
+        self.meta.stats.internal_traded_value += value_in_eur;
     }
 
     /// Set internal needs according to the EUR value of a certain good and the total value of the market (in EUR)
@@ -95,22 +99,31 @@ impl SOLMarket {
     ///     - 100 USD  (value: 96.55€)  -> need: (52.71 - 96.55)    = -43.84    -> Exporter
     ///     - 100 YEN  (value: 0.70€)   -> need: (52.71 - 0.70)     = 52.01     -> Importer
     ///     - 100 YUAN (value: 13.59€)  -> need: (52.71 - 13.59)    = 39.12     -> Importer
-    pub(crate) fn set_internal_needs(goods_vec: Vec<Good>) -> HashMap<GoodKind, TradeRole> {
-        let total_value_market = goods_vec.iter().fold(0f32, |acc, g| {
-            acc + get_value_good(&g.get_kind(), g.get_qty())
+    pub(crate) fn set_internal_needs(
+        stock: &HashMap<String, f32>,
+        goods_registry: &GoodsRegistry,
+    ) -> HashMap<String, TradeRole> {
+        //Always sum in the same order, so that rounding does not depend on the
+        //order of the goods (which comes from a HashMap)
+        let mut goods_vec: Vec<(&String, f32)> = stock.iter().map(|(t, q)| (t, *q)).collect();
+        goods_vec
+            .sort_by_key(|(ticker, _)| goods_registry.iter().position(|s| &s.ticker == *ticker));
+        let total_value_market = goods_vec.iter().fold(0f32, |acc, (ticker, quantity)| {
+            acc + goods_registry.value_in_default_good(ticker, *quantity)
         });
         let ideal_value_per_good = total_value_market / goods_vec.len() as f32;
 
-        let mut internal_needs: HashMap<GoodKind, TradeRole> = HashMap::new();
-        for g in goods_vec.iter() {
-            let need = ideal_value_per_good - get_value_good(&g.get_kind(), g.get_qty());
+        let mut internal_needs: HashMap<String, TradeRole> = HashMap::new();
+        for (ticker, quantity) in goods_vec {
+            let need =
+                ideal_value_per_good - goods_registry.value_in_default_good(ticker, quantity);
             // Set goods with needs as importers
             if need > 0f32 {
-                internal_needs.insert(g.get_kind(), TradeRole::Importer { need });
+                internal_needs.insert(ticker.clone(), TradeRole::Importer { need });
             }
             // Set goods with negative needs (surplus) as Exporters
             else {
-                internal_needs.insert(g.get_kind(), TradeRole::Exporter { need });
+                internal_needs.insert(ticker.clone(), TradeRole::Exporter { need });
             }
         }
         internal_needs
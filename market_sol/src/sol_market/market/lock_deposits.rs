@@ -0,0 +1,125 @@
+use crate::sol_market::{LockDeposits, MarketEvent, QuotaError, SOLMarket};
+use unitn_market_2022::good::consts::DEFAULT_GOOD_KIND;
+
+/// This block contains the deposits on locks, enabled by
+/// `LockDepositConfig::enabled`.
+///
+/// The `Market` trait moves no goods when locking, so the deposit comes out
+/// of the DEFAULT_GOOD_KIND collateral of the margin account of the trader:
+/// without enough of it, the lock is rejected like by a quota, see
+/// `SOLMarket::last_quota_rejection`. Redeeming the lock puts the deposit
+/// back in the account (opened again if the trader closed it meanwhile),
+/// while letting it expire leaves the deposit to the market and raises the
+/// rate of the next locks of the trader.
+impl SOLMarket {
+    /// Returns the rate, recent expiries and deposits of a trader
+    pub fn lock_deposits(&self, trader_name: &str) -> LockDeposits {
+        let config = &self.meta.config.lock_deposits;
+        let expiries = self.recent_expiries(trader_name);
+        let (held, forfeited) = match self.event_state().deposits.get(trader_name) {
+            Some(deposits) => (deposits.held, deposits.forfeited),
+            None => (0.0, 0.0),
+        };
+        LockDeposits {
+            rate: config.rate_after(expiries),
+            expiries,
+            held,
+            forfeited,
+        }
+    }
+
+    /// Returns the deposit a lock worth `value` needs, 0 if deposits are
+    /// disabled, or why the trader can not cover it
+    pub(crate) fn check_lock_deposit(
+        &mut self,
+        trader_name: &str,
+        value: f32,
+    ) -> Result<f32, QuotaError> {
+        if !self.meta.config.lock_deposits.enabled {
+            return Ok(0.0);
+        }
+        let deposit = value * self.lock_deposits(trader_name).rate;
+        let collateral = self
+            .event_state()
+            .margin_accounts
+            .get(trader_name)
+            .map(|account| account.balance(DEFAULT_GOOD_KIND).collateral)
+            .unwrap_or(0.0);
+        if collateral >= deposit {
+            return Ok(deposit);
+        }
+        let e = QuotaError::DepositNotCovered {
+            trader_name: trader_name.to_string(),
+            deposit,
+            collateral,
+        };
+        self.log(format!("QUOTA-{trader_name}-REJECTED:{e}"));
+        self.meta
+            .quota_rejections
+            .insert(trader_name.to_string(), e.clone());
+        Err(e)
+    }
+
+    /// Takes the deposit out of the margin account of the trader
+    pub(crate) fn hold_lock_deposit(&mut self, trader_name: &str, deposit: f32) {
+        if deposit <= 0.0 {
+            return;
+        }
+        self.apply(MarketEvent::DepositHeld {
+            trader_name: trader_name.to_string(),
+            deposit,
+        })
+        .expect("A deposit does not take from the stock");
+        self.log(format!("DEPOSIT-{trader_name}-HELD:{deposit:+e}"));
+    }
+
+    /// Puts the deposit of a redeemed lock back in the margin account of
+    /// the trader
+    pub(crate) fn refund_lock_deposit(&mut self, trader_name: &str, deposit: f32) {
+        if deposit <= 0.0 {
+            return;
+        }
+        self.apply(MarketEvent::DepositRefunded {
+            trader_name: trader_name.to_string(),
+            deposit,
+        })
+        .expect("A deposit does not take from the stock");
+        self.log(format!("DEPOSIT-{trader_name}-REFUNDED:{deposit:+e}"));
+    }
+
+    /// Keeps the deposit of an expired lock, and counts the expiry against
+    /// the trader
+    pub(crate) fn forfeit_lock_deposit(&mut self, trader_name: &str, deposit: f32) {
+        if !self.meta.config.lock_deposits.enabled && deposit <= 0.0 {
+            return;
+        }
+        let forfeiture = MarketEvent::DepositForfeited {
+            trader_name: trader_name.to_string(),
+            deposit,
+            forget_until: self.oldest_day_for_deposits(),
+        };
+        if self.apply_or_log(forfeiture) && deposit > 0.0 {
+            self.log(format!("DEPOSIT-{trader_name}-FORFEITED:{deposit:+e}"));
+        }
+    }
+
+    /// The locks of the trader that expired in the window
+    fn recent_expiries(&self, trader_name: &str) -> u32 {
+        let oldest_day = self.oldest_day_for_deposits();
+        match self.event_state().deposits.get(trader_name) {
+            Some(deposits) => deposits
+                .expiries
+                .iter()
+                .filter(|day| **day > oldest_day)
+                .count() as u32,
+            None => 0,
+        }
+    }
+
+    /// The last day before the window of `LockDepositConfig::window_days`
+    fn oldest_day_for_deposits(&self) -> u32 {
+        self.meta
+            .current_day
+            .saturating_sub(self.meta.config.lock_deposits.window_days)
+    }
+}
@@ -0,0 +1,383 @@
+use crate::sol_market::{MarginAccount, MarginError, MarginSummary, MarketEvent, SOLMarket};
+use unitn_market_2022::good::{consts::DEFAULT_GOOD_KIND, good::Good, good_kind::GoodKind};
+
+/// This block contains the margin accounts: a trader deposits collateral in
+/// the market and borrows goods against it, up to
+/// `MarketConfig::margin_max_leverage` times its equity.
+///
+/// Collateral and debts are valued at the exchange rate of the market,
+/// without margin. Every day `on_event` adds the interest to the debts and
+/// liquidates the accounts whose equity fell below
+/// `MarketConfig::margin_maintenance_ratio` of their debt.
+impl SOLMarket {
+    /// Opens a margin account, with all of `collateral` as its first deposit
+    pub fn open_margin_account(
+        &mut self,
+        trader_name: &str,
+        collateral: &mut Good,
+    ) -> Result<(), MarginError> {
+        let quantity = collateral.get_qty();
+        let result = if self.event_state().margin_accounts.contains_key(trader_name) {
+            Err(MarginError::AccountAlreadyOpen {
+                trader_name: trader_name.to_string(),
+            })
+        } else if quantity <= 0.0 {
+            Err(MarginError::NonPositiveQuantity { quantity })
+        } else {
+            let kinds: Vec<GoodKind> = self
+                .meta
+                .goods_registry
+                .traded()
+                .map(|(kind, _)| kind)
+                .collect();
+            let account = MarginAccount::new(trader_name, self.meta.current_day, kinds.iter());
+            self.apply(MarketEvent::MarginAccountOpened { account })
+                .expect("The account was not open");
+            self.deposit_collateral_unlogged(trader_name, collateral)
+        };
+        self.log_margin_step(format!("OPEN_MARGIN_ACCOUNT-TRADER:{trader_name}"), &result);
+        result
+    }
+
+    /// Adds all of `collateral` to the collateral of the account
+    pub fn deposit_collateral(
+        &mut self,
+        trader_name: &str,
+        collateral: &mut Good,
+    ) -> Result<(), MarginError> {
+        let kind = collateral.get_kind();
+        let quantity = collateral.get_qty();
+        let result = self.deposit_collateral_unlogged(trader_name, collateral);
+        self.log_margin_step(
+            format!("DEPOSIT_COLLATERAL-TRADER:{trader_name}-KIND:{kind}-QUANTITY:{quantity:+e}"),
+            &result,
+        );
+        result
+    }
+
+    /// Takes some collateral back, as long as the debt stays within the
+    /// leverage limit
+    pub fn withdraw_collateral(
+        &mut self,
+        trader_name: &str,
+        kind: GoodKind,
+        quantity: f32,
+    ) -> Result<Good, MarginError> {
+        let result = self.try_withdraw_collateral(trader_name, kind, quantity);
+        self.log_margin_step(
+            format!("WITHDRAW_COLLATERAL-TRADER:{trader_name}-KIND:{kind}-QUANTITY:{quantity:+e}"),
+            &result,
+        );
+        result
+    }
+
+    /// Lends some of a good to the trader, as long as the debt stays within
+    /// the leverage limit
+    pub fn margin_borrow(
+        &mut self,
+        trader_name: &str,
+        kind: GoodKind,
+        quantity: f32,
+    ) -> Result<Good, MarginError> {
+        let result = self.try_margin_borrow(trader_name, kind, quantity);
+        self.log_margin_step(
+            format!("MARGIN_BORROW-TRADER:{trader_name}-KIND:{kind}-QUANTITY:{quantity:+e}"),
+            &result,
+        );
+        result
+    }
+
+    /// Pays back the debt in the good of `payment`. Only what is owed is
+    /// taken, the rest stays in `payment`.
+    pub fn margin_repay(
+        &mut self,
+        trader_name: &str,
+        payment: &mut Good,
+    ) -> Result<(), MarginError> {
+        let kind = payment.get_kind();
+        let quantity = payment.get_qty();
+        let result = self.try_margin_repay(trader_name, payment);
+        self.log_margin_step(
+            format!("MARGIN_REPAY-TRADER:{trader_name}-KIND:{kind}-QUANTITY:{quantity:+e}"),
+            &result,
+        );
+        result
+    }
+
+    /// Closes an account without debts and hands back its collateral
+    pub fn close_margin_account(&mut self, trader_name: &str) -> Result<Vec<Good>, MarginError> {
+        let result = match self.event_state().margin_accounts.get(trader_name) {
+            None => Err(MarginError::NoAccount {
+                trader_name: trader_name.to_string(),
+            }),
+            Some(account) if account.has_debt() => Err(MarginError::OutstandingDebt {
+                debt_value: self.summarize(account).debt_value,
+            }),
+            Some(account) => {
+                let collateral = account
+                    .balances
+                    .iter()
+                    .filter(|balance| balance.collateral > 0.0)
+                    .map(|balance| Good::new(balance.kind, balance.collateral))
+                    .collect();
+                self.apply(MarketEvent::MarginAccountClosed {
+                    trader_name: trader_name.to_string(),
+                })
+                .expect("The account was checked");
+                Ok(collateral)
+            }
+        };
+        self.log_margin_step(
+            format!("CLOSE_MARGIN_ACCOUNT-TRADER:{trader_name}"),
+            &result,
+        );
+        result
+    }
+
+    /// Returns the margin account of a trader, if it has one
+    pub fn margin_account(&self, trader_name: &str) -> Option<&MarginAccount> {
+        self.event_state().margin_accounts.get(trader_name)
+    }
+
+    /// Returns the value of the margin account of a trader, at today's rates
+    pub fn margin_summary(&self, trader_name: &str) -> Option<MarginSummary> {
+        self.event_state()
+            .margin_accounts
+            .get(trader_name)
+            .map(|account| self.summarize(account))
+    }
+
+    /// Every margin account, sorted by trader name
+    pub(crate) fn margin_accounts_by_name(&self) -> Vec<MarginAccount> {
+        let mut accounts: Vec<MarginAccount> = self
+            .event_state()
+            .margin_accounts
+            .values()
+            .cloned()
+            .collect();
+        accounts.sort_by(|a, b| a.trader_name.cmp(&b.trader_name));
+        accounts
+    }
+
+    /// Adds a day of interest to every debt, then liquidates the accounts
+    /// below the maintenance margin. Called by `on_event`.
+    pub(crate) fn update_margin_accounts(&mut self) {
+        let mut trader_names: Vec<String> = self
+            .event_state()
+            .margin_accounts
+            .values()
+            .filter(|account| account.has_debt())
+            .map(|account| account.trader_name.clone())
+            .collect();
+        trader_names.sort();
+        let rate = self.meta.config.margin_daily_interest_rate;
+        for trader_name in trader_names {
+            let interest = MarketEvent::MarginInterestCharged {
+                trader_name: trader_name.clone(),
+                rate,
+            };
+            if !self.apply_or_log(interest) {
+                continue;
+            }
+            let summary = self.summarize(&self.event_state().margin_accounts[&trader_name]);
+            self.log(format!(
+                "MARGIN_INTEREST-TRADER:{trader_name}-COLLATERAL:{:+e}-DEBT:{:+e}",
+                summary.collateral_value, summary.debt_value
+            ));
+            if summary.margin_ratio() < self.meta.config.margin_maintenance_ratio {
+                self.liquidate_margin_account(&trader_name, summary);
+            }
+        }
+    }
+
+    /// Takes all the collateral of an account into the stock and cancels its
+    /// debts. What is left of the equity stays in the account, in
+    /// DEFAULT_GOOD_KIND.
+    fn liquidate_margin_account(&mut self, trader_name: &str, summary: MarginSummary) {
+        self.log(format!(
+            "MARGIN_CALL-TRADER:{trader_name}-COLLATERAL:{:+e}-DEBT:{:+e}",
+            summary.collateral_value, summary.debt_value
+        ));
+        let account = &self.event_state().margin_accounts[trader_name];
+        for balance in account.balances.iter() {
+            if balance.collateral > 0.0 {
+                self.log(format!(
+                    "MARGIN_LIQUIDATION-TRADER:{trader_name}-SEIZED-KIND:{}-QUANTITY:{:+e}",
+                    balance.kind, balance.collateral
+                ));
+            }
+            if balance.debt > 0.0 {
+                self.log(format!(
+                    "MARGIN_LIQUIDATION-TRADER:{trader_name}-CANCELLED-KIND:{}-QUANTITY:{:+e}",
+                    balance.kind, balance.debt
+                ));
+            }
+        }
+
+        // What is left of the equity can not be more than the cash of the
+        // market, the seized cash included
+        let seized_cash = account.balance(DEFAULT_GOOD_KIND).collateral;
+        let available = self.get_available_quantity(DEFAULT_GOOD_KIND) + seized_cash;
+        let left = summary.equity().clamp(0.0, available);
+        let liquidation = MarketEvent::MarginAccountLiquidated {
+            trader_name: trader_name.to_string(),
+            left,
+        };
+        if self.apply_or_log(liquidation) {
+            self.log(format!(
+                "MARGIN_LIQUIDATION-TRADER:{trader_name}-LEFT:{left:+e}-DONE"
+            ));
+        }
+    }
+
+    fn deposit_collateral_unlogged(
+        &mut self,
+        trader_name: &str,
+        collateral: &mut Good,
+    ) -> Result<(), MarginError> {
+        let quantity = collateral.get_qty();
+        if quantity <= 0.0 {
+            return Err(MarginError::NonPositiveQuantity { quantity });
+        }
+        self.existing_account(trader_name)?;
+        self.apply(MarketEvent::CollateralDeposited {
+            trader_name: trader_name.to_string(),
+            kind: collateral.get_kind(),
+            quantity,
+        })
+        .expect("The account was checked");
+        collateral.split(quantity).unwrap();
+        Ok(())
+    }
+
+    fn try_withdraw_collateral(
+        &mut self,
+        trader_name: &str,
+        kind: GoodKind,
+        quantity: f32,
+    ) -> Result<Good, MarginError> {
+        if quantity <= 0.0 {
+            return Err(MarginError::NonPositiveQuantity { quantity });
+        }
+        let mut account = self.existing_account(trader_name)?.clone();
+        let available = account.balance(kind).collateral;
+        if available < quantity {
+            return Err(MarginError::InsufficientCollateral {
+                kind,
+                required: quantity,
+                available,
+            });
+        }
+        account.balance_mut(kind).collateral -= quantity;
+        self.check_leverage(&account)?;
+        self.apply(MarketEvent::CollateralWithdrawn {
+            trader_name: trader_name.to_string(),
+            kind,
+            quantity,
+        })
+        .expect("The account was checked");
+        Ok(Good::new(kind, quantity))
+    }
+
+    fn try_margin_borrow(
+        &mut self,
+        trader_name: &str,
+        kind: GoodKind,
+        quantity: f32,
+    ) -> Result<Good, MarginError> {
+        if quantity <= 0.0 {
+            return Err(MarginError::NonPositiveQuantity { quantity });
+        }
+        let mut account = self.existing_account(trader_name)?.clone();
+        let available = self.get_available_quantity(kind);
+        if available < quantity {
+            return Err(MarginError::InsufficientMarketQuantity {
+                kind,
+                required: quantity,
+                available,
+            });
+        }
+        account.balance_mut(kind).debt += quantity;
+        self.check_leverage(&account)?;
+        self.apply(MarketEvent::MarginBorrowed {
+            trader_name: trader_name.to_string(),
+            kind,
+            quantity,
+        })
+        .expect("The quantity available was checked");
+        Ok(Good::new(kind, quantity))
+    }
+
+    fn try_margin_repay(
+        &mut self,
+        trader_name: &str,
+        payment: &mut Good,
+    ) -> Result<(), MarginError> {
+        let kind = payment.get_kind();
+        let debt = self.existing_account(trader_name)?.balance(kind).debt;
+        if debt <= 0.0 {
+            return Err(MarginError::NothingToRepay { kind });
+        }
+        let quantity = payment.get_qty().min(debt);
+        if quantity <= 0.0 {
+            return Err(MarginError::NonPositiveQuantity { quantity });
+        }
+        self.apply(MarketEvent::MarginRepaid {
+            trader_name: trader_name.to_string(),
+            kind,
+            quantity,
+        })
+        .expect("The account was checked");
+        payment.split(quantity).unwrap();
+        Ok(())
+    }
+
+    fn existing_account(&self, trader_name: &str) -> Result<&MarginAccount, MarginError> {
+        self.event_state()
+            .margin_accounts
+            .get(trader_name)
+            .ok_or_else(|| MarginError::NoAccount {
+                trader_name: trader_name.to_string(),
+            })
+    }
+
+    /// Checks that the debt of the account is at most `margin_max_leverage`
+    /// times its equity
+    fn check_leverage(&self, account: &MarginAccount) -> Result<(), MarginError> {
+        let summary = self.summarize(account);
+        if summary.debt_value <= 0.0 {
+            return Ok(());
+        }
+        // debt <= leverage * (collateral - debt)
+        let leverage = self.meta.config.margin_max_leverage.max(0.0);
+        let max_debt_value = summary.collateral_value * leverage / (1.0 + leverage);
+        if summary.debt_value > max_debt_value {
+            return Err(MarginError::LeverageExceeded {
+                debt_value: summary.debt_value,
+                max_debt_value,
+            });
+        }
+        Ok(())
+    }
+
+    fn summarize(&self, account: &MarginAccount) -> MarginSummary {
+        let mut summary = MarginSummary {
+            collateral_value: 0.0,
+            debt_value: 0.0,
+        };
+        for balance in account.balances.iter() {
+            let rate = self.get_good_buy_exchange_rate(balance.kind);
+            summary.collateral_value += balance.collateral / rate;
+            summary.debt_value += balance.debt / rate;
+        }
+        summary
+    }
+
+    /// Logs the outcome of an operation on a margin account
+    fn log_margin_step<T>(&self, step: String, result: &Result<T, MarginError>) {
+        match result {
+            Ok(_) => self.log(format!("{step}-OK")),
+            Err(e) => self.log(format!("{step}-ERROR:{e}")),
+        }
+    }
+}
@@ -1,5 +1,8 @@
 use crate::sol_market::domain::good_lock_meta::GoodLockMeta;
-use crate::sol_market::{get_value_good, log, SOLMarket, MARKET_NAME, TOKEN_DURATION};
+use crate::sol_market::domain::surveillance::ActivityKind;
+use crate::sol_market::market::quotes::{buy_price_at_rate, sell_price_at_rate};
+use crate::sol_market::market::rates::LockTerms;
+use crate::sol_market::{AuctionSide, MarketEvent, SOLMarket, TradeSide};
 use std::collections::HashMap;
 use std::{
     cell::RefCell,
@@ -40,32 +43,21 @@ impl Market for SOLMarket {
         Self::new_file_internal(path_str)
     }
 
-    /// Returns [`MARKET_NAME`]
+    /// Returns the name in the configuration of the market, "SOL" by default
     fn get_name(&self) -> &'static str {
-        MARKET_NAME
+        self.meta.logger.market_name
     }
 
     /// Returns the quantity of good EUR for the market.
     fn get_budget(&self) -> f32 {
-        self.goods.get(&DEFAULT_GOOD_KIND).unwrap().get_qty()
+        self.get_available_quantity(DEFAULT_GOOD_KIND)
     }
     
-    /// Returns the price in [`DEFAULT_GOOD_KIND`] that market offers in exchange for the `kind` and `quantity` given
+    /// Returns the price in [`DEFAULT_GOOD_KIND`] that market offers in exchange for the `kind` and `quantity` given.
+    /// It includes the buy fee, without the discount of the trader.
     fn get_buy_price(&self, kind: GoodKind, quantity: f32) -> Result<f32, MarketGetterError> {
-        if quantity.is_sign_negative() {
-            return Err(MarketGetterError::NonPositiveQuantityAsked);
-        }
-
         //TODO: check that this is the total unlocked quantity!
-        let total_quantity_in_the_market =
-            self.goods.get(&kind).map(|g| g.get_qty()).unwrap_or(0.0);
-        if quantity > total_quantity_in_the_market {
-            return Err(MarketGetterError::InsufficientGoodQuantityAvailable {
-                requested_good_kind: kind,
-                requested_good_quantity: quantity,
-                available_good_quantity: total_quantity_in_the_market,
-            });
-        }
+        let total_quantity_in_the_market = self.get_available_quantity(kind);
 
         let exchange_rate_eur_good = self.get_good_buy_exchange_rate(kind);
 
@@ -81,17 +73,18 @@ impl Market for SOLMarket {
         // // let price = initial_price + margin;
 
         //no more using the adaptive margin
-        let price = quantity / exchange_rate_eur_good;
-
-        Ok(price)
+        let price = buy_price_at_rate(
+            kind,
+            quantity,
+            exchange_rate_eur_good,
+            total_quantity_in_the_market,
+        )?;
+        Ok(price + self.get_fee(None, TradeSide::Buy, price))
     }
 
-    /// Returns the price in [`DEFAULT_GOOD_KIND`] that market pays in exchange for the `kind` and `quantity` given
+    /// Returns the price in [`DEFAULT_GOOD_KIND`] that market pays in exchange for the `kind` and `quantity` given.
+    /// The sell fee, without the discount of the trader, is taken off.
     fn get_sell_price(&self, kind: GoodKind, quantity: f32) -> Result<f32, MarketGetterError> {
-        if quantity.is_sign_negative() {
-            return Err(MarketGetterError::NonPositiveQuantityAsked);
-        }
-
         // let good_label = self
         //     .good_labels
         //     .iter()
@@ -101,7 +94,8 @@ impl Market for SOLMarket {
         // Ok(quantity / good_label.exchange_rate_buy) //as discussed in the group with farouk
 
         let eur_good_exchange_rate = self.get_good_sell_exchange_rate(kind);
-        Ok(quantity / eur_good_exchange_rate)
+        let price = sell_price_at_rate(quantity, eur_good_exchange_rate)?;
+        Ok((price - self.get_fee(None, TradeSide::Sell, price)).max(0.0))
     }
     
     /// Returns a vector of [`GoodLabel`] for each good kind that market sells.
@@ -145,7 +139,7 @@ impl Market for SOLMarket {
 
         // Check positive quantity
         if good_quantity_to_lock.is_sign_negative() {
-            log(log_error);
+            self.log(log_error);
             return Err(LockBuyError::NonPositiveQuantityToBuy {
                 negative_quantity_to_buy: good_quantity_to_lock,
             });
@@ -153,21 +147,61 @@ impl Market for SOLMarket {
 
         // Check positive bid
         if bid.is_sign_negative() {
-            log(log_error);
+            self.log(log_error);
             return Err(LockBuyError::NonPositiveBid { negative_bid: bid });
         }
 
+        // Banned traders can not lock
+        if self.is_banned(&trader_name) {
+            self.log(format!("SURVEILLANCE-{trader_name}-BANNED"));
+            self.log(log_error);
+            return Err(LockBuyError::MaxAllowedLocksReached);
+        }
+
+        // Check the quotas of the trader
+        let quotas = self.check_lock_quotas(&trader_name, kind_to_buy, good_quantity_to_lock, bid);
+        if let Err(e) = quotas {
+            self.log(log_error);
+            return Err(e.into());
+        }
+
         // Lock limit check
         let num_of_locks = self.meta.num_of_buy_locks(&trader_name);
-        if SOLMarket::lock_limit_exceeded(num_of_locks) {
-            log(log_error);
+        if self.lock_limit_exceeded(num_of_locks) {
+            self.log(log_error);
             return Err(LockBuyError::MaxAllowedLocksReached);
         }
 
+        // Check the trader can leave the deposit of the lock
+        let value = self
+            .buy_terms(kind_to_buy, good_quantity_to_lock, &trader_name)
+            .value;
+        let deposit = match self.check_lock_deposit(&trader_name, value) {
+            Ok(deposit) => deposit,
+            Err(e) => {
+                self.log(log_error);
+                return Err(e.into());
+            }
+        };
+
+        // In auction mode, the bid is a limit for the next clearing
+        if self.meta.config.auction.enabled {
+            let token = self.place_auction_order(
+                AuctionSide::Buy,
+                kind_to_buy,
+                good_quantity_to_lock,
+                bid,
+                deposit,
+                trader_name.clone(),
+            );
+            self.log(format!("LOCK_BUY-{trader_name}-KIND_TO_BUY:{kind_to_buy}-QUANTITY_TO_BUY:{good_quantity_to_lock:+e}-BID:{bid:+e}-TOKEN:{token}"));
+            return Ok(token);
+        }
+
         // Check quantity available
         let quantity_available = self.get_available_quantity(kind_to_buy);
         if quantity_available < good_quantity_to_lock {
-            log(log_error);
+            self.log(log_error);
             return Err(LockBuyError::InsufficientGoodQuantityAvailable {
                 requested_good_kind: kind_to_buy,
                 requested_good_quantity: good_quantity_to_lock,
@@ -176,10 +210,13 @@ impl Market for SOLMarket {
         }
 
         // Check bid
-        let sell_exchange_rate_eur_good = self.get_good_buy_exchange_rate(kind_to_buy);
-        let min_bid = good_quantity_to_lock / sell_exchange_rate_eur_good;
+        let LockTerms {
+            value,
+            fee,
+            limit: min_bid,
+        } = self.buy_terms(kind_to_buy, good_quantity_to_lock, &trader_name);
         if bid < min_bid {
-            log(log_error);
+            self.log(log_error);
             return Err(LockBuyError::BidTooLow {
                 requested_good_kind: kind_to_buy,
                 requested_good_quantity: good_quantity_to_lock,
@@ -190,12 +227,14 @@ impl Market for SOLMarket {
 
         // Create token
         let mut hasher = DefaultHasher::new();
-        let now = chrono::Local::now();
+        let now = self.meta.clock.now();
+        // The count of the locks tells tokens apart when the clock stands still
         (
             kind_to_buy,
             good_quantity_to_lock.to_string(),
             bid.to_string(),
             now,
+            self.meta.stats.locks.created,
             trader_name.clone(),
         )
             .hash(&mut hasher);
@@ -205,11 +244,15 @@ impl Market for SOLMarket {
         // problem with on_event method: the subscribed markets receive the notif, but you don't send the notif to yourself (at the moment) - but you can add that with one line
         // TODO: DISCUSS -> updates should be done only after a successful buy/sell, not locks
 
-        let previous_quantity = self.goods.get(&kind_to_buy).unwrap().get_qty();
-        self.goods.insert(
-            kind_to_buy,
-            Good::new(kind_to_buy, previous_quantity - good_quantity_to_lock),
-        );
+        self.apply(MarketEvent::GoodReserved {
+            token: token.clone(),
+            side: TradeSide::Buy,
+            trader_name: trader_name.clone(),
+            good: self.ticker(kind_to_buy).to_string(),
+            quantity: good_quantity_to_lock,
+            price: bid,
+        })
+        .expect("The quantity available was checked");
 
         // Update meta
         let good_meta = GoodLockMeta::new(
@@ -217,11 +260,23 @@ impl Market for SOLMarket {
             bid,
             good_quantity_to_lock,
             self.meta.current_day,
+            value,
+            fee,
             trader_name.clone(),
+        )
+        .with_deposit(deposit);
+
+        self.meta.insert_buy_lock(token.clone(), good_meta);
+        self.hold_lock_deposit(&trader_name, deposit);
+        self.meta.stats.locks.created += 1;
+        self.watch(
+            &trader_name,
+            ActivityKind::LockBuy,
+            kind_to_buy,
+            good_quantity_to_lock,
+            bid,
         );
 
-        self.meta.locked_buys.insert(token.clone(), good_meta);
-
         // Create and spread event
         let e = Event {
             kind: EventKind::LockedBuy,
@@ -232,7 +287,7 @@ impl Market for SOLMarket {
 
         self.notify_everyone(e);
 
-        log(format!("LOCK_BUY-{trader_name}-KIND_TO_BUY:{kind_to_buy}-QUANTITY_TO_BUY:{good_quantity_to_lock:+e}-BID:{bid:+e}-TOKEN:{token}"));
+        self.log(format!("LOCK_BUY-{trader_name}-KIND_TO_BUY:{kind_to_buy}-QUANTITY_TO_BUY:{good_quantity_to_lock:+e}-BID:{bid:+e}-TOKEN:{token}"));
 
         Ok(token)
     }
@@ -259,10 +314,18 @@ impl Market for SOLMarket {
         // Set error log
         let log_error = format!("BUY-TOKEN:{token}-ERROR");
 
+        // Check token validity, expired locks are not in the market anymore
+        if self.meta.buy_lock_index.is_expired(&token) {
+            self.log(log_error);
+            return Err(BuyError::ExpiredToken {
+                expired_token: token,
+            });
+        }
+
         // Check token existence
         let good_meta = match self.meta.locked_buys.get(&*token) {
             None => {
-                log(log_error);
+                self.log(log_error);
                 return Err(BuyError::UnrecognizedToken {
                     unrecognized_token: token,
                 });
@@ -270,19 +333,10 @@ impl Market for SOLMarket {
             Some(g) => g,
         };
 
-        // Check token validity
-        let days_since = self.meta.current_day - good_meta.created_on;
-        if days_since > TOKEN_DURATION {
-            log(log_error);
-            return Err(BuyError::ExpiredToken {
-                expired_token: token,
-            });
-        }
-
         // Check cash is default
         let kind = cash.get_kind();
         if kind.ne(&DEFAULT_GOOD_KIND) {
-            log(log_error);
+            self.log(log_error);
             return Err(BuyError::GoodKindNotDefault {
                 non_default_good_kind: kind,
             });
@@ -292,23 +346,31 @@ impl Market for SOLMarket {
         let contained_quantity = cash.get_qty();
         let pre_agreed_quantity = good_meta.price;
         if contained_quantity < pre_agreed_quantity {
-            log(log_error);
+            self.log(log_error);
             return Err(BuyError::InsufficientGoodQuantity {
                 contained_quantity,
                 pre_agreed_quantity,
             });
         }
 
+        // The market kept the locked good until now, so it gets its carry
+        let (locked_kind, locked_quantity, created_on) =
+            (good_meta.kind, good_meta.quantity, good_meta.created_on);
+        self.settle_carry(locked_kind, locked_quantity, created_on);
+        let good_meta = self.meta.locked_buys.get(&*token).unwrap();
+        let release_good = Good::new(good_meta.kind, good_meta.quantity);
+
         // Cash in, todo: Update good buy and sell price (in on_event method)
         let paid_eur = cash.split(pre_agreed_quantity).unwrap();
-        let total_quantity =
-            self.goods.get(&DEFAULT_GOOD_KIND).unwrap().get_qty() + paid_eur.get_qty();
-        self.goods.insert(
-            DEFAULT_GOOD_KIND,
-            Good::new(DEFAULT_GOOD_KIND, total_quantity),
-        );
+        self.apply(MarketEvent::CashReceived {
+            token: token.clone(),
+            cash: paid_eur.get_qty(),
+        })
+        .expect("Cash only adds to the stock");
 
-        let release_good = Good::new(good_meta.kind, good_meta.quantity);
+        // Reset lock
+        let good_meta = self.meta.remove_buy_lock(&token).unwrap();
+        self.count_trade(&good_meta.trader_name);
 
         // Create and spread event
         let e = Event {
@@ -317,28 +379,46 @@ impl Market for SOLMarket {
             quantity: release_good.get_qty(),
             price: good_meta.price,
         };
-
-        // Reset lock
-        self.meta.locked_buys.remove(&*token);
+        self.watch(
+            &good_meta.trader_name,
+            ActivityKind::Buy,
+            good_meta.kind,
+            good_meta.quantity,
+            good_meta.price,
+        );
+        self.record_trade(
+            good_meta.kind,
+            good_meta.quantity,
+            good_meta.price,
+            good_meta.market_value,
+            true,
+        );
+        self.record_fee(
+            &good_meta.trader_name,
+            TradeSide::Buy,
+            good_meta.price - good_meta.fee,
+            good_meta.fee,
+        );
+        self.refund_lock_deposit(&good_meta.trader_name, good_meta.deposit);
 
         // Increase need for release good
+        let registry = &self.meta.goods_registry;
+        let release_ticker = registry.ticker_of(release_good.get_kind());
         self.internal_needs
-            .get_mut(&release_good.get_kind())
+            .get_mut(release_ticker)
             .unwrap()
-            .increase_need(get_value_good(
-                &release_good.get_kind(),
-                release_good.get_qty(),
-            ));
+            .increase_need(registry.value_in_default_good(release_ticker, release_good.get_qty()));
 
         // Decrease need for cash
+        let cash_ticker = registry.ticker_of(paid_eur.get_kind());
         self.internal_needs
-            .get_mut(&paid_eur.get_kind())
+            .get_mut(cash_ticker)
             .unwrap()
-            .decrease_need(get_value_good(&paid_eur.get_kind(), paid_eur.get_qty()));
+            .decrease_need(registry.value_in_default_good(cash_ticker, paid_eur.get_qty()));
 
         self.notify_everyone(e);
 
-        log(format!("BUY-TOKEN:{token}-OK"));
+        self.log(format!("BUY-TOKEN:{token}-OK"));
 
         Ok(release_good)
     }
@@ -376,7 +456,7 @@ impl Market for SOLMarket {
 
         // Check positive quantity
         if quantity_to_sell.is_sign_negative() {
-            log(log_error);
+            self.log(log_error);
             return Err(LockSellError::NonPositiveQuantityToSell {
                 negative_quantity_to_sell: (quantity_to_sell),
             });
@@ -384,16 +464,30 @@ impl Market for SOLMarket {
 
         // Check positive bid
         if offer.is_sign_negative() {
-            log(log_error);
+            self.log(log_error);
             return Err(LockSellError::NonPositiveOffer {
                 negative_offer: offer,
             });
         }
 
+        // Banned traders can not lock
+        if self.is_banned(&trader_name) {
+            self.log(format!("SURVEILLANCE-{trader_name}-BANNED"));
+            self.log(log_error);
+            return Err(LockSellError::MaxAllowedLocksReached);
+        }
+
+        // Check the quotas of the trader
+        let quotas = self.check_lock_quotas(&trader_name, kind_to_sell, quantity_to_sell, offer);
+        if let Err(e) = quotas {
+            self.log(log_error);
+            return Err(e.into());
+        }
+
         // Check money available
-        let money_available = self.goods.get(&DEFAULT_GOOD_KIND).unwrap().get_qty();
+        let money_available = self.get_available_quantity(DEFAULT_GOOD_KIND);
         if money_available < offer {
-            log(log_error);
+            self.log(log_error);
             return Err(LockSellError::InsufficientDefaultGoodQuantityAvailable {
                 offered_good_kind: kind_to_sell,
                 offered_good_quantity: quantity_to_sell,
@@ -402,16 +496,45 @@ impl Market for SOLMarket {
         }
 
         // Lock limit check
-        if SOLMarket::lock_limit_exceeded(self.meta.num_of_locked_sells(&trader_name)) {
-            log(log_error);
+        if self.lock_limit_exceeded(self.meta.num_of_locked_sells(&trader_name)) {
+            self.log(log_error);
             return Err(LockSellError::MaxAllowedLocksReached);
         }
 
+        // Check the trader can leave the deposit of the lock
+        let value = self
+            .sell_terms(kind_to_sell, quantity_to_sell, &trader_name)
+            .value;
+        let deposit = match self.check_lock_deposit(&trader_name, value) {
+            Ok(deposit) => deposit,
+            Err(e) => {
+                self.log(log_error);
+                return Err(e.into());
+            }
+        };
+
+        // In auction mode, the offer is a limit for the next clearing
+        if self.meta.config.auction.enabled {
+            let token = self.place_auction_order(
+                AuctionSide::Sell,
+                kind_to_sell,
+                quantity_to_sell,
+                offer,
+                deposit,
+                trader_name.clone(),
+            );
+            self.log(format!("LOCK_SELL-{trader_name}-KIND_TO_SELL:{kind_to_sell}-QUANTITY_TO_SELL:{quantity_to_sell:+e}-OFFER:{offer:+e}-TOKEN:{token}"));
+            return Ok(token);
+        }
+
         // Check offer not too high
-        let good_sell_rate = self.get_good_sell_exchange_rate(kind_to_sell);
-        let acceptable_eur_we_give_the_trader_on_sell = quantity_to_sell / good_sell_rate;
+        let LockTerms {
+            value,
+            fee,
+            limit: acceptable_eur_we_give_the_trader_on_sell,
+        } = self.sell_terms(kind_to_sell, quantity_to_sell, &trader_name);
         if offer > acceptable_eur_we_give_the_trader_on_sell {
-            log(log_error);
+            self.log(log_error);
             return Err(LockSellError::OfferTooHigh {
                 offered_good_kind: kind_to_sell,
                 offered_good_quantity: quantity_to_sell,
@@ -421,12 +544,14 @@ impl Market for SOLMarket {
         }
 
         let mut hasher = DefaultHasher::new();
-        let now = chrono::Local::now();
+        let now = self.meta.clock.now();
+        // The count of the locks tells tokens apart when the clock stands still
         (
             kind_to_sell,
             quantity_to_sell.to_string(),
             offer.to_string(),
             now,
+            self.meta.stats.locks.created,
             trader_name.clone(),
         )
             .hash(&mut hasher);
@@ -434,12 +559,15 @@ impl Market for SOLMarket {
 
         // Update default good quantity available, todo: Update good buy and sell price (in on_event method)
         // also: updates should be done only after a successful buy/sell, not locks
-        let mut remaining_quantity = self.goods.get(&DEFAULT_GOOD_KIND).unwrap().get_qty();
-        remaining_quantity -= offer;
-        self.goods.insert(
-            DEFAULT_GOOD_KIND,
-            Good::new(DEFAULT_GOOD_KIND, remaining_quantity),
-        );
+        self.apply(MarketEvent::GoodReserved {
+            token: token.clone(),
+            side: TradeSide::Sell,
+            trader_name: trader_name.clone(),
+            good: self.ticker(kind_to_sell).to_string(),
+            quantity: quantity_to_sell,
+            price: offer,
+        })
+        .expect("The money available was checked");
 
         // Update meta
         let good_meta = GoodLockMeta::new(
@@ -447,11 +575,23 @@ impl Market for SOLMarket {
             offer,
             quantity_to_sell,
             self.meta.current_day,
+            value / (1.0 + self.meta.config.market_margin),
+            fee,
             trader_name.clone(),
+        )
+        .with_deposit(deposit);
+
+        self.meta.insert_sell_lock(token.clone(), good_meta);
+        self.hold_lock_deposit(&trader_name, deposit);
+        self.meta.stats.locks.created += 1;
+        self.watch(
+            &trader_name,
+            ActivityKind::LockSell,
+            kind_to_sell,
+            quantity_to_sell,
+            offer,
         );
 
-        self.meta.locked_sells.insert(token.clone(), good_meta);
-
         // Create and spread event
         let e = Event {
             kind: EventKind::LockedSell,
@@ -462,7 +602,7 @@ impl Market for SOLMarket {
 
         self.notify_everyone(e);
 
-        log(format!("LOCK_SELL-{trader_name}-KIND_TO_SELL:{kind_to_sell}-QUANTITY_TO_SELL:{quantity_to_sell:+e}-OFFER:{offer:+e}-TOKEN:{token}"));
+        self.log(format!("LOCK_SELL-{trader_name}-KIND_TO_SELL:{kind_to_sell}-QUANTITY_TO_SELL:{quantity_to_sell:+e}-OFFER:{offer:+e}-TOKEN:{token}"));
 
         Ok(token)
     }
@@ -488,10 +628,18 @@ impl Market for SOLMarket {
         // Set error log
         let log_error = format!("SELL-TOKEN:{token}-ERROR");
 
+        // Check token validity, expired locks are not in the market anymore
+        if self.meta.sell_lock_index.is_expired(&token) {
+            self.log(log_error);
+            return Err(SellError::ExpiredToken {
+                expired_token: token,
+            });
+        }
+
         // Check token existence
         let good_meta = match self.meta.locked_sells.get(&*token) {
             None => {
-                log(log_error);
+                self.log(log_error);
                 return Err(SellError::UnrecognizedToken {
                     unrecognized_token: token,
                 });
@@ -499,20 +647,11 @@ impl Market for SOLMarket {
             Some(g) => g,
         };
 
-        // Check token validity
-        let days_since = self.meta.current_day - good_meta.created_on;
-        if days_since > TOKEN_DURATION {
-            log(log_error);
-            return Err(SellError::ExpiredToken {
-                expired_token: token,
-            });
-        }
-
         // Check good is the same as we agreed on lock
         let kind = good.get_kind();
         let expected_kind = good_meta.kind;
         if kind.ne(&expected_kind) {
-            log(log_error);
+            self.log(log_error);
             return Err(SellError::WrongGoodKind {
                 wrong_good_kind: kind,
                 pre_agreed_kind: expected_kind,
@@ -523,20 +662,31 @@ impl Market for SOLMarket {
         let contained_quantity = good.get_qty();
         let pre_agreed_quantity = good_meta.quantity;
         if contained_quantity < pre_agreed_quantity {
-            log(log_error);
+            self.log(log_error);
             return Err(SellError::InsufficientGoodQuantity {
                 contained_quantity,
                 pre_agreed_quantity,
             });
         }
 
+        // The market kept the locked cash until now, so it gets its carry
+        let (locked_price, created_on) = (good_meta.price, good_meta.created_on);
+        self.settle_carry(DEFAULT_GOOD_KIND, locked_price, created_on);
+        let good_meta = self.meta.locked_sells.get(&*token).unwrap();
+        let give_money = Good::new(DEFAULT_GOOD_KIND, good_meta.price);
+
         // Get your good now
         let selling_good = good.split(pre_agreed_quantity).unwrap();
-        let my_good = self.goods.get(&kind).unwrap();
-        let final_quantity = my_good.get_qty() + selling_good.get_qty();
-        self.goods.insert(kind, Good::new(kind, final_quantity));
+        self.apply(MarketEvent::GoodReceived {
+            token: token.clone(),
+            good: self.ticker(kind).to_string(),
+            quantity: selling_good.get_qty(),
+        })
+        .expect("The good sold only adds to the stock");
 
-        let give_money = Good::new(DEFAULT_GOOD_KIND, good_meta.price);
+        // Reset lock
+        let good_meta = self.meta.remove_sell_lock(&token).unwrap();
+        self.count_trade(&good_meta.trader_name);
 
         // Create and sold event
         let e = Event {
@@ -545,28 +695,46 @@ impl Market for SOLMarket {
             quantity: good_meta.quantity,
             price: good_meta.price,
         };
-
-        // Reset lock
-        self.meta.locked_sells.remove(&*token);
+        self.watch(
+            &good_meta.trader_name,
+            ActivityKind::Sell,
+            good_meta.kind,
+            good_meta.quantity,
+            good_meta.price,
+        );
+        self.record_trade(
+            good_meta.kind,
+            good_meta.quantity,
+            good_meta.price,
+            good_meta.market_value,
+            false,
+        );
+        self.record_fee(
+            &good_meta.trader_name,
+            TradeSide::Sell,
+            good_meta.price + good_meta.fee,
+            good_meta.fee,
+        );
+        self.refund_lock_deposit(&good_meta.trader_name, good_meta.deposit);
 
         // Increase need for cash
+        let registry = &self.meta.goods_registry;
+        let cash_ticker = registry.ticker_of(give_money.get_kind());
         self.internal_needs
-            .get_mut(&give_money.get_kind())
+            .get_mut(cash_ticker)
             .unwrap()
-            .increase_need(get_value_good(&give_money.get_kind(), give_money.get_qty()));
+            .increase_need(registry.value_in_default_good(cash_ticker, give_money.get_qty()));
 
         // Decrease need for selling good
+        let selling_ticker = registry.ticker_of(selling_good.get_kind());
         self.internal_needs
-            .get_mut(&selling_good.get_kind())
+            .get_mut(selling_ticker)
             .unwrap()
-            .decrease_need(get_value_good(
-                &selling_good.get_kind(),
-                selling_good.get_qty(),
-            ));
+            .decrease_need(registry.value_in_default_good(selling_ticker, selling_good.get_qty()));
 
         self.notify_everyone(e);
 
-        log(format!("SELL-TOKEN:{token}-OK"));
+        self.log(format!("SELL-TOKEN:{token}-OK"));
 
         Ok(give_money)
     }
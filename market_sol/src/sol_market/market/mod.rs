@@ -1,10 +1,27 @@
+pub(crate) mod auction;
+pub(crate) mod background_flow;
+pub(crate) mod carry;
 pub(crate) mod constructors;
 pub(crate) mod drop;
+pub(crate) mod event_log;
+pub(crate) mod fees;
+pub(crate) mod forwards;
+pub(crate) mod instance;
 pub(crate) mod internal_trading;
+pub(crate) mod lock_deposits;
+pub(crate) mod margin;
 pub(crate) mod market_trait;
 /// Implementation of Notifiablr for SOL Market.
 pub(crate) mod notifiable_trait;
+pub(crate) mod options;
+pub(crate) mod paper;
 /// Implementation of price change strategy.
 pub(crate) mod price_strategies;
+pub(crate) mod quotas;
+pub(crate) mod quotes;
 pub(crate) mod rates;
 pub(crate) mod serde;
+pub(crate) mod snapshots;
+pub(crate) mod stats;
+pub(crate) mod surveillance;
+pub(crate) mod weight_tuning;
@@ -1,10 +1,12 @@
-use crate::sol_market::{SOLMarket, TOKEN_DURATION};
+use crate::sol_market::{
+    domain::good_lock_meta::GoodLockMeta, InstanceEvent, MarketEvent, SOLMarket,
+};
 use unitn_market_2022::{
     event::{
         event::{Event, EventKind},
         notifiable::Notifiable,
     },
-    good::{consts::DEFAULT_GOOD_KIND, good::Good},
+    good::{consts::DEFAULT_GOOD_KIND, good_kind::GoodKind},
 };
 
 impl Notifiable for SOLMarket {
@@ -26,51 +28,94 @@ impl Notifiable for SOLMarket {
             EventKind::Wait => {}
         }
 
+        let days = self.meta.clock.elapsed_days(true);
+        for _ in 0..days {
+            self.advance_day();
+        }
+    }
+}
+
+impl SOLMarket {
+    /// Everything that happens when a day goes by, see `Clock`
+    pub(crate) fn advance_day(&mut self) {
         // Reinstate any good which has an expired token
-        for (_, meta) in self.meta.locked_buys.iter() {
-            let days_since = self.meta.current_day - meta.created_on;
-            if days_since == TOKEN_DURATION {
-                let good = self.goods.get(&meta.kind).unwrap();
-                let replenished_good_qty = good.get_qty() + meta.quantity;
-                self.goods
-                    .insert(meta.kind, Good::new(meta.kind, replenished_good_qty));
-            }
+        for (token, meta) in self.meta.expire_buy_locks() {
+            self.reinstate_lock(token, meta.kind, meta.quantity, meta);
         }
-        for (_, meta) in self.meta.locked_sells.iter() {
-            let days_since = self.meta.current_day - meta.created_on;
-            if days_since == TOKEN_DURATION {
-                let default_good = self.goods.get(&DEFAULT_GOOD_KIND).unwrap();
-                let new_def_good_qty = default_good.get_qty() + meta.price;
-                self.goods.insert(
-                    DEFAULT_GOOD_KIND,
-                    Good::new(DEFAULT_GOOD_KIND, new_def_good_qty),
-                );
-            }
+        for (token, meta) in self.meta.expire_sell_locks() {
+            self.reinstate_lock(token, DEFAULT_GOOD_KIND, meta.price, meta);
         }
 
+        // Goods earn (or lose) their daily interest
+        self.apply_daily_carry();
+
         // Every 100 days update exporters and importers
         if self.meta.current_day % 100 == 0 {
-            let mut goods_vec = Vec::new();
-            for (_, g) in self.goods.iter() {
-                goods_vec.push(g.clone())
-            }
-            self.internal_needs = SOLMarket::set_internal_needs(goods_vec);
+            self.internal_needs = SOLMarket::set_internal_needs(
+                &self.meta.event_log.state().stock,
+                &self.meta.goods_registry,
+            );
         }
 
         // Perform an internal trade if needed
         self.internal_trade_if_needed();
 
+        // Let the synthetic traders trade, if any
+        self.background_flow_if_needed();
+
         //progress one day in any case
-        self.meta.current_day += 1;
+        self.apply_or_log(MarketEvent::DayAdvanced {
+            day: self.meta.current_day + 1,
+        });
+
+        // Clear the orders of the call auction, if it is the day
+        self.clear_auction_if_needed();
+
+        // Settle the forward contracts that reach maturity
+        self.settle_forwards();
+
+        // Exercise the options that reach their expiry day
+        self.exercise_options();
+
+        // Charge the interest on margin debts, and liquidate the accounts
+        // that fell below the maintenance margin
+        self.update_margin_accounts();
+
+        self.record_day();
+
+        // Move the weights of the pricing strategies, if they tune themselves
+        self.tune_weights_if_needed();
+
+        self.autosave_if_needed();
+    }
+
+    /// Gives the market back what an expired lock reserved: the good of a
+    /// buy lock, the cash of a sell lock
+    fn reinstate_lock(&mut self, token: String, kind: GoodKind, quantity: f32, meta: GoodLockMeta) {
+        self.apply_or_log(MarketEvent::LockExpired {
+            token,
+            good: self.ticker(kind).to_string(),
+            quantity,
+        });
+        self.settle_carry(kind, quantity, meta.created_on);
+        self.meta.stats.locks.expired += 1;
+        self.forfeit_lock_deposit(&meta.trader_name, meta.deposit);
     }
-}
 
-impl SOLMarket {
     /// Notify every market including ours of an event
     pub(crate) fn notify_everyone(&mut self, e: Event) {
         for subscriber in &mut self.subscribers {
             subscriber.on_event(e.clone())
         }
+        let instance_event = InstanceEvent {
+            instance_id: self.meta.instance_id,
+            market_name: self.meta.logger.market_name,
+            day: self.meta.current_day,
+            event: e.clone(),
+        };
+        for listener in &mut self.event_listeners {
+            listener(&instance_event)
+        }
         // UNCOMMENT THIS LINE TO NOTIFY YOURSELF TOO, AND NOT ONLY YOUR NEIGHBOURS
         self.on_event(e);
     }
@@ -0,0 +1,322 @@
+use crate::sol_market::{
+    MarketEvent, OptionContract, OptionError, OptionKind, OptionRequest, OptionStatus, SOLMarket,
+};
+use probability::prelude::{Distribution, Gaussian};
+use unitn_market_2022::good::{consts::DEFAULT_GOOD_KIND, good::Good, good_kind::GoodKind};
+
+/// The least premium the market asks, as a share of the strike, so that
+/// options far out of the money are not given away
+const MIN_OPTION_PREMIUM_RATIO: f32 = 0.001;
+
+/// This block contains the European options the market writes on its goods
+/// against DEFAULT_GOOD_KIND.
+///
+/// As for forward contracts, both sides are deposited when the option is
+/// bought: the trader hands over the strike (call) or the good (put), and
+/// the market sets aside the good (call) or the strike (put). On the expiry
+/// day, `on_event` exercises the option if it is in the money, and the
+/// trader can `collect_option` the payout.
+impl SOLMarket {
+    /// Returns the premium, in DEFAULT_GOOD_KIND, the market asks for an
+    /// option.
+    ///
+    /// The premium is the Black price of the option on the forward price of
+    /// the good (`get_forward_buy_price` for calls, `get_forward_sell_price`
+    /// for puts), with the volatility implied by the seasons of the
+    /// stocastic price strategy. The market margin is added on top.
+    pub fn get_option_premium(
+        &self,
+        kind: OptionKind,
+        good_kind: GoodKind,
+        quantity: f32,
+        strike: f32,
+        expiry_day: u32,
+    ) -> Result<f32, OptionError> {
+        if good_kind == DEFAULT_GOOD_KIND {
+            return Err(OptionError::UnsupportedGood { good_kind });
+        }
+        if quantity <= 0.0 {
+            return Err(OptionError::NonPositiveQuantity { quantity });
+        }
+        if strike <= 0.0 {
+            return Err(OptionError::NonPositiveStrike { strike });
+        }
+        let current_day = self.meta.current_day;
+        if expiry_day <= current_day {
+            return Err(OptionError::ExpiryNotInTheFuture {
+                expiry_day,
+                current_day,
+            });
+        }
+        let days_ahead = expiry_day - current_day;
+
+        let forward_price = match kind {
+            OptionKind::Call => self.get_forward_buy_price(good_kind, quantity, days_ahead),
+            OptionKind::Put => self.get_forward_sell_price(good_kind, quantity, days_ahead),
+        }
+        .map_err(OptionError::PriceUnavailable)?;
+        let volatility = self
+            .meta
+            .stocastic_price
+            .borrow()
+            .implied_volatility(self.ticker(good_kind), days_ahead);
+
+        let price = black_price(kind, forward_price, strike, volatility);
+        let premium = price * (1.0 + self.meta.config.market_margin);
+        Ok(premium.max(strike * MIN_OPTION_PREMIUM_RATIO))
+    }
+
+    /// Sells an option to a trader and returns its id.
+    ///
+    /// The premium asked by `get_option_premium` is taken from `payment`, as
+    /// long as it does not exceed the one in the request. The deposit is
+    /// taken from `deposit`: the strike, in DEFAULT_GOOD_KIND, for a call and
+    /// the whole quantity of the good for a put.
+    pub fn write_option(
+        &mut self,
+        request: OptionRequest,
+        payment: &mut Good,
+        deposit: &mut Good,
+    ) -> Result<String, OptionError> {
+        let log_error = format!(
+            "WRITE_OPTION-{}-{:?}-KIND:{}-QUANTITY:{:+e}-STRIKE:{:+e}-EXPIRY:{}-ERROR",
+            request.trader_name,
+            request.kind,
+            request.good_kind,
+            request.quantity,
+            request.strike,
+            request.expiry_day
+        );
+        let result = self.check_option(&request, payment, deposit);
+        let (premium, deposit_kind, deposit_quantity, reserved_kind, reserved) = match result {
+            Ok(amounts) => amounts,
+            Err(e) => {
+                self.log(log_error);
+                return Err(e);
+            }
+        };
+
+        let id = self.new_contract_id(
+            'O',
+            (
+                request.kind,
+                request.good_kind,
+                request.quantity.to_string(),
+                request.strike.to_string(),
+                &request.trader_name,
+            ),
+        );
+
+        let contract = OptionContract {
+            id: id.clone(),
+            trader_name: request.trader_name,
+            kind: request.kind,
+            good_kind: request.good_kind,
+            quantity: request.quantity,
+            strike: request.strike,
+            premium,
+            created_on: self.meta.current_day,
+            expiry_day: request.expiry_day,
+            deposit_kind,
+            deposit: deposit_quantity,
+            reserved_kind,
+            reserved,
+            status: OptionStatus::Open,
+        };
+
+        // Take the premium and the deposit, and set aside the side of the market
+        self.apply(MarketEvent::OptionWritten { contract })
+            .expect("The quantity available was checked");
+        payment.split(premium).unwrap();
+        deposit.split(deposit_quantity).unwrap();
+        self.log(format!("WRITE_OPTION-ID:{id}-PREMIUM:{premium:+e}-OK"));
+        Ok(id)
+    }
+
+    /// Hands over the payout of an option that reached its expiry day: the
+    /// other side if it was exercised, the deposit otherwise
+    pub fn collect_option(&mut self, id: &str) -> Result<Good, OptionError> {
+        let payout = match self.event_state().option_contracts.get(id) {
+            Some(contract) => contract.payout(),
+            None => {
+                self.log(format!("COLLECT_OPTION-ID:{id}-ERROR"));
+                return Err(OptionError::UnrecognizedContract { id: id.to_string() });
+            }
+        };
+        let (kind, quantity) = match payout {
+            Some(payout) => payout,
+            None => {
+                self.log(format!("COLLECT_OPTION-ID:{id}-ERROR"));
+                return Err(OptionError::NothingToCollect { id: id.to_string() });
+            }
+        };
+        self.apply(MarketEvent::OptionCollected { id: id.to_string() })
+            .expect("The option was checked");
+        self.log(format!("COLLECT_OPTION-ID:{id}-OK"));
+        Ok(Good::new(kind, quantity))
+    }
+
+    /// Returns the option with the given id, unless it was collected
+    pub fn option_contract(&self, id: &str) -> Option<&OptionContract> {
+        self.event_state().option_contracts.get(id)
+    }
+
+    /// Returns the options of a trader that were not collected yet, from the
+    /// oldest
+    pub fn option_contracts_of(&self, trader_name: &str) -> Vec<&OptionContract> {
+        let mut contracts: Vec<&OptionContract> = self
+            .event_state()
+            .option_contracts
+            .values()
+            .filter(|contract| contract.trader_name == trader_name)
+            .collect();
+        contracts.sort_by(|a, b| (a.created_on, &a.id).cmp(&(b.created_on, &b.id)));
+        contracts
+    }
+
+    /// Every option that was not collected yet, from the oldest
+    pub(crate) fn option_contracts_by_age(&self) -> Vec<OptionContract> {
+        let mut contracts: Vec<OptionContract> = self
+            .event_state()
+            .option_contracts
+            .values()
+            .cloned()
+            .collect();
+        contracts.sort_by(|a, b| (a.created_on, &a.id).cmp(&(b.created_on, &b.id)));
+        contracts
+    }
+
+    /// Exercises the options that reach their expiry day, if they are in the
+    /// money at today's prices. Called by `on_event` once the day has
+    /// advanced.
+    pub(crate) fn exercise_options(&mut self) {
+        let today = self.meta.current_day;
+        let mut due: Vec<String> = self
+            .event_state()
+            .option_contracts
+            .values()
+            .filter(|c| c.status == OptionStatus::Open && c.expiry_day <= today)
+            .map(|c| c.id.clone())
+            .collect();
+        due.sort();
+        for id in due {
+            let contract = &self.event_state().option_contracts[&id];
+            let in_the_money = match contract.kind {
+                // Buying the good from the market would cost more than the strike
+                OptionKind::Call => {
+                    let rate = self.get_good_buy_exchange_rate(contract.good_kind);
+                    contract.quantity / rate > contract.strike
+                }
+                // Selling the good to the market would pay less than the strike
+                OptionKind::Put => {
+                    let rate = self.get_good_sell_exchange_rate(contract.good_kind);
+                    contract.quantity / rate < contract.strike
+                }
+            };
+            let (status, event) = if in_the_money {
+                // The market keeps the deposit, the trader gets what was set aside
+                let event = MarketEvent::OptionExercised { id: id.clone() };
+                (OptionStatus::Exercised, event)
+            } else {
+                // What was set aside goes back to the stock
+                let event = MarketEvent::OptionExpired { id: id.clone() };
+                (OptionStatus::Expired, event)
+            };
+            if self.apply_or_log(event) {
+                self.log(format!("EXERCISE_OPTION-ID:{id}-{status:?}"));
+            }
+        }
+    }
+
+    /// Validates a request and returns the premium, what the trader deposits
+    /// and what the market sets aside, as (premium, deposit kind, deposit,
+    /// reserved kind, reserved)
+    fn check_option(
+        &self,
+        request: &OptionRequest,
+        payment: &Good,
+        deposit: &Good,
+    ) -> Result<(f32, GoodKind, f32, GoodKind, f32), OptionError> {
+        let premium = self.get_option_premium(
+            request.kind,
+            request.good_kind,
+            request.quantity,
+            request.strike,
+            request.expiry_day,
+        )?;
+        if request.premium < premium {
+            return Err(OptionError::PremiumTooLow {
+                offered: request.premium,
+                required: premium,
+            });
+        }
+        if payment.get_kind() != DEFAULT_GOOD_KIND {
+            return Err(OptionError::WrongPaymentKind {
+                provided: payment.get_kind(),
+            });
+        }
+        if payment.get_qty() < premium {
+            return Err(OptionError::InsufficientPayment {
+                required: premium,
+                provided: payment.get_qty(),
+            });
+        }
+
+        let (deposit_kind, deposit_quantity, reserved_kind, reserved) = match request.kind {
+            OptionKind::Call => (
+                DEFAULT_GOOD_KIND,
+                request.strike,
+                request.good_kind,
+                request.quantity,
+            ),
+            OptionKind::Put => (
+                request.good_kind,
+                request.quantity,
+                DEFAULT_GOOD_KIND,
+                request.strike,
+            ),
+        };
+        if deposit.get_kind() != deposit_kind {
+            return Err(OptionError::WrongDepositKind {
+                expected: deposit_kind,
+                provided: deposit.get_kind(),
+            });
+        }
+        if deposit.get_qty() < deposit_quantity {
+            return Err(OptionError::InsufficientDeposit {
+                required: deposit_quantity,
+                provided: deposit.get_qty(),
+            });
+        }
+        let available = self.get_available_quantity(reserved_kind);
+        if available < reserved {
+            return Err(OptionError::InsufficientMarketQuantity {
+                kind: reserved_kind,
+                required: reserved,
+                available,
+            });
+        }
+        Ok((
+            premium,
+            deposit_kind,
+            deposit_quantity,
+            reserved_kind,
+            reserved,
+        ))
+    }
+}
+
+/// Price of a European option on a good whose forward price is `forward`,
+/// with the given total volatility until expiry (Black's formula, without
+/// discounting: the carry is already in the forward price)
+fn black_price(kind: OptionKind, forward: f32, strike: f32, volatility: f32) -> f32 {
+    let (forward, strike, volatility) = (forward as f64, strike as f64, volatility as f64);
+    let normal = Gaussian::new(0.0, 1.0);
+    let d1 = ((forward / strike).ln() + volatility.powi(2) / 2.0) / volatility;
+    let d2 = d1 - volatility;
+    let price = match kind {
+        OptionKind::Call => forward * normal.distribution(d1) - strike * normal.distribution(d2),
+        OptionKind::Put => strike * normal.distribution(-d2) - forward * normal.distribution(-d1),
+    };
+    price.max(0.0) as f32
+}
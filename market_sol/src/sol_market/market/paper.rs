@@ -0,0 +1,97 @@
+use crate::sol_market::{market::rates::LockTerms, PaperError, PaperTrade, SOLMarket, TradeSide};
+use std::{cell::RefCell, rc::Rc};
+use unitn_market_2022::{
+    good::{consts::DEFAULT_GOOD_KIND, good::Good, good_kind::GoodKind},
+    market::Market,
+};
+
+/// This block contains the paper mode, where traders try locks and trades on
+/// a copy of the market before doing them for real.
+///
+/// The copy starts from the current state of the market: goods, locks,
+/// rates, quotas and fees of the traders. It has no subscribers, does not
+/// log nor save, and its days stand still, so that the prices it gives are
+/// those of today plus the impact of what was traded on it. Nothing done on
+/// the copy reaches the market it was made from.
+impl SOLMarket {
+    /// Returns a copy of the market in paper mode. Use it as any other
+    /// market; call `set_clock` on it to let its days go by.
+    pub fn paper_market(&self) -> Rc<RefCell<SOLMarket>> {
+        Rc::new(RefCell::new(SOLMarket {
+            subscribers: vec![],
+            event_listeners: vec![],
+            meta: self.meta.paper_copy(),
+            internal_needs: self.internal_needs.clone(),
+        }))
+    }
+
+    /// Returns what buying `quantity` of the good would cost the trader,
+    /// and the prices once it is bought. The bid is the lowest the market
+    /// accepts. In auction mode, the trade is filled at once as if the
+    /// auction was off.
+    pub fn paper_buy(
+        &self,
+        kind: GoodKind,
+        quantity: f32,
+        trader_name: &str,
+    ) -> Result<PaperTrade, PaperError> {
+        let paper = self.paper_market();
+        let mut paper = paper.borrow_mut();
+        paper.meta.config.auction.enabled = false;
+        let before = paper.get_quote_ladders(&[quantity]);
+
+        let LockTerms {
+            fee, limit: bid, ..
+        } = paper.buy_terms(kind, quantity, trader_name);
+        let token = paper
+            .lock_buy(kind, quantity, bid, trader_name.to_string())
+            .map_err(PaperError::LockBuy)?;
+        let mut cash = Good::new(DEFAULT_GOOD_KIND, bid);
+        paper.buy(token, &mut cash).map_err(PaperError::Buy)?;
+
+        Ok(PaperTrade {
+            good_kind: kind,
+            side: TradeSide::Buy,
+            quantity,
+            price: bid,
+            fee,
+            before,
+            after: paper.get_quote_ladders(&[quantity]),
+        })
+    }
+
+    /// Returns what selling `quantity` of the good would give the trader,
+    /// and the prices once it is sold. The offer is the highest the market
+    /// accepts. In auction mode, the trade is filled at once as if the
+    /// auction was off.
+    pub fn paper_sell(
+        &self,
+        kind: GoodKind,
+        quantity: f32,
+        trader_name: &str,
+    ) -> Result<PaperTrade, PaperError> {
+        let paper = self.paper_market();
+        let mut paper = paper.borrow_mut();
+        paper.meta.config.auction.enabled = false;
+        let before = paper.get_quote_ladders(&[quantity]);
+
+        let LockTerms {
+            fee, limit: offer, ..
+        } = paper.sell_terms(kind, quantity, trader_name);
+        let token = paper
+            .lock_sell(kind, quantity, offer, trader_name.to_string())
+            .map_err(PaperError::LockSell)?;
+        let mut good = Good::new(kind, quantity);
+        paper.sell(token, &mut good).map_err(PaperError::Sell)?;
+
+        Ok(PaperTrade {
+            good_kind: kind,
+            side: TradeSide::Sell,
+            quantity,
+            price: offer,
+            fee,
+            before,
+            after: paper.get_quote_ladders(&[quantity]),
+        })
+    }
+}
@@ -3,7 +3,7 @@ use unitn_market_2022::good::good_kind::GoodKind;
 
 /// This strategy returns the latest price used by other markets.
 /// An improvement could be tracking which are the market the traders trade the most with and follow them more closely
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) struct OtherMarketsPrice {
     ///Exchange rate EUR-goodkind of the other markets
     exchange_rates: HashMap<GoodKind, f32>,
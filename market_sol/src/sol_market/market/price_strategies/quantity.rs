@@ -1,35 +1,34 @@
-use crate::sol_market::ALL_GOOD_KINDS;
+use crate::sol_market::GoodsRegistry;
 use std::{collections::HashMap, fmt::Debug};
-use unitn_market_2022::good::{good::Good, good_kind::GoodKind};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) struct QuantityPrice {
-    initial_quantities: HashMap<GoodKind, f32>,
+    /// Key is the ticker of the good
+    initial_quantities: HashMap<String, f32>,
+    default_rates: HashMap<String, f32>,
 }
 
 impl QuantityPrice {
-    pub(crate) fn new(goods: Vec<Good>) -> Self {
-        let mut initial_quantities = HashMap::with_capacity(goods.len());
-        for good in goods {
-            initial_quantities.insert(good.get_kind(), good.get_qty());
-        }
-        for g in ALL_GOOD_KINDS {
-            if !initial_quantities.contains_key(&g) {
-                panic!("Should have had key for {g}");
-            }
+    pub(crate) fn new(goods_registry: &GoodsRegistry) -> Self {
+        let initial_quantities = goods_registry
+            .iter()
+            .map(|spec| (spec.ticker.clone(), spec.initial_stock))
+            .collect();
+        let default_rates = goods_registry
+            .iter()
+            .map(|spec| (spec.ticker.clone(), spec.default_rate))
+            .collect();
+        QuantityPrice {
+            initial_quantities,
+            default_rates,
         }
-        QuantityPrice { initial_quantities }
     }
 
-    /// Returns the exchange rate EUR/Good for the given good
-    pub(crate) fn get_exchange_rate(&self, good_kind: &GoodKind, goods: Vec<Good>) -> f32 {
-        let rate = good_kind.get_default_exchange_rate();
-        let quantity_now = goods
-            .iter()
-            .find(|g| g.get_kind() == *good_kind)
-            .unwrap()
-            .get_qty();
-        let initial_quantity = self.initial_quantities.get(good_kind).unwrap();
+    /// Returns the exchange rate EUR/Good for the given good, of which the
+    /// market has `quantity_now`
+    pub(crate) fn get_exchange_rate(&self, ticker: &str, quantity_now: f32) -> f32 {
+        let rate = *self.default_rates.get(ticker).unwrap();
+        let initial_quantity = self.initial_quantities.get(ticker).unwrap();
         let remaining_percentage = quantity_now.max(0.000001) / initial_quantity.max(0.000001);
         rate / remaining_percentage.abs().sqrt()
     }
@@ -1,3 +1,4 @@
+use crate::sol_market::GoodsRegistry;
 use probability::{
     prelude::{Gaussian, Sample},
     source::Source,
@@ -8,13 +9,7 @@ use rand::{
 };
 use rand_chacha::ChaCha20Rng;
 use std::collections::HashMap;
-use unitn_market_2022::good::{
-    consts::{
-        DEFAULT_EUR_USD_EXCHANGE_RATE, DEFAULT_EUR_YEN_EXCHANGE_RATE,
-        DEFAULT_EUR_YUAN_EXCHANGE_RATE, DEFAULT_GOOD_KIND,
-    },
-    good_kind::GoodKind,
-};
+use unitn_market_2022::good::consts::DEFAULT_GOOD_KIND;
 
 ///How long a season can be, max
 pub(crate) const MAX_SEASON_LENGTH: u32 = 65;
@@ -23,18 +18,26 @@ pub(crate) const MIN_VARIATION_IN_SEASON: f32 = 0.3;
 pub(crate) const MAX_NOISE_CLAMP: f32 = 1.0;
 pub(crate) const MIN_NOISE_CLAMP: f32 = -MAX_NOISE_CLAMP;
 
-///Holds all the info that we need to determine the price of a good on a given day
-#[derive(Debug)]
+///Holds all the info that we need to determine the price of a good on a given day.
+///Goods are known by their ticker.
+#[derive(Debug, Clone)]
 pub(crate) struct StocasticPrice {
-    last_price: HashMap<GoodKind, f32>,
-    day_price: HashMap<GoodKind, (u32, f32)>,
-    pub(crate) seasons: HashMap<GoodKind, Season>,
-    rand: ChaCha20Rngg,
-    gaus: Gaussian,
+    /// The ticker of DEFAULT_GOOD_KIND, whose price is always one
+    cash: String,
+    last_price: HashMap<String, f32>,
+    day_price: HashMap<String, (u32, f32)>,
+    pub(crate) seasons: HashMap<String, Season>,
+    /// One generator per good, so that the price of a good does not depend
+    /// on the order the goods are priced in
+    rand: HashMap<String, ChaCha20Rngg>,
+    /// The distribution of the variations of each good, from its volatility
+    gaus: HashMap<String, Gaussian>,
+    /// The rate each good starts from
+    default_rates: HashMap<String, f32>,
     max_increase_in_season: f32,
     max_decrease_per_season: f32,
     //debug_only
-    pub(crate) past_seasons: HashMap<GoodKind, Vec<Season>>,
+    pub(crate) past_seasons: HashMap<String, Vec<Season>>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -45,16 +48,20 @@ pub(crate) struct Season {
     ending_price: f32,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct ChaCha20Rngg {
     rng: ChaCha20Rng,
 }
 
 impl ChaCha20Rngg {
-    fn new() -> Self {
-        ChaCha20Rngg {
-            rng: ChaCha20Rng::from_entropy(),
-        }
+    fn new(seed: Option<u64>, stream: u64) -> Self {
+        let mut rng = match seed {
+            //Use a different seed than the other generators seeded with the same seed
+            Some(seed) => ChaCha20Rng::seed_from_u64(seed.wrapping_add(1)),
+            None => ChaCha20Rng::from_entropy(),
+        };
+        rng.set_stream(stream);
+        ChaCha20Rngg { rng }
     }
 
     fn gen_range<T, R>(&mut self, range: R) -> T
@@ -113,15 +120,35 @@ impl Season {
 }
 
 impl StocasticPrice {
-    pub(crate) fn new() -> Self {
-        let mut rng = ChaCha20Rngg::new();
+    pub(crate) fn new(seed: Option<u64>, goods_registry: &GoodsRegistry) -> Self {
+        let mut rng = ChaCha20Rngg::new(seed, 0);
         let max_decrease_per_season = rng.gen_range(MIN_VARIATION_IN_SEASON..0.95);
         let max_increase_in_season = rng.gen_range(MIN_VARIATION_IN_SEASON..5.0);
+        let rand = goods_registry
+            .iter()
+            .enumerate()
+            .map(|(i, spec)| (spec.ticker.clone(), ChaCha20Rngg::new(seed, i as u64 + 1)))
+            .collect();
+        let gaus = goods_registry
+            .iter()
+            .map(|spec| {
+                (
+                    spec.ticker.clone(),
+                    Gaussian::new(0.0, spec.volatility as f64),
+                )
+            })
+            .collect();
+        let default_rates = goods_registry
+            .iter()
+            .map(|spec| (spec.ticker.clone(), spec.default_rate))
+            .collect();
         StocasticPrice {
+            cash: goods_registry.ticker_of(DEFAULT_GOOD_KIND).to_string(),
             last_price: HashMap::new(),
             seasons: HashMap::new(),
-            rand: rng,
-            gaus: Gaussian::new(0.0, 0.25),
+            rand,
+            gaus,
+            default_rates,
             max_decrease_per_season,
             max_increase_in_season,
             past_seasons: HashMap::new(),
@@ -129,13 +156,13 @@ impl StocasticPrice {
         }
     }
 
-    ///Returns the price of (a unit of) the given goodkind on the given day
-    pub(crate) fn get_rate(&mut self, good_kind: &GoodKind, day: u32) -> f32 {
+    ///Returns the price of (a unit of) the given good on the given day
+    pub(crate) fn get_rate(&mut self, ticker: &str, day: u32) -> f32 {
         //If we talk about the default good, its price is one.
-        if *good_kind == DEFAULT_GOOD_KIND {
+        if ticker == self.cash {
             1.0
         } else {
-            let day_price_opt = self.day_price.get(good_kind);
+            let day_price_opt = self.day_price.get(ticker);
             let already_have_price_for_today =
                 day_price_opt.map(|tuple| tuple.0 == day).unwrap_or(false);
             //If we already generated the price for today, we return that
@@ -143,68 +170,93 @@ impl StocasticPrice {
                 day_price_opt.unwrap().1
             } else {
                 //Else we generate a new one
-                let random = self.gaus.sample(&mut self.rand);
+                let gaus = self.gaus.get(ticker).expect("Should be initialized");
+                let random = gaus.sample(self.rand.get_mut(ticker).expect("Should be initialized"));
 
-                let current_season = self.get_current_season(good_kind, day);
+                let current_season = self.get_current_season(ticker, day);
                 let price = current_season.get_price(day, random);
-                self.day_price.insert(*good_kind, (day, price));
+                self.day_price.insert(ticker.to_string(), (day, price));
                 price
             }
         }
     }
 
-    fn latest_price(&self, gk: &GoodKind) -> f32 {
-        let historic = self.last_price.get(gk);
-        let default_price: f32 = match gk {
-            &DEFAULT_GOOD_KIND => 1.0,
-            GoodKind::YEN => DEFAULT_EUR_YEN_EXCHANGE_RATE,
-            GoodKind::USD => DEFAULT_EUR_USD_EXCHANGE_RATE,
-            GoodKind::YUAN => DEFAULT_EUR_YUAN_EXCHANGE_RATE,
-        };
-        *historic.unwrap_or(&default_price)
+    /// Returns how much the price of a good can move in the next `days`
+    /// days, as the standard deviation of its relative change.
+    ///
+    /// A season moves the price by a sample of the Gaussian of the good,
+    /// clamped to `[-max_decrease_per_season, max_increase_in_season]`, and
+    /// lasts `(MIN_SEASON_LENGTH + MAX_SEASON_LENGTH) / 2` days on average.
+    /// On top of the trend, the price of a day is off by the (clamped) noise
+    /// times the change of the season.
+    pub(crate) fn implied_volatility(&self, ticker: &str, days: u32) -> f32 {
+        if ticker == self.cash {
+            return 0.0;
+        }
+        let volatility = self
+            .gaus
+            .get(ticker)
+            .expect("Should be initialized")
+            .sigma() as f32;
+        let season_change = volatility
+            .min(self.max_decrease_per_season)
+            .min(self.max_increase_in_season);
+        let average_season_length = (MIN_SEASON_LENGTH + MAX_SEASON_LENGTH) as f32 / 2.0;
+        let trend_variance = season_change.powi(2) * days as f32 / average_season_length;
+        let noise_variance = (volatility.min(MAX_NOISE_CLAMP) * season_change).powi(2);
+        (trend_variance + noise_variance).sqrt()
+    }
+
+    fn latest_price(&self, ticker: &str) -> f32 {
+        let historic = self.last_price.get(ticker);
+        let default_price = self
+            .default_rates
+            .get(ticker)
+            .expect("Should be initialized");
+        *historic.unwrap_or(default_price)
     }
 
-    /// Returns the current season, for the given goodkind on the given day.
-    /// If, for the given goodkind, the current season is ended (or not
+    /// Returns the current season, for the given good on the given day.
+    /// If, for the given good, the current season is ended (or not
     /// present), a new season is created (and returned).
-    fn get_current_season(&mut self, good_kind: &GoodKind, day: u32) -> &Season {
-        let season_opt = self.seasons.get(good_kind);
+    fn get_current_season(&mut self, ticker: &str, day: u32) -> &Season {
+        let season_opt = self.seasons.get(ticker);
         let need_new_season = match season_opt {
             Some(s) => {
                 let finished = day >= s.end();
                 if finished {
-                    self.last_price.insert(*good_kind, s.ending_price);
+                    self.last_price.insert(ticker.to_string(), s.ending_price);
                 }
                 finished
             }
             None => true,
         };
         if need_new_season {
-            let latest_price = self.latest_price(good_kind);
+            let latest_price = self.latest_price(ticker);
             let new_season = Season::new(
                 day,
-                &mut self.rand,
-                self.gaus,
+                self.rand.get_mut(ticker).expect("Should be initialized"),
+                *self.gaus.get(ticker).expect("Should be initialized"),
                 latest_price,
                 self.max_decrease_per_season,
                 self.max_increase_in_season,
             );
-            let s = self.seasons.get_mut(good_kind);
+            let s = self.seasons.get_mut(ticker);
             if let Some(ended_season) = s {
-                let vec_opt = self.past_seasons.get_mut(good_kind);
+                let vec_opt = self.past_seasons.get_mut(ticker);
                 match vec_opt {
                     Some(v) => v.push(*ended_season),
                     None => {
                         let v: Vec<Season> = Vec::from_iter([*ended_season]);
-                        self.past_seasons.insert(*good_kind, v);
+                        self.past_seasons.insert(ticker.to_string(), v);
                     }
                 }
             }
-            self.seasons.insert(*good_kind, new_season);
+            self.seasons.insert(ticker.to_string(), new_season);
         }
         let season = self
             .seasons
-            .get(good_kind)
+            .get(ticker)
             .expect("A valid season must exist now");
         season
     }
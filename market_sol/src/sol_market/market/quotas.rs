@@ -0,0 +1,147 @@
+use crate::sol_market::{QuotaError, QuotaUsage, SOLMarket};
+use unitn_market_2022::good::good_kind::GoodKind;
+
+/// This block contains the quotas of the traders, on top of the lock limit.
+///
+/// Only locks and trades can be limited: the getters of the `Market` trait
+/// do not say who is asking. A trade is counted for the trader who made its
+/// lock. Since every lock and trade advances the market by a day, they are
+/// counted over the last `quota_window_days` days.
+impl SOLMarket {
+    /// Returns how much of its quotas a trader is using
+    pub fn quota_usage(&self, trader_name: &str) -> QuotaUsage {
+        QuotaUsage {
+            locks_in_window: self.locks_in_window(trader_name),
+            locked_value: self.locked_value(trader_name),
+        }
+    }
+
+    /// Returns why the last lock of a trader rejected by the quotas was
+    /// rejected
+    pub fn last_quota_rejection(&self, trader_name: &str) -> Option<&QuotaError> {
+        self.meta.quota_rejections.get(trader_name)
+    }
+
+    /// Counts a lock request of a trader and checks it against the quotas.
+    /// `price` is the bid (or offer) of the lock.
+    pub(crate) fn check_lock_quotas(
+        &mut self,
+        trader_name: &str,
+        kind: GoodKind,
+        quantity: f32,
+        price: f32,
+    ) -> Result<(), QuotaError> {
+        self.record_request(trader_name);
+        let locks_in_window = self.locks_in_window(trader_name);
+
+        let result = self.check_quotas(trader_name, kind, quantity, price, locks_in_window);
+        if let Err(e) = &result {
+            self.log(format!("QUOTA-{trader_name}-REJECTED:{e}"));
+            self.meta
+                .quota_rejections
+                .insert(trader_name.to_string(), e.clone());
+        }
+        result
+    }
+
+    /// Counts a trade of a trader. Trades are never rejected by the quotas,
+    /// or the locks they redeem would be stuck, but they count toward
+    /// `MarketConfig::max_locks_per_window` for the next locks.
+    pub(crate) fn count_trade(&mut self, trader_name: &str) {
+        self.record_request(trader_name);
+    }
+
+    /// Records a request made today and forgets the ones out of the window
+    fn record_request(&mut self, trader_name: &str) {
+        let today = self.meta.current_day;
+        let oldest_day = self.oldest_day_in_window();
+        let days = self
+            .meta
+            .lock_requests
+            .entry(trader_name.to_string())
+            .or_default();
+        days.push_back(today);
+        days.retain(|day| *day > oldest_day);
+    }
+
+    fn check_quotas(
+        &self,
+        trader_name: &str,
+        kind: GoodKind,
+        quantity: f32,
+        price: f32,
+        locks_in_window: u32,
+    ) -> Result<(), QuotaError> {
+        let config = &self.meta.config;
+
+        // The default rate keeps working when the market runs out of the good
+        let value = self
+            .meta
+            .goods_registry
+            .value_in_default_good(self.ticker(kind), quantity);
+        if !(quantity > 0.0 && value >= config.min_lock_value) {
+            return Err(QuotaError::DustLock {
+                kind,
+                quantity,
+                value,
+                min_value: config.min_lock_value,
+            });
+        }
+
+        if let Some(limit) = config.max_locks_per_window {
+            if locks_in_window > limit {
+                return Err(QuotaError::TooManyLocks {
+                    trader_name: trader_name.to_string(),
+                    limit,
+                    window_days: config.quota_window_days,
+                });
+            }
+        }
+
+        if let Some(limit) = config.max_locked_value {
+            let locked_value = self.locked_value(trader_name);
+            if locked_value + price > limit {
+                return Err(QuotaError::LockedValueExceeded {
+                    trader_name: trader_name.to_string(),
+                    locked_value,
+                    requested_value: price,
+                    limit,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Lock requests and trades of a trader in the current window
+    fn locks_in_window(&self, trader_name: &str) -> u32 {
+        let oldest_day = self.oldest_day_in_window();
+        match self.meta.lock_requests.get(trader_name) {
+            Some(days) => days.iter().filter(|day| **day > oldest_day).count() as u32,
+            None => 0,
+        }
+    }
+
+    /// The last day before the window of `quota_window_days` days
+    fn oldest_day_in_window(&self) -> u32 {
+        self.meta
+            .current_day
+            .saturating_sub(self.meta.config.quota_window_days)
+    }
+
+    /// Value, in DEFAULT_GOOD_KIND, of the unexpired buy and sell locks of a
+    /// trader, and of its auction orders waiting for the next clearing
+    fn locked_value(&self, trader_name: &str) -> f32 {
+        let pending_orders = self
+            .event_state()
+            .pending_orders_of(trader_name)
+            .map(|order| order.limit);
+        self.meta
+            .locked_buys
+            .values()
+            .chain(self.meta.locked_sells.values())
+            .filter(|lock| lock.trader_name == trader_name)
+            .map(|lock| lock.price)
+            .chain(pending_orders)
+            .sum()
+    }
+}
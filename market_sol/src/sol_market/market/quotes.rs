@@ -0,0 +1,147 @@
+use crate::sol_market::{Quote, QuoteLadder, SOLMarket, TradeSide, ALL_GOOD_KINDS};
+use std::collections::HashMap;
+use unitn_market_2022::{
+    good::{consts::DEFAULT_GOOD_KIND, good_kind::GoodKind},
+    market::MarketGetterError,
+};
+
+impl SOLMarket {
+    /// Returns, for every good, the buy and sell price of each of the given
+    /// quantities.
+    ///
+    /// The exchange rates are computed once per good, so all the quotes come
+    /// from the same state of the market. Calling `get_buy_price` and
+    /// `get_sell_price` in a loop would recompute every strategy each time.
+    pub fn get_quote_ladders(&self, quantities: &[f32]) -> HashMap<GoodKind, QuoteLadder> {
+        self.get_forward_quote_ladders(quantities, 0)
+    }
+
+    /// Same as `get_quote_ladders`, but the prices are for goods delivered
+    /// (and paid) `days_ahead` days from now. See `get_forward_buy_price`.
+    pub fn get_forward_quote_ladders(
+        &self,
+        quantities: &[f32],
+        days_ahead: u32,
+    ) -> HashMap<GoodKind, QuoteLadder> {
+        let mut ladders = HashMap::with_capacity(ALL_GOOD_KINDS.len());
+        for good_kind in ALL_GOOD_KINDS {
+            let forward_factor = self.forward_factor(good_kind, days_ahead);
+            let exchange_rate_buy = self.get_good_buy_exchange_rate(good_kind);
+            let exchange_rate_sell = self.get_good_sell_exchange_rate(good_kind);
+            let available_quantity = self.get_available_quantity(good_kind);
+            let quotes = quantities
+                .iter()
+                .map(|&quantity| {
+                    let buy_price = buy_price_at_rate(
+                        good_kind,
+                        quantity,
+                        exchange_rate_buy,
+                        available_quantity,
+                    )
+                    .map(|price| price * forward_factor);
+                    let sell_price = sell_price_at_rate(quantity, exchange_rate_sell)
+                        .map(|price| price * forward_factor);
+                    // Forward contracts do not pay fees
+                    let fee = |side, price: &Result<f32, _>| match price {
+                        Ok(price) if days_ahead == 0 => self.get_fee(None, side, *price),
+                        _ => 0.0,
+                    };
+                    let buy_fee = fee(TradeSide::Buy, &buy_price);
+                    let sell_fee = fee(TradeSide::Sell, &sell_price);
+                    Quote {
+                        quantity,
+                        buy_price: buy_price.map(|price| price + buy_fee),
+                        sell_price: sell_price.map(|price| (price - sell_fee).max(0.0)),
+                        buy_fee,
+                        sell_fee,
+                    }
+                })
+                .collect();
+            ladders.insert(
+                good_kind,
+                QuoteLadder {
+                    good_kind,
+                    days_ahead,
+                    exchange_rate_buy,
+                    exchange_rate_sell,
+                    available_quantity,
+                    quotes,
+                },
+            );
+        }
+        ladders
+    }
+
+    /// Returns the price of `quantity` of `kind` delivered `days_ahead` days
+    /// from now, paid on delivery.
+    ///
+    /// It is the price `get_buy_price` returns today, adjusted by the carry
+    /// of the two goods: waiting earns the interest of DEFAULT_GOOD_KIND on
+    /// the cash, but loses the interest of `kind` on the goods.
+    pub fn get_forward_buy_price(
+        &self,
+        kind: GoodKind,
+        quantity: f32,
+        days_ahead: u32,
+    ) -> Result<f32, MarketGetterError> {
+        let price = buy_price_at_rate(
+            kind,
+            quantity,
+            self.get_good_buy_exchange_rate(kind),
+            self.get_available_quantity(kind),
+        )?;
+        Ok(price * self.forward_factor(kind, days_ahead))
+    }
+
+    /// Same as `get_forward_buy_price`, but for selling to the market
+    pub fn get_forward_sell_price(
+        &self,
+        kind: GoodKind,
+        quantity: f32,
+        days_ahead: u32,
+    ) -> Result<f32, MarketGetterError> {
+        let price = sell_price_at_rate(quantity, self.get_good_sell_exchange_rate(kind))?;
+        Ok(price * self.forward_factor(kind, days_ahead))
+    }
+
+    /// How much the price of `kind` in DEFAULT_GOOD_KIND changes for delivery
+    /// `days_ahead` days from now
+    fn forward_factor(&self, kind: GoodKind, days_ahead: u32) -> f32 {
+        let registry = &self.meta.goods_registry;
+        registry.carry_factor(self.ticker(DEFAULT_GOOD_KIND), days_ahead)
+            / registry.carry_factor(self.ticker(kind), days_ahead)
+    }
+}
+
+/// Price in DEFAULT_GOOD_KIND the trader pays to buy `quantity` of `kind`,
+/// given the buy exchange rate (EUR/kind) and how much of it is available
+pub(crate) fn buy_price_at_rate(
+    kind: GoodKind,
+    quantity: f32,
+    exchange_rate_eur_good: f32,
+    available_quantity: f32,
+) -> Result<f32, MarketGetterError> {
+    if quantity.is_sign_negative() {
+        return Err(MarketGetterError::NonPositiveQuantityAsked);
+    }
+    if quantity > available_quantity {
+        return Err(MarketGetterError::InsufficientGoodQuantityAvailable {
+            requested_good_kind: kind,
+            requested_good_quantity: quantity,
+            available_good_quantity: available_quantity,
+        });
+    }
+    Ok(quantity / exchange_rate_eur_good)
+}
+
+/// Price in DEFAULT_GOOD_KIND the market pays for `quantity` of a good,
+/// given the sell exchange rate (EUR/kind)
+pub(crate) fn sell_price_at_rate(
+    quantity: f32,
+    exchange_rate_eur_good: f32,
+) -> Result<f32, MarketGetterError> {
+    if quantity.is_sign_negative() {
+        return Err(MarketGetterError::NonPositiveQuantityAsked);
+    }
+    Ok(quantity / exchange_rate_eur_good)
+}
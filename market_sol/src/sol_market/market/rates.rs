@@ -1,12 +1,44 @@
-use crate::sol_market::{domain::strategy_name::StrategyName, SOLMarket, MARKET_MARGIN};
+use crate::sol_market::{domain::strategy_name::StrategyName, SOLMarket, TradeSide};
 use unitn_market_2022::good::{consts::DEFAULT_GOOD_KIND, good_kind::GoodKind};
 
+/// What a lock of a trader is worth to the market, see `SOLMarket::buy_terms`
+pub(crate) struct LockTerms {
+    /// The value of the quantity at the rate of the market, in DEFAULT_GOOD_KIND
+    pub value: f32,
+    /// The fee of the trader on `value`
+    pub fee: f32,
+    /// The lowest bid (or highest offer) the market accepts
+    pub limit: f32,
+}
+
 impl SOLMarket {
-    /// Exchange rate (EUR/goodkind) for this good
-    fn get_exchange_rate(&self, good_kind: GoodKind) -> f32 {
-        let stocastic_rate = self.get_stocastic_rate(good_kind);
-        let quantity_rate = self.get_quantity_rate(good_kind);
-        let other_markets_rate = self.get_other_rate(good_kind);
+    /// Exchange rate (good/EUR) for the good with this ticker.
+    /// It is computed once for the same day, stock and rate of the other
+    /// markets, see `RateCache`.
+    fn get_exchange_rate(&self, ticker: &str) -> f32 {
+        let day = self.meta.current_day;
+        let stock = self.stock(ticker);
+        let other_markets_rate = self.get_other_rate(ticker);
+        let cached = self
+            .meta
+            .rate_cache
+            .borrow()
+            .get(ticker, day, stock, other_markets_rate);
+        if let Some(rate) = cached {
+            return rate;
+        }
+        let rate = self.compute_exchange_rate(ticker, other_markets_rate);
+        self.meta
+            .rate_cache
+            .borrow_mut()
+            .insert(ticker, day, stock, other_markets_rate, rate);
+        rate
+    }
+
+    /// The weighted average of the rates of the pricing strategies
+    fn compute_exchange_rate(&self, ticker: &str, other_markets_rate: f32) -> f32 {
+        let stocastic_rate = self.get_stocastic_rate(ticker);
+        let quantity_rate = self.get_quantity_rate(ticker);
         //Compute the weighted average of the three
         let stochastic_weight: f32 = *self
             .meta
@@ -27,41 +59,79 @@ impl SOLMarket {
         weighted_sum / total_weight
     }
 
-    pub(crate) fn get_other_rate(&self, good_kind: GoodKind) -> f32 {
-        self.meta.other_markets.get_exchange_rate(&good_kind)
+    /// The rate of the other markets. Goods that are not traded have no
+    /// other market: their rate stays at the default one.
+    pub(crate) fn get_other_rate(&self, ticker: &str) -> f32 {
+        let registry = &self.meta.goods_registry;
+        match registry.kind_of(ticker) {
+            Some(kind) => self.meta.other_markets.get_exchange_rate(&kind),
+            None => registry.spec(ticker).default_rate,
+        }
     }
 
-    pub(crate) fn get_quantity_rate(&self, good_kind: GoodKind) -> f32 {
+    pub(crate) fn get_quantity_rate(&self, ticker: &str) -> f32 {
         self.meta
             .quantity_price
-            .get_exchange_rate(&good_kind, Vec::from_iter(self.goods.values().cloned()))
+            .get_exchange_rate(ticker, self.stock(ticker))
     }
 
-    pub(crate) fn get_stocastic_rate(&self, good_kind: GoodKind) -> f32 {
+    pub(crate) fn get_stocastic_rate(&self, ticker: &str) -> f32 {
         self.meta
             .stocastic_price
             .borrow_mut()
-            .get_rate(&good_kind, self.meta.current_day)
+            .get_rate(ticker, self.meta.current_day)
     }
 
     /// Return the rate applied when the trader wants to BUY the good from this market
     /// The rate is EUR/goodkind
     pub(crate) fn get_good_buy_exchange_rate(&self, good_kind: GoodKind) -> f32 {
-        if good_kind == DEFAULT_GOOD_KIND {
+        self.buy_rate(self.ticker(good_kind))
+    }
+
+    /// Return the rate applied when the trader wants to SELL the good to this market
+    /// The rate is EUR/goodkind
+    pub(crate) fn get_good_sell_exchange_rate(&self, good_kind: GoodKind) -> f32 {
+        self.sell_rate(self.ticker(good_kind))
+    }
+
+    /// Same as `get_good_buy_exchange_rate`, for the good with this ticker
+    pub(crate) fn buy_rate(&self, ticker: &str) -> f32 {
+        if ticker == self.ticker(DEFAULT_GOOD_KIND) {
             1.0
         } else {
             //we divide, since the rate is eur/kind and not kind/eur
-            self.get_exchange_rate(good_kind)
+            self.get_exchange_rate(ticker)
         }
     }
 
-    /// Return the rate applied when the trader wants to SELL the good to this market
-    /// The rate is EUR/goodkind
-    pub(crate) fn get_good_sell_exchange_rate(&self, good_kind: GoodKind) -> f32 {
-        if good_kind == DEFAULT_GOOD_KIND {
+    /// Same as `get_good_sell_exchange_rate`, for the good with this ticker
+    pub(crate) fn sell_rate(&self, ticker: &str) -> f32 {
+        if ticker == self.ticker(DEFAULT_GOOD_KIND) {
             1.0
         } else {
-            self.get_exchange_rate(good_kind) / (1.0 + MARKET_MARGIN)
+            self.get_exchange_rate(ticker) / (1.0 + self.meta.config.market_margin)
+        }
+    }
+
+    /// The terms `lock_buy` checks the bid of the trader against
+    pub(crate) fn buy_terms(&self, kind: GoodKind, quantity: f32, trader_name: &str) -> LockTerms {
+        let value = quantity / self.get_good_buy_exchange_rate(kind);
+        let fee = self.get_fee(Some(trader_name), TradeSide::Buy, value);
+        LockTerms {
+            value,
+            fee,
+            limit: value * self.spread_penalty(trader_name) + fee,
+        }
+    }
+
+    /// The terms `lock_sell` checks the offer of the trader against
+    pub(crate) fn sell_terms(&self, kind: GoodKind, quantity: f32, trader_name: &str) -> LockTerms {
+        let value = quantity / self.get_good_sell_exchange_rate(kind);
+        let fee = self.get_fee(Some(trader_name), TradeSide::Sell, value);
+        LockTerms {
+            value,
+            fee,
+            limit: value / self.spread_penalty(trader_name) - fee,
         }
     }
 }
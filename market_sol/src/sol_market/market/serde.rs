@@ -1,12 +1,16 @@
 use crate::sol_market::{
-    SOLMarket, ALL_GOOD_KINDS,
+    GoodDefinition, GoodsRegistry, MarketDefinition, SOLMarket, SOLMarketError, WeightsDefinition,
+    ALL_GOOD_KINDS, MARKET_DEFINITION_SCHEMA_VERSION,
     {
         domain::strategy_name::{StrategyName, ALL_STRATEGY_NAMES},
         misc::banner::BANNER,
     },
 };
-use std::{collections::HashMap, fs, path::Path};
-use unitn_market_2022::good::{good::Good, good_kind::GoodKind};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+use unitn_market_2022::good::consts::DEFAULT_GOOD_KIND;
 
 mod sol_file_prefixes {
     pub const COMMENT_PREFIX: &str = "#";
@@ -14,54 +18,217 @@ mod sol_file_prefixes {
     pub const WEIGHT_PREFIX: &str = "weight ";
 }
 
+/// The formats a market can be stored in. It is chosen from the extension of
+/// the file: `.toml` and `.json` are the serde-based formats, anything else is
+/// the legacy `.sol` format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MarketFileFormat {
+    Sol,
+    Toml,
+    Json,
+}
+
+impl MarketFileFormat {
+    pub(crate) fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => MarketFileFormat::Toml,
+            Some("json") => MarketFileFormat::Json,
+            _ => MarketFileFormat::Sol,
+        }
+    }
+}
+
 /// This block contains the logic to serialize/deserialize the market to and from a file
 impl SOLMarket {
-    /// If the market knows about a file, it means it read the state from there.
-    /// This function updates such file with the current state of the market.
-    pub(crate) fn write_to_file(&self) {
-        match &self.meta.file_path {
-            Some(pts) => {
-                let path = Path::new(pts);
-                let exists = Path::exists(path);
-                //If needed, create target file
-                if !exists {
-                    let parent = Path::parent(path);
-                    //If needed, create parent directory
-                    if let Some(directory_path) = parent {
-                        let parent_exists = Path::exists(directory_path);
-                        if !parent_exists {
-                            match fs::create_dir_all(directory_path) {
-                                Ok(_) => {}
-                                Err(_) => {
-                                    panic!("Could not create directory for SOL market status file");
-                                }
-                            }
+    /// Returns the definition of a market equivalent to this one: same
+    /// goods, weights, configuration and seed.
+    ///
+    /// Locks are not part of a definition, so the goods they reserve are
+    /// counted back in the stock, as if the locks had expired.
+    pub fn to_definition(&self) -> MarketDefinition {
+        let goods = self
+            .meta
+            .goods_registry
+            .iter()
+            .map(|spec| GoodDefinition {
+                kind: spec.kind,
+                //Goods named after their kind do not need a ticker
+                ticker: match spec.kind {
+                    Some(kind) if kind.to_string() == spec.ticker => None,
+                    _ => Some(spec.ticker.clone()),
+                },
+                quantity: self.stock(&spec.ticker) + self.locked_quantity(&spec.ticker),
+                exchange_rate: Some(self.sell_rate(&spec.ticker)),
+                default_rate: Some(spec.default_rate),
+                volatility: Some(spec.volatility),
+                daily_interest_rate: Some(spec.daily_interest_rate),
+            })
+            .collect();
+        MarketDefinition {
+            schema_version: MARKET_DEFINITION_SCHEMA_VERSION,
+            seed: self.meta.seed,
+            current_day: Some(self.meta.current_day),
+            goods,
+            weights: WeightsDefinition::from_map(&self.meta.weights),
+            config: self.meta.config.clone(),
+            forward_contracts: self.forward_contracts_by_age(),
+            option_contracts: self.option_contracts_by_age(),
+            margin_accounts: self.margin_accounts_by_name(),
+        }
+    }
+
+    /// The quantity of a good reserved by the pending locks: goods for the
+    /// buy locks, DEFAULT_GOOD_KIND for the sell locks
+    fn locked_quantity(&self, ticker: &str) -> f32 {
+        let locked_goods = self
+            .meta
+            .locked_buys
+            .values()
+            .filter(|lock| self.ticker(lock.kind) == ticker)
+            .map(|lock| lock.quantity);
+        let locked_cash = self
+            .meta
+            .locked_sells
+            .values()
+            .filter(|_| self.ticker(DEFAULT_GOOD_KIND) == ticker)
+            .map(|lock| lock.price);
+        locked_goods.chain(locked_cash).sum()
+    }
+
+    /// Reads and parses the state file at the provided path
+    pub(crate) fn read_file(path: &Path) -> Result<MarketDefinition, SOLMarketError> {
+        MarketDefinition::from_file(path)
+    }
+}
+
+impl MarketDefinition {
+    /// Reads a definition from a file. The format is chosen from the
+    /// extension of the file (see [`MarketDefinition::write_to_file`]).
+    pub fn from_file(path: &Path) -> Result<Self, SOLMarketError> {
+        let contents = fs::read_to_string(path).map_err(|source| SOLMarketError::Io {
+            path: path.to_path_buf(),
+            action: "read",
+            source,
+        })?;
+        let definition =
+            match MarketFileFormat::from_path(path) {
+                MarketFileFormat::Sol => parse_sol_file(path, &contents)?,
+                MarketFileFormat::Toml => {
+                    toml::from_str::<MarketDefinition>(&contents).map_err(|e| {
+                        SOLMarketError::Parse {
+                            path: path.to_path_buf(),
+                            line: e.line_col().map(|(line, _)| line + 1).unwrap_or(0),
+                            reason: e.to_string(),
                         }
-                    }
-                } else {
-                    //The file already exists, let's try to rename it
-                    let mut new_name = String::from(path.to_str().unwrap_or("./sol.sol"));
-                    let date_now = chrono::offset::Local::now();
-                    let last_dot = new_name.rfind('.').unwrap_or(new_name.len() - 1);
-                    new_name.insert_str(last_dot, format!("{}", date_now.timestamp()).as_str());
-                    let _ = fs::rename(path, new_name);
+                    })?
                 }
-                //Get string contents
-                let contents = self.serialize_to_file_string();
-                match fs::write(path, contents) {
-                    Ok(_) => {
-                        //Success
-                    }
-                    Err(_) => {
-                        println!("SOL market could not write to its file. Next run contents will not be resotred");
-                    }
+                MarketFileFormat::Json => serde_json::from_str::<MarketDefinition>(&contents)
+                    .map_err(|e| SOLMarketError::Parse {
+                        path: path.to_path_buf(),
+                        line: e.line(),
+                        reason: e.to_string(),
+                    })?,
+            };
+        if definition.schema_version != MARKET_DEFINITION_SCHEMA_VERSION {
+            return Err(SOLMarketError::UnsupportedSchemaVersion {
+                path: path.to_path_buf(),
+                found: definition.schema_version,
+            });
+        }
+        Ok(definition)
+    }
+
+    /// Writes the definition to a file, atomically replacing it. The format is chosen
+    /// from the extension of the file: `.toml` and `.json` keep everything,
+    /// any other extension uses the legacy `.sol` format, which only keeps
+    /// goods and weights.
+    ///
+    /// The goods set aside by contracts and the collateral of margin
+    /// accounts are not part of `goods`, so a definition with contracts or
+    /// margin accounts can not be written as `.sol`: that is a
+    /// `SOLMarketError::LossySolFile`, and the file is left as it is.
+    ///
+    /// Reading a file with `from_file` and writing it with another extension
+    /// converts it from one format to the other.
+    pub fn write_to_file(&self, path: &Path) -> Result<(), SOLMarketError> {
+        let serialization_error = |e: String| SOLMarketError::Io {
+            path: path.to_path_buf(),
+            action: "serialize the market for",
+            source: io::Error::new(io::ErrorKind::InvalidData, e),
+        };
+        let contents = match MarketFileFormat::from_path(path) {
+            MarketFileFormat::Sol => {
+                let lost = self.lost_in_sol_file();
+                if !lost.is_empty() {
+                    return Err(SOLMarketError::LossySolFile {
+                        path: path.to_path_buf(),
+                        lost: lost.join(", "),
+                    });
                 }
+                self.to_sol_string()
             }
-            None => {}
+            MarketFileFormat::Toml => {
+                toml::to_string_pretty(self).map_err(|e| serialization_error(e.to_string()))?
+            }
+            MarketFileFormat::Json => serde_json::to_string_pretty(self)
+                .map_err(|e| serialization_error(e.to_string()))?,
+        };
+        //Write to a temporary file first and then move it in place, so that
+        //a crash never leaves a half-written file behind
+        let mut temp_path = path.as_os_str().to_owned();
+        temp_path.push(".tmp");
+        let temp_path = PathBuf::from(temp_path);
+        fs::write(&temp_path, contents).map_err(|source| SOLMarketError::Io {
+            path: temp_path.clone(),
+            action: "write",
+            source,
+        })?;
+        fs::rename(&temp_path, path).map_err(|source| SOLMarketError::Io {
+            path: path.to_path_buf(),
+            action: "replace",
+            source,
+        })
+    }
+
+    /// What the `.sol` format can not keep, and would be lost with the
+    /// goods it sets aside
+    fn lost_in_sol_file(&self) -> Vec<String> {
+        let mut lost = vec![];
+        if !self.forward_contracts.is_empty() {
+            lost.push(format!(
+                "{} forward contracts",
+                self.forward_contracts.len()
+            ));
+        }
+        if !self.option_contracts.is_empty() {
+            lost.push(format!("{} options", self.option_contracts.len()));
         }
+        //Accounts that hold nothing have nothing to lose
+        let accounts = self
+            .margin_accounts
+            .iter()
+            .filter(|account| {
+                account
+                    .balances
+                    .iter()
+                    .any(|balance| balance.collateral != 0.0 || balance.debt != 0.0)
+            })
+            .count();
+        if accounts > 0 {
+            lost.push(format!("{accounts} margin accounts"));
+        }
+        lost
+    }
+
+    /// Parses a definition in the legacy `.sol` format
+    pub fn from_sol_str(contents: &str) -> Result<Self, SOLMarketError> {
+        parse_sol_file(&PathBuf::from("<string>"), contents)
     }
 
-    fn serialize_to_file_string(&self) -> String {
+    /// Serializes the definition in the legacy `.sol` format. Only goods
+    /// and weights are part of that format, everything else is lost,
+    /// including the tickers of the goods of the protocol.
+    pub fn to_sol_string(&self) -> String {
         let mut contents = String::new();
         for banner_line in BANNER {
             let mut s = String::from(sol_file_prefixes::COMMENT_PREFIX);
@@ -71,25 +238,30 @@ impl SOLMarket {
             contents.push_str(&s);
         }
         contents.push('\n');
-        for good in self.goods.values() {
-            contents.push_str(sol_file_prefixes::GOOD_PREFIX);
-            let kind = match good.get_kind() {
-                GoodKind::EUR => "EUR",
-                GoodKind::YEN => "YEN",
-                GoodKind::USD => "USD",
-                GoodKind::YUAN => "YUAN",
+        for good in self.goods.iter() {
+            //The goods of the protocol are known by their kind, the others by
+            //their ticker. A good without either could not be read back.
+            let name = match (good.kind, &good.ticker) {
+                (Some(kind), _) => kind.to_string(),
+                (None, Some(ticker)) => ticker.clone(),
+                (None, None) => continue,
             };
-            contents.push_str(kind);
+            contents.push_str(sol_file_prefixes::GOOD_PREFIX);
+            contents.push_str(&name);
             contents.push(' ');
-            contents.push_str(good.get_qty().to_string().as_str());
+            contents.push_str(good.quantity.to_string().as_str());
             contents.push(' ');
-            let exchange_rate = self.get_good_sell_exchange_rate(good.get_kind());
+            let exchange_rate = good
+                .exchange_rate
+                .or(good.default_rate)
+                .or_else(|| good.kind.map(|kind| kind.get_default_exchange_rate()))
+                .unwrap_or(1.0);
             contents.push_str(exchange_rate.to_string().as_str());
             contents.push('\n');
         }
         contents.push('\n');
         for s in ALL_STRATEGY_NAMES {
-            let weight_opt = self.meta.weights.get(&s);
+            let weight_opt = self.weights.get(&s);
             if let Some(weight) = weight_opt {
                 contents.push_str(sol_file_prefixes::WEIGHT_PREFIX);
                 contents.push_str(s.to_string().as_str());
@@ -100,175 +272,99 @@ impl SOLMarket {
         }
         contents
     }
+}
 
-    pub(crate) fn read_weights_from_file(path: &Path) -> HashMap<StrategyName, f32> {
-        use sol_file_prefixes::*;
+/// Parses the contents of a `.sol` file. `path` is only used for diagnostics.
+///
+/// Comments, empty lines and lines with unknown prefixes are ignored, but
+/// `good` and `weight` lines must be well formed.
+fn parse_sol_file(path: &Path, contents: &str) -> Result<MarketDefinition, SOLMarketError> {
+    use sol_file_prefixes::*;
 
-        if let Some(contents) = get_file_content(path) {
-            let mut strategy_weights: HashMap<StrategyName, f32> = HashMap::new();
-            for (line_number, line) in contents.split('\n').into_iter().enumerate() {
-                if line.starts_with(COMMENT_PREFIX) {
-                    continue;
-                } else if line.starts_with(WEIGHT_PREFIX) {
-                    let parts = line.replace(WEIGHT_PREFIX, "");
-                    let parts: Vec<&str> = parts.split(' ').collect();
-                    //TODO:Create map with all strategies
-                    let strategy_name = parts.first().and_then(|name| match *name {
-                        "Stocastic" => Some(StrategyName::Stocastic),
-                        "Quantity" => Some(StrategyName::Quantity),
-                        "Others" => Some(StrategyName::Others),
-                        _ => None,
-                    });
-                    let weight: Option<f32> = parts.get(1).and_then(|quantity_str| {
-                        let weight_result = quantity_str.parse();
-                        match weight_result {
-                            Ok(w) => Some(w),
-                            Err(_) => {
-                                println!("Line {line_number} should have a valid good quantity, but has '{quantity_str}'");
-                                None
-                            }
-                        }
-                    });
-                    match strategy_name {
-                        Some(strategy_name) => match weight {
-                            Some(weight) => {
-                                strategy_weights.insert(strategy_name, weight);
-                            }
-                            None => break,
-                        },
-                        None => break,
-                    }
-                }
-            }
-            let stocastic_weight = strategy_weights.get(&StrategyName::Stocastic);
-            let quantity_weight = strategy_weights.get(&StrategyName::Quantity);
-            let others_weight = strategy_weights.get(&StrategyName::Others);
-            let all_are_something = [stocastic_weight, quantity_weight, others_weight]
-                .into_iter()
-                .all(|i| i.is_some());
-            if all_are_something {
-                let mut m = HashMap::with_capacity(ALL_STRATEGY_NAMES.len());
-                m.insert(StrategyName::Stocastic, *stocastic_weight.unwrap());
-                m.insert(StrategyName::Quantity, *quantity_weight.unwrap());
-                m.insert(StrategyName::Others, *others_weight.unwrap());
-                return m;
-            }
-        }
-        HashMap::new()
-    }
+    let parse_error = |line_number: usize, reason: String| SOLMarketError::Parse {
+        path: path.to_path_buf(),
+        line: line_number + 1,
+        reason,
+    };
 
-    /// Reads the file at the provided path and optionally returns vector with the goods
-    /// that the SOL Market represented in that file has.
-    ///
-    /// If there is an error reading or parsing the file, None is returned.
-    pub(crate) fn read_quantities_from_file(path: &Path) -> Option<Vec<Good>> {
-        use sol_file_prefixes::*;
-
-        if let Some(contents) = get_file_content(path) {
-            let mut reading_failed = false;
-            let mut goodmap: HashMap<GoodKind, f32> = HashMap::new();
-            for (line_number, line) in contents.split('\n').into_iter().enumerate() {
-                if line.starts_with(COMMENT_PREFIX) {
-                    continue;
-                } else if line.starts_with(GOOD_PREFIX) {
-                    let parts = line.replace(GOOD_PREFIX, "");
-                    let parts: Vec<&str> = parts.split(' ').collect();
-                    let good_kind = match parts.first() {
-                        Some(ticket) => match *ticket {
-                            "USD" => GoodKind::USD,
-                            "YEN" => GoodKind::YEN,
-                            "EUR" => GoodKind::EUR,
-                            "YUAN" => GoodKind::YUAN,
-                            _ => {
-                                println!("Line {line_number} should have a known good kind, but has '{ticket}'");
-                                reading_failed = true;
-                                break;
-                            }
-                        },
-                        None => {
-                            println!(
-                                "Line {line_number} should declare a good in the correct format"
-                            );
-                            reading_failed = true;
-                            break;
-                        }
-                    };
-                    let quantity: f32 = match parts.get(1) {
-                        Some(quantity_str) => {
-                            let qty_result = quantity_str.parse();
-                            match qty_result {
-                                Ok(qt) => qt,
-                                Err(_) => {
-                                    println!("Line {line_number} should have a valid good quantity, but has '{quantity_str}'");
-                                    reading_failed = true;
-                                    break;
-                                }
-                            }
-                        }
-                        None => {
-                            println!(
-                                "Line {line_number} should declare a good in the correct format"
-                            );
-                            reading_failed = true;
-                            break;
-                        }
-                    };
-                    if quantity < 0.0 {
-                        println!("Line {line_number} should not declare a negative good quanity");
-                    }
-                    goodmap.insert(good_kind, quantity);
+    let mut goods: Vec<GoodDefinition> = Vec::with_capacity(ALL_GOOD_KINDS.len());
+    let mut weights = WeightsDefinition::default();
+    let registry = GoodsRegistry::with_protocol_goods();
+    for (line_number, line) in contents.lines().enumerate() {
+        if line.starts_with(COMMENT_PREFIX) {
+            continue;
+        } else if let Some(rest) = line.strip_prefix(GOOD_PREFIX) {
+            let parts: Vec<&str> = rest.split(' ').collect();
+            let ticker = match parts.first() {
+                Some(ticker) if !ticker.is_empty() => *ticker,
+                _ => {
+                    return Err(parse_error(
+                        line_number,
+                        String::from("Should declare a good in the correct format"),
+                    ))
                 }
-            }
-            let usd_qty = *goodmap.get(&GoodKind::USD).unwrap_or(&-1.0);
-            if usd_qty < 0.0 {
-                println!("Invalid quantity of usd in the SOL market file");
-                reading_failed = true;
-            }
-            let eur_qty = *goodmap.get(&GoodKind::EUR).unwrap_or(&-1.0);
-            if eur_qty < 0.0 {
-                println!("Invalid quantity of eur in the SOL market file");
-                reading_failed = true;
-            }
-            let yen_qty = *goodmap.get(&GoodKind::YEN).unwrap_or(&-1.0);
-            if yen_qty < 0.0 {
-                println!("Invalid quantity of yen in the SOL market file");
-                reading_failed = true;
-            }
-            let yuan_qty = *goodmap.get(&GoodKind::YUAN).unwrap_or(&-1.0);
-            if yuan_qty < 0.0 {
-                println!("Invalid quantity of yuan in the SOL market file");
-                reading_failed = true;
-            }
-
-            if reading_failed {
-                None
-            } else {
-                let mut goods_vec = Vec::with_capacity(ALL_GOOD_KINDS.len());
-                for gk in ALL_GOOD_KINDS {
-                    let quantity = match gk {
-                        GoodKind::EUR => eur_qty,
-                        GoodKind::YEN => yen_qty,
-                        GoodKind::USD => usd_qty,
-                        GoodKind::YUAN => yuan_qty,
-                    };
-                    goods_vec.push(Good::new(gk, quantity));
+            };
+            let quantity: f32 = match parts.get(1) {
+                Some(quantity_str) => quantity_str.parse().map_err(|_| {
+                    parse_error(
+                        line_number,
+                        format!("Should have a valid good quantity, but has '{quantity_str}'"),
+                    )
+                })?,
+                None => {
+                    return Err(parse_error(
+                        line_number,
+                        String::from("Should declare a good in the correct format"),
+                    ))
                 }
-                Some(goods_vec)
+            };
+            if quantity < 0.0 {
+                return Err(parse_error(
+                    line_number,
+                    format!("Should not declare a negative good quantity, but has {quantity}"),
+                ));
             }
-        } else {
-            None
+            //The exchange rate is only informative, we do not validate it
+            let exchange_rate = parts.get(2).and_then(|rate_str| rate_str.parse().ok());
+            //Goods that are not of the protocol are only simulated
+            let good = match registry.kind_of(ticker) {
+                Some(kind) => GoodDefinition::new(kind, quantity),
+                None => GoodDefinition::instrument(ticker, quantity),
+            };
+            goods.push(GoodDefinition {
+                exchange_rate,
+                ..good
+            });
+        } else if let Some(rest) = line.strip_prefix(WEIGHT_PREFIX) {
+            let parts: Vec<&str> = rest.split(' ').collect();
+            let strategy_name = match parts.first() {
+                Some(&"Stocastic") => StrategyName::Stocastic,
+                Some(&"Quantity") => StrategyName::Quantity,
+                Some(&"Others") => StrategyName::Others,
+                other => {
+                    return Err(parse_error(
+                        line_number,
+                        format!(
+                            "Should have a known strategy, but has '{}'",
+                            other.unwrap_or(&"")
+                        ),
+                    ))
+                }
+            };
+            let weight_str = parts.get(1).unwrap_or(&"");
+            let weight: f32 = weight_str.parse().map_err(|_| {
+                parse_error(
+                    line_number,
+                    format!("Should have a valid weight, but has '{weight_str}'"),
+                )
+            })?;
+            weights.set(&strategy_name, weight);
         }
     }
-}
 
-fn get_file_content(path: &Path) -> Option<String> {
-    let pts = path.to_str().unwrap_or("invalid path");
-    let exists = Path::exists(path);
-    if !exists {
-        println!("SOL Market file at {} does not seem to exist", pts);
-        return None;
-    }
-    let contents = fs::read_to_string(path)
-        .unwrap_or_else(|_| panic!("Should have been able to read the file at {pts}"));
-    Some(contents)
+    Ok(MarketDefinition {
+        goods,
+        weights,
+        ..Default::default()
+    })
 }
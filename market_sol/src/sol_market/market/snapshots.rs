@@ -0,0 +1,176 @@
+use crate::sol_market::{MarketDefinition, SOLMarket, SOLMarketError};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// This block contains the logic to save the state of the market to its
+/// state file, keeping the previous versions around as snapshots.
+///
+/// Snapshots live next to the state file and are named after it, with the
+/// time they were taken (in milliseconds) before the extension, e.g.,
+/// `market.snapshot-1674000000000.toml` for `market.toml`. Other files in
+/// the directory are never touched.
+impl SOLMarket {
+    /// Saves the current state of the market to its state file.
+    ///
+    /// The previous content of the state file, if any, is kept as a
+    /// snapshot, then old snapshots are deleted according to
+    /// `MarketConfig::snapshots_to_keep`. The state file is replaced
+    /// atomically, so it is never left half-written.
+    pub fn save_snapshot(&self) -> Result<(), SOLMarketError> {
+        let path = self.state_file_path()?;
+        //If needed, create parent directory
+        if let Some(directory_path) = path.parent() {
+            if !directory_path.as_os_str().is_empty() && !directory_path.exists() {
+                fs::create_dir_all(directory_path).map_err(|source| SOLMarketError::Io {
+                    path: directory_path.to_path_buf(),
+                    action: "create the directory",
+                    source,
+                })?;
+            }
+        }
+        if path.exists() {
+            //Never overwrite a snapshot taken in the same millisecond
+            let mut time = self.meta.clock.now().timestamp_millis();
+            while snapshot_path(&path, time).exists() {
+                time += 1;
+            }
+            let snapshot_path = snapshot_path(&path, time);
+            fs::copy(&path, &snapshot_path).map_err(|source| SOLMarketError::Io {
+                path: path.clone(),
+                action: "back up",
+                source,
+            })?;
+        }
+        self.to_definition().write_to_file(&path)?;
+        if let Some(to_keep) = self.meta.config.snapshots_to_keep {
+            let snapshots = self.snapshots()?;
+            let to_delete = snapshots.len().saturating_sub(to_keep);
+            for snapshot in snapshots.into_iter().take(to_delete) {
+                fs::remove_file(&snapshot).map_err(|source| SOLMarketError::Io {
+                    path: snapshot,
+                    action: "delete the old snapshot",
+                    source,
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Brings the market back to the state saved in its state file, i.e., to
+    /// the last `save_snapshot`.
+    ///
+    /// Goods, weights, configuration and day are restored. All the pending
+    /// locks are forgotten, since they were made against a different state:
+    /// the goods they reserved at the time of the save are back in stock.
+    pub fn restore_snapshot(&mut self) -> Result<(), SOLMarketError> {
+        let path = self.state_file_path()?;
+        self.restore_snapshot_from(&path)
+    }
+
+    /// Same as `restore_snapshot`, but reads the state from the given file,
+    /// e.g., one of the older `snapshots`. The state file of the market
+    /// does not change.
+    pub fn restore_snapshot_from(&mut self, snapshot: &Path) -> Result<(), SOLMarketError> {
+        let definition = MarketDefinition::from_file(snapshot)?;
+        let restored = Self::try_new_from_definition_and_path(&definition, None)?;
+        let mut restored = restored.borrow_mut();
+        std::mem::swap(&mut self.internal_needs, &mut restored.internal_needs);
+        std::mem::swap(&mut self.meta, &mut restored.meta);
+        //Keep the state file, and make sure the discarded state is not saved
+        std::mem::swap(&mut self.meta.file_path, &mut restored.meta.file_path);
+        //Same for the identity of the market
+        std::mem::swap(&mut self.meta.instance_id, &mut restored.meta.instance_id);
+        std::mem::swap(&mut self.meta.logger, &mut restored.meta.logger);
+        std::mem::swap(&mut self.meta.clock, &mut restored.meta.clock);
+        self.log(format!(
+            "RESTORE_SNAPSHOT-PATH:{}-DAY:{}",
+            snapshot.display(),
+            self.meta.current_day
+        ));
+        Ok(())
+    }
+
+    /// Returns the snapshots of the state file, from the oldest to the newest
+    pub fn snapshots(&self) -> Result<Vec<PathBuf>, SOLMarketError> {
+        let path = self.state_file_path()?;
+        let directory = match path.parent() {
+            Some(directory) if !directory.as_os_str().is_empty() => directory.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        let entries = match fs::read_dir(&directory) {
+            Ok(entries) => entries,
+            //No directory, no snapshots
+            Err(_) => return Ok(vec![]),
+        };
+        let mut snapshots: Vec<(i64, PathBuf)> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let time = snapshot_time(&path, &entry.path())?;
+                Some((time, entry.path()))
+            })
+            .collect();
+        snapshots.sort();
+        Ok(snapshots.into_iter().map(|(_, path)| path).collect())
+    }
+
+    /// Saves a snapshot if autosave is enabled and it is time to do so.
+    /// Called once per day, errors are only logged.
+    pub(crate) fn autosave_if_needed(&self) {
+        let every = match self.meta.config.autosave_every_days {
+            Some(every) if every > 0 => every,
+            _ => return,
+        };
+        let days_since_last_autosave = self.meta.current_day % every;
+        if self.meta.file_path.is_none() || days_since_last_autosave != 0 {
+            return;
+        }
+        if let Err(e) = self.save_snapshot() {
+            self.log(format!("AUTOSAVE-ERROR:{e}"));
+        }
+    }
+
+    fn state_file_path(&self) -> Result<PathBuf, SOLMarketError> {
+        self.meta
+            .file_path
+            .as_ref()
+            .map(PathBuf::from)
+            .ok_or(SOLMarketError::NoStateFile)
+    }
+}
+
+/// Between the stem of the state file and the time, in the name of a snapshot
+const SNAPSHOT_INFIX: &str = ".snapshot-";
+
+/// Splits a file name in stem and extension (with the dot), e.g.,
+/// `market.toml` in `market` and `.toml`
+fn split_file_name(path: &Path) -> (String, String) {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    match name.rfind('.') {
+        Some(dot) if dot > 0 => (name[..dot].to_string(), name[dot..].to_string()),
+        _ => (name, String::new()),
+    }
+}
+
+fn snapshot_path(state_file: &Path, time: i64) -> PathBuf {
+    let (stem, extension) = split_file_name(state_file);
+    state_file.with_file_name(format!("{stem}{SNAPSHOT_INFIX}{time}{extension}"))
+}
+
+/// If `candidate` is a snapshot of `state_file`, returns when it was taken
+fn snapshot_time(state_file: &Path, candidate: &Path) -> Option<i64> {
+    let (stem, extension) = split_file_name(state_file);
+    let name = candidate.file_name()?.to_str()?;
+    let time = name
+        .strip_prefix(&stem)?
+        .strip_prefix(SNAPSHOT_INFIX)?
+        .strip_suffix(&extension)?;
+    if time.is_empty() || !time.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    time.parse().ok()
+}
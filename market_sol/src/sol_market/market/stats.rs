@@ -0,0 +1,170 @@
+use crate::sol_market::{
+    domain::{
+        report::{ValuePoint, VALUE_HISTORY_DAYS},
+        strategy_name::ALL_STRATEGY_NAMES,
+    },
+    GoodRates, GoodVolume, MarketReport, SOLMarket, SOLMarketError, StrategyWeight,
+};
+use std::{fs, path::Path};
+use unitn_market_2022::good::{consts::DEFAULT_GOOD_KIND, good_kind::GoodKind};
+
+/// This block contains the statistics of the market, and the report built
+/// from them.
+///
+/// The counters start when the market is created: they are not saved in the
+/// state file. When the market is dropped, the report is written next to its
+/// state file (see `report_path`).
+impl SOLMarket {
+    /// Returns the statistics and health of the market
+    pub fn report(&self) -> MarketReport {
+        let stats = &self.meta.stats;
+        let mut volumes: Vec<GoodVolume> = stats.volumes.values().copied().collect();
+        volumes.sort_by_key(|v| {
+            self.meta
+                .goods_registry
+                .traded()
+                .position(|(kind, _)| kind == v.kind)
+        });
+        let weights = ALL_STRATEGY_NAMES
+            .iter()
+            .map(|strategy| StrategyWeight {
+                strategy: strategy.to_string(),
+                weight: self.meta.weights.get(strategy).copied().unwrap_or(1.0),
+            })
+            .collect();
+        let rates = self
+            .meta
+            .goods_registry
+            .traded()
+            .map(|(kind, spec)| GoodRates {
+                kind,
+                stocastic: self.get_stocastic_rate(&spec.ticker),
+                quantity: self.get_quantity_rate(&spec.ticker),
+                others: self.get_other_rate(&spec.ticker),
+                buy: self.get_good_buy_exchange_rate(kind),
+                sell: self.get_good_sell_exchange_rate(kind),
+            })
+            .collect();
+        MarketReport {
+            market_name: self.meta.logger.market_name.to_string(),
+            day: self.meta.current_day,
+            total_value: self.total_value(),
+            value_history: stats.value_history.iter().copied().collect(),
+            realized_margin: stats.realized_margin,
+            volumes,
+            locks: stats.locks,
+            internal_trades: stats.internal_trades,
+            internal_traded_value: stats.internal_traded_value,
+            background_trades: stats.background_trades,
+            background_traded_value: stats.background_traded_value,
+            fees: stats.fees,
+            weights,
+            rates,
+        }
+    }
+
+    /// Writes the report to a file: JSON if its extension is `.json`, text
+    /// otherwise
+    pub fn write_report(&self, path: &Path) -> Result<(), SOLMarketError> {
+        let report = self.report();
+        let contents = match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => report.to_json().map_err(|e| SOLMarketError::Io {
+                path: path.to_path_buf(),
+                action: "serialize the report for",
+                source: e.into(),
+            })?,
+            _ => report.to_string(),
+        };
+        fs::write(path, contents).map_err(|source| SOLMarketError::Io {
+            path: path.to_path_buf(),
+            action: "write",
+            source,
+        })
+    }
+
+    /// Where the report is written when the market is dropped: next to the
+    /// state file, e.g., `market.report.json` for `market.toml`. `None` if
+    /// the market has no state file.
+    pub fn report_path(&self) -> Option<std::path::PathBuf> {
+        let state_file = Path::new(self.meta.file_path.as_ref()?);
+        let stem = state_file.file_stem()?.to_string_lossy();
+        Some(state_file.with_file_name(format!("{stem}.report.json")))
+    }
+
+    /// Writes the report to `report_path`, if any. Errors are only logged.
+    pub(crate) fn write_report_if_needed(&self) {
+        if let Some(path) = self.report_path() {
+            if let Err(e) = self.write_report(&path) {
+                self.log(format!("WRITE_REPORT-ERROR:{e}"));
+            }
+        }
+    }
+
+    /// Records the value of the market at the end of the day
+    pub(crate) fn record_day(&mut self) {
+        let point = ValuePoint {
+            day: self.meta.current_day,
+            value: self.total_value(),
+        };
+        let history = &mut self.meta.stats.value_history;
+        history.push_back(point);
+        while history.len() > VALUE_HISTORY_DAYS {
+            history.pop_front();
+        }
+    }
+
+    /// Records a redeemed lock. `market_value` is the value of the good at
+    /// the rate of the market when it was locked.
+    pub(crate) fn record_trade(
+        &mut self,
+        kind: GoodKind,
+        quantity: f32,
+        price: f32,
+        market_value: f32,
+        bought_by_trader: bool,
+    ) {
+        let stats = &mut self.meta.stats;
+        stats.locks.redeemed += 1;
+        let volume = stats.volumes.entry(kind).or_insert(GoodVolume {
+            kind,
+            trades: 0,
+            bought: 0.0,
+            sold: 0.0,
+            bought_value: 0.0,
+            sold_value: 0.0,
+        });
+        volume.trades += 1;
+        if bought_by_trader {
+            volume.bought += quantity;
+            volume.bought_value += price;
+            stats.realized_margin += price - market_value;
+        } else {
+            volume.sold += quantity;
+            volume.sold_value += price;
+            stats.realized_margin += market_value - price;
+        }
+    }
+
+    /// Value of all the goods of the market, locked ones included, at the
+    /// default rates
+    fn total_value(&self) -> f32 {
+        let registry = &self.meta.goods_registry;
+        // Expired locks are back in the goods of the market, and not in
+        // the locks anymore
+        let locked_goods = self
+            .meta
+            .locked_buys
+            .values()
+            .map(|lock| registry.value_in_default_good(self.ticker(lock.kind), lock.quantity));
+        let locked_cash =
+            self.meta.locked_sells.values().map(|lock| {
+                registry.value_in_default_good(self.ticker(DEFAULT_GOOD_KIND), lock.price)
+            });
+        registry
+            .iter()
+            .map(|spec| registry.value_in_default_good(&spec.ticker, self.stock(&spec.ticker)))
+            .chain(locked_goods)
+            .chain(locked_cash)
+            .sum()
+    }
+}
@@ -0,0 +1,238 @@
+use crate::sol_market::{
+    domain::surveillance::{Activity, ActivityKind},
+    SOLMarket, SurveillanceFlag, SuspiciousPattern,
+};
+use unitn_market_2022::good::good_kind::GoodKind;
+
+/// This block contains the surveillance of the market: every lock and trade
+/// is recorded per trader, and the activity of the last
+/// `SurveillanceConfig::window_days` days is checked for wash trading, lock
+/// spamming and pump and dump.
+///
+/// Flags are logged. With `SurveillanceConfig::penalties`, a flagged trader
+/// gets worse prices for a while, and is banned from locking when flagged
+/// too often. Flags and penalties are not saved in the state file.
+impl SOLMarket {
+    /// Returns the flags raised against a trader, from the oldest
+    pub fn surveillance_flags(&self, trader_name: &str) -> Vec<SurveillanceFlag> {
+        self.meta
+            .surveillance
+            .traders
+            .get(trader_name)
+            .map(|record| record.flags.clone())
+            .unwrap_or_default()
+    }
+
+    /// Returns whether a trader is currently banned from locking
+    pub fn is_banned(&self, trader_name: &str) -> bool {
+        let today = self.meta.current_day;
+        let banned_until = self
+            .meta
+            .surveillance
+            .traders
+            .get(trader_name)
+            .and_then(|record| record.banned_until);
+        matches!(banned_until, Some(until) if today < until)
+    }
+
+    /// How much worse the prices of a trader are, as a factor on the bid the
+    /// market asks (and a divisor on the offer it accepts). 1 if the trader
+    /// is not penalized.
+    pub(crate) fn spread_penalty(&self, trader_name: &str) -> f32 {
+        let today = self.meta.current_day;
+        let penalized_until = self
+            .meta
+            .surveillance
+            .traders
+            .get(trader_name)
+            .and_then(|record| record.penalized_until);
+        if matches!(penalized_until, Some(until) if today < until) {
+            1.0 + self.meta.config.surveillance.penalty_spread
+        } else {
+            1.0
+        }
+    }
+
+    /// Records a lock or a trade, and checks the trader for suspicious
+    /// patterns
+    pub(crate) fn watch(
+        &mut self,
+        trader_name: &str,
+        kind: ActivityKind,
+        good_kind: GoodKind,
+        quantity: f32,
+        price: f32,
+    ) {
+        if !self.meta.config.surveillance.enabled {
+            return;
+        }
+        let today = self.meta.current_day;
+        let oldest_day = today.saturating_sub(self.meta.config.surveillance.window_days);
+        let market_stock = self.get_available_quantity(good_kind);
+        let record = self
+            .meta
+            .surveillance
+            .traders
+            .entry(trader_name.to_string())
+            .or_default();
+        record.activity.retain(|activity| activity.day > oldest_day);
+        record.activity.push_back(Activity {
+            day: today,
+            kind,
+            good_kind,
+            quantity,
+            price,
+            market_stock,
+        });
+
+        let mut suspicions = vec![];
+        if let Some(details) = self.wash_trading(trader_name, good_kind) {
+            suspicions.push((SuspiciousPattern::WashTrading, details));
+        }
+        if let Some(details) = self.lock_spam(trader_name) {
+            suspicions.push((SuspiciousPattern::LockSpam, details));
+        }
+        if kind == ActivityKind::Sell {
+            if let Some(details) = self.pump_and_dump(trader_name, good_kind, quantity, price) {
+                suspicions.push((SuspiciousPattern::PumpAndDump, details));
+            }
+        }
+        for (pattern, details) in suspicions {
+            self.flag(trader_name, pattern, details);
+        }
+    }
+
+    /// Raises a flag, unless the same one was raised in the window, and
+    /// applies the penalties
+    fn flag(&mut self, trader_name: &str, pattern: SuspiciousPattern, details: String) {
+        let config = self.meta.config.surveillance.clone();
+        let today = self.meta.current_day;
+        let oldest_day = today.saturating_sub(config.window_days);
+        let record = self
+            .meta
+            .surveillance
+            .traders
+            .get_mut(trader_name)
+            .expect("Activity should be recorded first");
+        let already_flagged = record
+            .flags
+            .iter()
+            .any(|flag| flag.pattern == pattern && flag.day > oldest_day);
+        if already_flagged {
+            return;
+        }
+        self.meta.logger.log(
+            self.meta.clock.now(),
+            format!("SURVEILLANCE-{trader_name}-FLAG:{pattern}-{details}"),
+        );
+        record.flags.push(SurveillanceFlag {
+            trader_name: trader_name.to_string(),
+            pattern,
+            day: today,
+            details,
+        });
+
+        if !config.penalties {
+            return;
+        }
+        record.penalized_until = Some(today + config.penalty_days);
+        self.meta.logger.log(
+            self.meta.clock.now(),
+            format!(
+                "SURVEILLANCE-{trader_name}-PENALTY-SPREAD:{:+e}-UNTIL:{}",
+                config.penalty_spread,
+                today + config.penalty_days
+            ),
+        );
+        let recent_flags = record
+            .flags
+            .iter()
+            .filter(|flag| flag.day > oldest_day)
+            .count() as u32;
+        if recent_flags >= config.ban_after_flags {
+            record.banned_until = Some(today + config.ban_days);
+            self.meta.logger.log(
+                self.meta.clock.now(),
+                format!(
+                    "SURVEILLANCE-{trader_name}-BAN-UNTIL:{}",
+                    today + config.ban_days
+                ),
+            );
+        }
+    }
+
+    /// Many buys and sells of the same good that (almost) cancel out
+    fn wash_trading(&self, trader_name: &str, good_kind: GoodKind) -> Option<String> {
+        let config = &self.meta.config.surveillance;
+        let (mut trades, mut bought, mut sold) = (0, 0.0, 0.0);
+        for activity in self.window_activity(trader_name) {
+            if activity.good_kind != good_kind {
+                continue;
+            }
+            match activity.kind {
+                ActivityKind::Buy => bought += activity.quantity,
+                ActivityKind::Sell => sold += activity.quantity,
+                _ => continue,
+            }
+            trades += 1;
+        }
+        let traded: f32 = bought + sold;
+        let net = (bought - sold).abs();
+        let washing = trades >= config.min_wash_trades
+            && bought > 0.0
+            && sold > 0.0
+            && net <= traded * config.wash_net_ratio;
+        washing.then(|| format!("KIND:{good_kind}-TRADES:{trades}-TRADED:{traded:+e}-NET:{net:+e}"))
+    }
+
+    /// Many locks that do not turn into trades
+    fn lock_spam(&self, trader_name: &str) -> Option<String> {
+        let config = &self.meta.config.surveillance;
+        let (mut locks, mut trades) = (0, 0);
+        for activity in self.window_activity(trader_name) {
+            match activity.kind {
+                ActivityKind::LockBuy | ActivityKind::LockSell => locks += 1,
+                ActivityKind::Buy | ActivityKind::Sell => trades += 1,
+            }
+        }
+        let spamming = locks >= config.spam_min_locks
+            && trades as f32 <= locks as f32 * config.spam_max_fill_ratio;
+        spamming.then(|| format!("LOCKS:{locks}-TRADES:{trades}"))
+    }
+
+    /// A sale of a good the trader bought a large share of, at a much higher
+    /// price than it paid
+    fn pump_and_dump(
+        &self,
+        trader_name: &str,
+        good_kind: GoodKind,
+        quantity: f32,
+        price: f32,
+    ) -> Option<String> {
+        let config = &self.meta.config.surveillance;
+        let buys: Vec<&Activity> = self
+            .window_activity(trader_name)
+            .filter(|a| a.good_kind == good_kind && a.kind == ActivityKind::Buy)
+            .collect();
+        let bought: f32 = buys.iter().map(|a| a.quantity).sum();
+        let paid: f32 = buys.iter().map(|a| a.price).sum();
+        // The stock before the trader started buying
+        let stock = buys.first().map(|a| a.market_stock + a.quantity)?;
+        if bought <= 0.0 || quantity <= 0.0 || stock <= 0.0 {
+            return None;
+        }
+        let stock_share = bought / stock;
+        let gain = (price / quantity) / (paid / bought) - 1.0;
+        let dumping = stock_share >= config.pump_min_stock_share && gain >= config.pump_min_gain;
+        dumping.then(|| format!("KIND:{good_kind}-STOCK_SHARE:{stock_share:+e}-GAIN:{gain:+e}"))
+    }
+
+    fn window_activity<'a>(&'a self, trader_name: &str) -> impl Iterator<Item = &'a Activity> {
+        self.meta
+            .surveillance
+            .traders
+            .get(trader_name)
+            .into_iter()
+            .flat_map(|record| record.activity.iter())
+    }
+}
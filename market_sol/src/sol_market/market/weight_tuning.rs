@@ -0,0 +1,132 @@
+use crate::sol_market::{
+    domain::strategy_name::ALL_STRATEGY_NAMES, SOLMarket, TuningObjective, WeightChange,
+};
+
+/// This block contains the self-tuning of the weights of the pricing
+/// strategies, enabled by `WeightTuningConfig::enabled`.
+///
+/// It is a hill climbing: every `every_days` days the period is scored, and
+/// one weight moves by a step. If the next period scores worse, the move is
+/// undone and the next move of that weight goes the other way. Tuned weights
+/// are saved in the state file like the others.
+impl SOLMarket {
+    /// Returns the changes made by the weight tuning, from the oldest
+    pub fn weight_changes(&self) -> &[WeightChange] {
+        &self.meta.weight_tuner.changes
+    }
+
+    /// Scores the period and moves a weight, if the period is over
+    pub(crate) fn tune_weights_if_needed(&mut self) {
+        let config = self.meta.config.weight_tuning.clone();
+        if !config.enabled || config.every_days == 0 {
+            return;
+        }
+        let days_in_period = self.meta.current_day % config.every_days;
+        if days_in_period != 0 {
+            return;
+        }
+        let score = self.tuning_score(config.objective);
+        self.meta.weight_tuner.period_start_margin = self.meta.stats.realized_margin;
+
+        // Evaluate the last move
+        let last_score = self.meta.weight_tuner.last_score;
+        if let Some((index, delta)) = self.meta.weight_tuner.pending.take() {
+            if matches!(last_score, Some(last) if score < last) {
+                let weight = self.weight_at(index);
+                self.set_weight(index, weight - delta, score, true);
+                let downwards = &mut self.meta.weight_tuner.downwards[index];
+                *downwards = !*downwards;
+                // The weights are back to the ones last_score was measured
+                // with, measure them again before the next move
+                self.meta.weight_tuner.last_score = None;
+                return;
+            }
+        }
+        self.meta.weight_tuner.last_score = Some(score);
+
+        // Move the next weight, away from the bound it reached
+        let tuner = &mut self.meta.weight_tuner;
+        let index = tuner.next_strategy;
+        tuner.next_strategy = (index + 1) % ALL_STRATEGY_NAMES.len();
+        let step = config.learning_rate * (config.max_weight - config.min_weight);
+        let old_weight = self.weight_at(index);
+        let mut new_weight = self.stepped_weight(index, old_weight, step);
+        if new_weight == old_weight {
+            let downwards = &mut self.meta.weight_tuner.downwards[index];
+            *downwards = !*downwards;
+            new_weight = self.stepped_weight(index, old_weight, step);
+        }
+        if new_weight == old_weight {
+            return;
+        }
+        self.set_weight(index, new_weight, score, false);
+        self.meta.weight_tuner.pending = Some((index, new_weight - old_weight));
+    }
+
+    /// The higher, the better
+    fn tuning_score(&self, objective: TuningObjective) -> f32 {
+        match objective {
+            TuningObjective::Margin => {
+                self.meta.stats.realized_margin - self.meta.weight_tuner.period_start_margin
+            }
+            TuningObjective::Inventory => {
+                let registry = &self.meta.goods_registry;
+                let targets = &self.meta.config.weight_tuning.target_inventory;
+                let distance: f32 = registry
+                    .iter()
+                    .map(|spec| {
+                        let target = targets
+                            .iter()
+                            .find(|target| Some(target.kind) == spec.kind)
+                            .map(|target| target.quantity)
+                            .unwrap_or(spec.initial_stock);
+                        let quantity = self.stock(&spec.ticker);
+                        registry
+                            .value_in_default_good(&spec.ticker, quantity - target)
+                            .abs()
+                    })
+                    .sum();
+                -distance
+            }
+        }
+    }
+
+    fn weight_at(&self, index: usize) -> f32 {
+        let strategy = &ALL_STRATEGY_NAMES[index];
+        self.meta.weights.get(strategy).copied().unwrap_or(1.0)
+    }
+
+    fn stepped_weight(&self, index: usize, weight: f32, step: f32) -> f32 {
+        let config = &self.meta.config.weight_tuning;
+        let step = if self.meta.weight_tuner.downwards[index] {
+            -step
+        } else {
+            step
+        };
+        (weight + step).clamp(config.min_weight, config.max_weight)
+    }
+
+    /// Sets a weight, within the bounds of the tuning. Since they are
+    /// positive, the weights never all reach 0.
+    fn set_weight(&mut self, index: usize, new_weight: f32, score: f32, reverted: bool) {
+        let config = &self.meta.config.weight_tuning;
+        let new_weight = new_weight.clamp(config.min_weight, config.max_weight);
+        let strategy = &ALL_STRATEGY_NAMES[index];
+        let old_weight = self.weight_at(index);
+        self.meta.weights.insert(*strategy, new_weight);
+        self.meta.rate_cache.borrow_mut().clear();
+        self.log(format!(
+            "WEIGHT_TUNING-{}-FROM:{old_weight:+e}-TO:{new_weight:+e}-SCORE:{score:+e}{}",
+            strategy.to_string(),
+            if reverted { "-REVERTED" } else { "" }
+        ));
+        self.meta.weight_tuner.changes.push(WeightChange {
+            day: self.meta.current_day,
+            strategy: strategy.to_string(),
+            old_weight,
+            new_weight,
+            score,
+            reverted,
+        });
+    }
+}
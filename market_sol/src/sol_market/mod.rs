@@ -1,10 +1,7 @@
 use self::domain::market_meta::MarketMeta;
 use self::market::internal_trading::trade_role::TradeRole;
 use std::collections::HashMap;
-use std::fs::OpenOptions;
-use std::io::Write;
 use unitn_market_2022::event::notifiable::Notifiable;
-use unitn_market_2022::good::good::Good;
 use unitn_market_2022::good::good_kind::GoodKind;
 use unitn_market_2022::market::good_label::GoodLabel;
 
@@ -12,36 +9,99 @@ mod domain;
 mod market;
 mod misc;
 
+pub use self::domain::auction::{AuctionConfig, AuctionOrder, AuctionSide, AuctionStatus};
+pub use self::domain::background_flow::BackgroundFlowConfig;
+pub use self::domain::clock::{Clock, EventClock, RealTimeClock, SimulatedClock};
+pub use self::domain::event_log::EventLogConfig;
+pub use self::domain::fees::{Fee, FeeSchedule, FeeTier, FeeTotals, TradeSide, TraderFees};
+pub use self::domain::forward_contract::{
+    ForwardContract, ForwardError, ForwardPayout, ForwardRequest, ForwardSettlement, ForwardSide,
+    ForwardStatus,
+};
+pub use self::domain::goods_registry::{GoodSpec, GoodsRegistry};
+pub use self::domain::instance::{EventListener, InstanceEvent, LogSink};
+pub use self::domain::lock_deposit::{LockDepositConfig, LockDeposits, TraderDeposits};
+pub use self::domain::margin_account::{MarginAccount, MarginBalance, MarginError, MarginSummary};
+pub use self::domain::market_config::MarketConfig;
+pub use self::domain::market_definition::{
+    GoodDefinition, MarketDefinition, WeightsDefinition, MARKET_DEFINITION_SCHEMA_VERSION,
+};
+pub use self::domain::market_event::{
+    AdjustmentReason, EventError, MarketEvent, MarketState, Reservation,
+};
+pub use self::domain::option_contract::{
+    OptionContract, OptionError, OptionKind, OptionRequest, OptionStatus,
+};
+pub use self::domain::paper::{PaperError, PaperTrade};
+pub use self::domain::quota::{QuotaError, QuotaUsage};
+pub use self::domain::quote::{Quote, QuoteLadder};
+pub use self::domain::report::{
+    GoodRates, GoodVolume, LockCounts, MarketReport, StrategyWeight, ValuePoint,
+};
+pub use self::domain::sol_market_error::SOLMarketError;
+pub use self::domain::surveillance::{SurveillanceConfig, SurveillanceFlag, SuspiciousPattern};
+pub use self::domain::weight_tuning::{
+    InventoryTarget, TuningObjective, WeightChange, WeightTuningConfig,
+};
+
+// The name of the market, unless its configuration says otherwise
 pub(crate) const MARKET_NAME: &str = "SOL";
+// Defaults of the MarketConfig
 pub(crate) const TOKEN_DURATION: u32 = 15;
 pub(crate) const LOCK_LIMIT: u32 = 10;
+// Locks worth less than this (in DEFAULT_GOOD_KIND) are rejected
+pub(crate) const MIN_LOCK_VALUE: f32 = 0.01;
+// Lock requests are counted over this many days
+pub(crate) const QUOTA_WINDOW_DAYS: u32 = 1;
 // The margin this market applies on buy orders
 pub(crate) const MARKET_MARGIN: f32 = 0.06;
+// The margin of cash-settled forward contracts, as a share of their price
+pub(crate) const FORWARD_MARGIN_RATIO: f32 = 0.2;
+// Defaults of the margin accounts
+pub(crate) const MARGIN_MAX_LEVERAGE: f32 = 3.0;
+pub(crate) const MARGIN_MAINTENANCE_RATIO: f32 = 0.15;
+pub(crate) const MARGIN_DAILY_INTEREST_RATE: f32 = 0.0005;
 
+// The goods of the market protocol. Everything else about them is in the GoodsRegistry
 pub(crate) const ALL_GOOD_KINDS: [GoodKind; 4] =
     [GoodKind::EUR, GoodKind::USD, GoodKind::YEN, GoodKind::YUAN];
 
 pub struct SOLMarket {
-    pub(crate) goods: HashMap<GoodKind, Good>,
     pub(crate) subscribers: Vec<Box<dyn Notifiable>>,
+    pub(crate) event_listeners: Vec<EventListener>,
     pub(crate) meta: MarketMeta,
-    pub(crate) internal_needs: HashMap<GoodKind, TradeRole>,
+    pub(crate) internal_needs: HashMap<String, TradeRole>,
 }
 
 impl SOLMarket {
     /// Returns how much of the asked GoodKind is available (not locked)
     pub(crate) fn get_available_quantity(&self, good_kind: GoodKind) -> f32 {
-        let good = self.goods.get(&good_kind).expect("Should be initialized");
-        good.get_qty()
+        self.stock(self.ticker(good_kind))
+    }
+
+    /// Returns how much of the good with the given ticker is available, as
+    /// folded from the events
+    pub(crate) fn stock(&self, ticker: &str) -> f32 {
+        *self
+            .meta
+            .event_log
+            .state()
+            .stock
+            .get(ticker)
+            .expect("Should be initialized")
+    }
+
+    /// Returns the ticker of the good traded as `good_kind`
+    pub(crate) fn ticker(&self, good_kind: GoodKind) -> &str {
+        self.meta.goods_registry.ticker_of(good_kind)
     }
 
     pub(crate) fn get_good_labels(&self) -> Vec<GoodLabel> {
-        let values = self.goods.values();
-        let iter = values.map(|g: &Good| -> GoodLabel {
-            let good_kind = g.get_kind();
+        let traded = self.meta.goods_registry.traded();
+        let iter = traded.map(|(good_kind, spec)| -> GoodLabel {
             GoodLabel {
                 good_kind,
-                quantity: g.get_qty(),
+                quantity: self.stock(&spec.ticker),
                 exchange_rate_buy: self.get_good_buy_exchange_rate(good_kind),
                 exchange_rate_sell: self.get_good_sell_exchange_rate(good_kind),
             }
@@ -49,30 +109,7 @@ impl SOLMarket {
         Vec::from_iter(iter)
     }
 
-    pub(crate) fn lock_limit_exceeded(num_of_locks: u32) -> bool {
-        num_of_locks + 1 > LOCK_LIMIT
-    }
-}
-
-/// Append log code to file according to specifications
-pub(crate) fn log(log_code: String) {
-    let filename = format!("log_{}.txt", MARKET_NAME);
-    let mut file = OpenOptions::new()
-        .append(true)
-        .create(true)
-        .open(filename)
-        .unwrap();
-
-    let time = chrono::Local::now()
-        .format("%y:%m:%d:%H:%M:%S:%3f")
-        .to_string();
-
-    if let Err(e) = writeln!(file, "{}|{}|{}", MARKET_NAME, time, log_code) {
-        eprintln!("Error while writing to file {}", e);
+    pub(crate) fn lock_limit_exceeded(&self, num_of_locks: u32) -> bool {
+        num_of_locks + 1 > self.meta.config.lock_limit
     }
 }
-
-/// Return the value in DEFAULT_GOOD_KIND of a good
-pub(crate) fn get_value_good(kind: &GoodKind, qty: f32) -> f32 {
-    qty / kind.get_default_exchange_rate()
-}
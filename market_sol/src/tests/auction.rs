@@ -0,0 +1,247 @@
+use super::{definition_with, market_from, market_with};
+use crate::sol_market::{
+    AuctionConfig, AuctionSide, AuctionStatus, LogSink, MarketConfig, SOLMarket,
+};
+use std::{cell::RefCell, rc::Rc};
+use unitn_market_2022::{
+    event::notifiable::Notifiable,
+    good::{consts::DEFAULT_GOOD_KIND, good::Good, good_kind::GoodKind},
+    market::{BuyError, LockBuyError, Market},
+    wait_one_day,
+};
+
+const TRADER_NAME: &str = "foobar";
+
+/// A market in auction mode, clearing every 3 days, with 100000 of each good
+/// but `usd` USD
+fn market(usd: f32) -> Rc<RefCell<SOLMarket>> {
+    let config = MarketConfig {
+        auction: AuctionConfig {
+            enabled: true,
+            every_days: 3,
+        },
+        ..Default::default()
+    };
+    let mut definition = definition_with(config, 7);
+    for good in definition.goods.iter_mut() {
+        if good.kind == Some(GoodKind::USD) {
+            good.quantity = usd;
+        }
+    }
+    market_from(&definition)
+}
+
+fn filled(market: &SOLMarket, token: &str) -> (f32, f32, f32) {
+    match market.auction_order(token).unwrap().status {
+        AuctionStatus::Filled {
+            clearing_price,
+            quantity,
+            price,
+            ..
+        } => (clearing_price, quantity, price),
+        status => panic!("The order should be filled, it is {status:?}"),
+    }
+}
+
+#[test]
+/// A lock should wait for the clearing, then be redeemed at the clearing
+/// price
+///
+/// SOL group
+fn locks_should_be_filled_at_the_clearing() {
+    let market = market(100000.0);
+    let ask = market.borrow().get_buy_price(GoodKind::USD, 1.0).unwrap();
+    let bid = 200.0 * ask;
+    let token = market
+        .borrow_mut()
+        .lock_buy(GoodKind::USD, 100.0, bid, TRADER_NAME.to_string())
+        .unwrap();
+
+    // Day 2, the auction did not clear yet
+    {
+        let m = market.borrow();
+        let order = m.auction_order(&token).unwrap();
+        assert_eq!(order.side, AuctionSide::Buy);
+        assert_eq!(order.status, AuctionStatus::Pending);
+        assert_eq!(m.pending_auction_orders().len(), 1);
+    }
+    let result = market
+        .borrow_mut()
+        .buy(token.clone(), &mut Good::new(DEFAULT_GOOD_KIND, bid));
+    assert!(matches!(result, Err(BuyError::UnrecognizedToken { .. })));
+
+    wait_one_day!(market);
+    let (clearing_price, quantity, price) = filled(&market.borrow(), &token);
+    assert_eq!(quantity, 100.0);
+    assert!(clearing_price <= bid / 100.0);
+    assert_eq!(price, quantity * clearing_price);
+    assert!(market.borrow().pending_auction_orders().is_empty());
+
+    let mut cash = Good::new(DEFAULT_GOOD_KIND, bid);
+    let bought = market.borrow_mut().buy(token, &mut cash).unwrap();
+    assert_eq!(bought.get_qty(), 100.0);
+    assert_eq!(cash.get_qty(), bid - price);
+}
+
+#[test]
+/// A buyer and a seller should cross at a single price within their limits
+///
+/// SOL group
+fn buyers_and_sellers_should_cross_at_one_price() {
+    let market = market(100000.0);
+    let ask = market.borrow().get_buy_price(GoodKind::YEN, 1.0).unwrap();
+    let mut m = market.borrow_mut();
+    let buy = m
+        .lock_buy(GoodKind::YEN, 1000.0, 2000.0 * ask, TRADER_NAME.to_string())
+        .unwrap();
+    let sell = m
+        .lock_sell(GoodKind::YEN, 1000.0, 500.0 * ask, "barfoo".to_string())
+        .unwrap();
+
+    let (buy_price, bought, _) = filled(&m, &buy);
+    let (sell_price, sold, _) = filled(&m, &sell);
+    assert_eq!(buy_price, sell_price);
+    assert!((0.5 * ask..=2.0 * ask).contains(&buy_price));
+    assert_eq!((bought, sold), (1000.0, 1000.0));
+}
+
+#[test]
+/// An order that can not be matched within its limit should stay unfilled
+///
+/// SOL group
+fn orders_out_of_the_market_should_not_be_filled() {
+    let market = market(100000.0);
+    let ask = market.borrow().get_buy_price(GoodKind::USD, 1.0).unwrap();
+    let mut m = market.borrow_mut();
+    let token = m
+        .lock_buy(GoodKind::USD, 100.0, 50.0 * ask, TRADER_NAME.to_string())
+        .unwrap();
+    m.lock_buy(GoodKind::YUAN, 1.0, 1.0, TRADER_NAME.to_string())
+        .unwrap();
+
+    assert!(matches!(
+        m.auction_order(&token).unwrap().status,
+        AuctionStatus::Unfilled {
+            clearing_price: None,
+            ..
+        }
+    ));
+    let result = m.buy(token, &mut Good::new(DEFAULT_GOOD_KIND, 100.0));
+    assert!(matches!(result, Err(BuyError::UnrecognizedToken { .. })));
+}
+
+#[test]
+/// A filled sell order should be valued at the bid of the market, not at
+/// its ask
+///
+/// SOL group
+fn filled_sells_should_be_valued_at_the_bid() {
+    let market = market(100000.0);
+    let token = market
+        .borrow_mut()
+        .lock_sell(GoodKind::USD, 100.0, 1.0, TRADER_NAME.to_string())
+        .unwrap();
+    wait_one_day!(market);
+
+    let m = market.borrow();
+    let (_, quantity, _) = filled(&m, &token);
+    let market_value = m.meta.locked_sells[&token].market_value;
+    let bid_value = quantity / m.get_good_sell_exchange_rate(GoodKind::USD);
+    let ask_value = quantity / m.get_good_buy_exchange_rate(GoodKind::USD);
+    assert!((market_value - bid_value).abs() <= 1e-4 * bid_value);
+    assert!((market_value - ask_value).abs() > 1e-4 * ask_value);
+}
+
+#[test]
+/// Orders should be logged like the locks they stand for
+///
+/// SOL group
+fn orders_should_be_logged_as_locks() {
+    let market = market(100000.0);
+    let lines = Rc::new(RefCell::new(vec![]));
+    market
+        .borrow_mut()
+        .set_log_sink(LogSink::Memory(lines.clone()));
+    let buy = market
+        .borrow_mut()
+        .lock_buy(GoodKind::USD, 10.0, 100.0, TRADER_NAME.to_string())
+        .unwrap();
+    let sell = market
+        .borrow_mut()
+        .lock_sell(GoodKind::USD, 10.0, 1.0, TRADER_NAME.to_string())
+        .unwrap();
+
+    let lines = lines.borrow();
+    let logged = |prefix: &str, token: &str| {
+        lines
+            .iter()
+            .any(|line| line.contains(prefix) && line.ends_with(&format!("-TOKEN:{token}")))
+    };
+    assert!(logged("LOCK_BUY-foobar", &buy));
+    assert!(logged("AUCTION_BUY-foobar", &buy));
+    assert!(logged("LOCK_SELL-foobar", &sell));
+    assert!(logged("AUCTION_SELL-foobar", &sell));
+}
+
+#[test]
+/// Orders should share the stock of the market pro rata
+///
+/// SOL group
+fn orders_should_share_the_stock() {
+    let market = market(10.0);
+    let mut m = market.borrow_mut();
+    let first = m
+        .lock_buy(GoodKind::USD, 100000.0, 1e7, TRADER_NAME.to_string())
+        .unwrap();
+    let second = m
+        .lock_buy(GoodKind::USD, 100000.0, 1e7, "barfoo".to_string())
+        .unwrap();
+
+    let (_, first, _) = filled(&m, &first);
+    let (_, second, _) = filled(&m, &second);
+    assert_eq!(first, second);
+    // The market keeps some stock, its price rises as the stock runs out
+    assert!(first < 100000.0);
+    assert!(m.get_available_quantity(GoodKind::USD) > 0.0);
+}
+
+#[test]
+/// Orders waiting for the clearing should count toward the lock limit and
+/// the locked value of the trader
+///
+/// SOL group
+fn pending_orders_should_count_as_locks() {
+    let config = |lock_limit: u32, max_locked_value: Option<f32>| MarketConfig {
+        lock_limit,
+        max_locked_value,
+        auction: AuctionConfig {
+            enabled: true,
+            every_days: 1000,
+        },
+        ..Default::default()
+    };
+
+    let market = market_with(config(2, None), 7);
+    let mut market = market.borrow_mut();
+    for _ in 0..2 {
+        assert!(market
+            .lock_buy(GoodKind::USD, 10.0, 100.0, TRADER_NAME.to_string())
+            .is_ok());
+    }
+    let result = market.lock_buy(GoodKind::USD, 10.0, 100.0, TRADER_NAME.to_string());
+    assert_eq!(result.unwrap_err(), LockBuyError::MaxAllowedLocksReached);
+    // Sell orders have their own limit
+    assert!(market
+        .lock_sell(GoodKind::USD, 10.0, 1.0, TRADER_NAME.to_string())
+        .is_ok());
+    assert_eq!(market.pending_auction_orders().len(), 3);
+
+    let market = market_with(config(10, Some(150.0)), 7);
+    let mut market = market.borrow_mut();
+    assert!(market
+        .lock_buy(GoodKind::USD, 10.0, 100.0, TRADER_NAME.to_string())
+        .is_ok());
+    let result = market.lock_buy(GoodKind::USD, 10.0, 100.0, TRADER_NAME.to_string());
+    assert_eq!(result.unwrap_err(), LockBuyError::MaxAllowedLocksReached);
+    assert_eq!(market.quota_usage(TRADER_NAME).locked_value, 100.0);
+}
@@ -0,0 +1,130 @@
+use super::{definition_with, market_from};
+use crate::sol_market::{
+    BackgroundFlowConfig, MarketConfig, SOLMarket, SOLMarketError, ALL_GOOD_KINDS,
+};
+use std::{cell::RefCell, rc::Rc};
+use unitn_market_2022::{event::notifiable::Notifiable, good::good_kind::GoodKind, wait_one_day};
+
+fn market(quantity: f32, background_flow: BackgroundFlowConfig) -> Rc<RefCell<SOLMarket>> {
+    let config = MarketConfig {
+        background_flow,
+        ..Default::default()
+    };
+    let mut definition = definition_with(config, 42);
+    for good in definition.goods.iter_mut() {
+        good.quantity = quantity;
+    }
+    market_from(&definition)
+}
+
+fn enabled() -> BackgroundFlowConfig {
+    BackgroundFlowConfig {
+        enabled: true,
+        ..Default::default()
+    }
+}
+
+fn wait_days(market: &Rc<RefCell<SOLMarket>>, days: u32) {
+    for _ in 0..days {
+        wait_one_day!(market);
+    }
+}
+
+#[test]
+/// Without background flow, nobody should trade
+///
+/// SOL group
+fn background_flow_should_be_disabled_by_default() {
+    let market = market(100000.0, Default::default());
+    wait_days(&market, 20);
+    assert_eq!(market.borrow().report().background_trades, 0);
+}
+
+#[test]
+/// The background traders should move the quantity and others prices
+///
+/// SOL group
+fn background_flow_should_move_the_prices() {
+    let quiet = market(100000.0, Default::default());
+    let busy = market(100000.0, enabled());
+    wait_days(&quiet, 30);
+    wait_days(&busy, 30);
+
+    let report = busy.borrow().report();
+    assert_eq!(report.background_trades, 30 * 8);
+    assert!(report.background_traded_value > 0.0);
+    let quiet = quiet.borrow().report();
+    for (busy, quiet) in report.rates.iter().zip(quiet.rates.iter()) {
+        assert_eq!(busy.kind, quiet.kind);
+        if busy.kind == GoodKind::EUR {
+            continue;
+        }
+        assert_ne!(busy.quantity, quiet.quantity);
+        assert_ne!(busy.others, quiet.others);
+    }
+}
+
+#[test]
+/// The same seed should give the same background trades
+///
+/// SOL group
+fn background_flow_should_follow_the_seed() {
+    let first = market(100000.0, enabled());
+    let second = market(100000.0, enabled());
+    wait_days(&first, 30);
+    wait_days(&second, 30);
+    for kind in ALL_GOOD_KINDS {
+        assert_eq!(
+            first.borrow().get_available_quantity(kind),
+            second.borrow().get_available_quantity(kind)
+        );
+    }
+}
+
+#[test]
+/// The background traders should not take more than the market has
+///
+/// SOL group
+fn background_flow_should_respect_the_inventory() {
+    let market = market(
+        10.0,
+        BackgroundFlowConfig {
+            liquidity_trades: 5,
+            liquidity_trade_value: 1_000_000.0,
+            ..enabled()
+        },
+    );
+    for _ in 0..30 {
+        wait_days(&market, 1);
+        for kind in ALL_GOOD_KINDS {
+            assert!(market.borrow().get_available_quantity(kind) >= 0.0);
+        }
+    }
+    assert!(market.borrow().report().background_trades > 0);
+}
+
+#[test]
+/// Trade values the traders can not draw should be rejected when the market
+/// is created
+///
+/// SOL group
+fn invalid_trade_values_should_be_rejected() {
+    for max_trade_value in [-1.0, f32::NAN] {
+        let config = MarketConfig {
+            background_flow: BackgroundFlowConfig {
+                max_trade_value,
+                ..enabled()
+            },
+            ..Default::default()
+        };
+        let definition = definition_with(config, 42);
+        let result = SOLMarket::try_new_from_definition(&definition);
+        assert!(matches!(
+            result,
+            Err(SOLMarketError::InvalidConfig {
+                parameter: "background_flow.max_trade_value",
+                ..
+            })
+        ));
+    }
+}
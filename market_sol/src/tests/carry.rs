@@ -0,0 +1,136 @@
+use crate::sol_market::{MarketDefinition, SOLMarket, WeightsDefinition};
+use std::{cell::RefCell, rc::Rc};
+use unitn_market_2022::{
+    good::{consts::DEFAULT_GOOD_KIND, good::Good, good_kind::GoodKind},
+    market::Market,
+    wait_one_day,
+};
+
+const TRADER_NAME: &str = "foobar";
+const USD_RATE: f32 = 0.01;
+
+/// A market where every good is worth the same, so that no internal trade
+/// gets in the way, and only USD earns interest
+fn market_with_usd_carry() -> Rc<RefCell<SOLMarket>> {
+    let mut definition = MarketDefinition::from_sol_str(&format!(
+        "good EUR 10000 1\ngood YEN {} 1\ngood USD {} 1\ngood YUAN {} 1\n",
+        10000.0 * GoodKind::YEN.get_default_exchange_rate(),
+        10000.0 * GoodKind::USD.get_default_exchange_rate(),
+        10000.0 * GoodKind::YUAN.get_default_exchange_rate(),
+    ))
+    .unwrap();
+    definition.weights = WeightsDefinition {
+        stocastic: Some(0.0),
+        quantity: Some(1.0),
+        others: Some(0.0),
+    };
+    for good in definition.goods.iter_mut() {
+        if good.kind == Some(GoodKind::USD) {
+            good.daily_interest_rate = Some(USD_RATE);
+        }
+    }
+    SOLMarket::try_new_from_definition(&definition).unwrap()
+}
+
+fn assert_close(actual: f32, expected: f32) {
+    assert!(
+        (actual - expected).abs() <= expected.abs() * 1e-4,
+        "{actual} should be close to {expected}"
+    );
+}
+
+#[test]
+/// The stock should earn its daily interest every day
+///
+/// SOL group
+fn on_event_should_apply_the_daily_interest() {
+    let market = market_with_usd_carry();
+    let usd = market.borrow().get_available_quantity(GoodKind::USD);
+    let eur = market.borrow().get_available_quantity(GoodKind::EUR);
+    let dyn_market: Rc<RefCell<dyn Market>> = market.clone();
+    for _ in 0..3 {
+        wait_one_day!(dyn_market);
+    }
+    let market = market.borrow();
+    assert_close(
+        market.get_available_quantity(GoodKind::USD),
+        usd * (1.0 + USD_RATE).powi(3),
+    );
+    assert_close(market.get_available_quantity(GoodKind::EUR), eur);
+}
+
+#[test]
+/// The carry of locked goods should go to the market when they are bought
+///
+/// SOL group
+fn buy_should_settle_the_carry_of_the_lock() {
+    let market = market_with_usd_carry();
+    let quantity = 100.0;
+    let bid = market
+        .borrow()
+        .get_buy_price(GoodKind::USD, quantity)
+        .unwrap();
+    let token = market
+        .borrow_mut()
+        .lock_buy(GoodKind::USD, quantity, bid, TRADER_NAME.to_string())
+        .unwrap();
+    let dyn_market: Rc<RefCell<dyn Market>> = market.clone();
+    for _ in 0..2 {
+        wait_one_day!(dyn_market);
+    }
+
+    let (stock, expected_carry) = {
+        let market = market.borrow();
+        let lock = market.meta.locked_buys.get(&token).unwrap();
+        let days = market.meta.current_day - lock.created_on;
+        let carry = quantity * ((1.0 + USD_RATE).powi(days as i32) - 1.0);
+        (market.get_available_quantity(GoodKind::USD), carry)
+    };
+    let mut cash = Good::new(DEFAULT_GOOD_KIND, bid);
+    let bought = market.borrow_mut().buy(token, &mut cash).unwrap();
+    assert_eq!(bought.get_qty(), quantity);
+    //The buy itself is an event, so a day of interest is applied after settling
+    assert_close(
+        market.borrow().get_available_quantity(GoodKind::USD),
+        (stock + expected_carry) * (1.0 + USD_RATE),
+    );
+}
+
+#[test]
+/// Forward prices should follow the carry of the goods
+///
+/// SOL group
+fn forward_prices_should_include_the_carry() {
+    let market = market_with_usd_carry();
+    let market = market.borrow();
+    let days = 30;
+    let factor = 1.0 / (1.0 + USD_RATE).powi(days as i32);
+
+    let spot = market.get_buy_price(GoodKind::USD, 10.0).unwrap();
+    let forward = market
+        .get_forward_buy_price(GoodKind::USD, 10.0, days)
+        .unwrap();
+    assert_close(forward, spot * factor);
+    let spot = market.get_sell_price(GoodKind::USD, 10.0).unwrap();
+    let forward = market
+        .get_forward_sell_price(GoodKind::USD, 10.0, days)
+        .unwrap();
+    assert_close(forward, spot * factor);
+
+    //Goods without interest have the same price today and in the future
+    let spot = market.get_buy_price(GoodKind::YEN, 10.0).unwrap();
+    let forward = market
+        .get_forward_buy_price(GoodKind::YEN, 10.0, days)
+        .unwrap();
+    assert_close(forward, spot);
+
+    let ladders = market.get_forward_quote_ladders(&[10.0], days);
+    let usd = &ladders[&GoodKind::USD];
+    assert_eq!(usd.days_ahead, days);
+    assert_close(
+        usd.quotes[0].buy_price.clone().unwrap(),
+        market
+            .get_forward_buy_price(GoodKind::USD, 10.0, days)
+            .unwrap(),
+    );
+}
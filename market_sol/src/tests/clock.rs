@@ -0,0 +1,136 @@
+use super::market_with;
+use crate::sol_market::{LogSink, MarketConfig, SOLMarket, SimulatedClock};
+use chrono::{Local, TimeZone};
+use std::{cell::RefCell, rc::Rc, thread, time::Duration};
+use unitn_market_2022::event::notifiable::Notifiable;
+use unitn_market_2022::{
+    good::{consts::DEFAULT_GOOD_KIND, good::Good, good_kind::GoodKind},
+    market::{BuyError, Market},
+    wait_one_day,
+};
+
+const TRADER_NAME: &str = "foobar";
+
+/// A market on the given simulated clock
+fn simulated_market(clock: &SimulatedClock) -> Rc<RefCell<SOLMarket>> {
+    let market =
+        SOLMarket::try_new_with_quantities(100000.0, 100000.0, 100000.0, 100000.0).unwrap();
+    market.borrow_mut().set_clock(Box::new(clock.clone()));
+    market.borrow_mut().set_log_sink(LogSink::Disabled);
+    market
+}
+
+fn clock() -> SimulatedClock {
+    SimulatedClock::new(Local.with_ymd_and_hms(2023, 1, 2, 10, 30, 0).unwrap())
+}
+
+#[test]
+/// The log should be timestamped by the clock of the market
+///
+/// SOL group
+fn log_should_use_the_time_of_the_clock() {
+    let clock = clock();
+    let market = simulated_market(&clock);
+    let log = Rc::new(RefCell::new(vec![]));
+    market
+        .borrow_mut()
+        .set_log_sink(LogSink::Memory(log.clone()));
+    market
+        .borrow_mut()
+        .lock_sell(GoodKind::USD, 1.0, 0.1, TRADER_NAME.to_string())
+        .unwrap();
+    clock.advance(chrono::Duration::milliseconds(1500));
+    market
+        .borrow_mut()
+        .lock_sell(GoodKind::USD, 1.0, 0.1, TRADER_NAME.to_string())
+        .unwrap();
+
+    let log = log.borrow();
+    assert_eq!(log.len(), 2);
+    assert!(log[0].starts_with("SOL|23:01:02:10:30:00:000|LOCK_SELL-"));
+    assert!(log[1].starts_with("SOL|23:01:02:10:30:01:500|LOCK_SELL-"));
+}
+
+#[test]
+/// On a simulated clock, only the clock should move the days, and identical
+/// locks at the same time should still get their own token
+///
+/// SOL group
+fn simulated_clock_should_move_the_days() {
+    let clock = clock();
+    let market = simulated_market(&clock);
+    let mut m = market.borrow_mut();
+    let first = m
+        .lock_buy(GoodKind::USD, 1.0, 10.0, TRADER_NAME.to_string())
+        .unwrap();
+    let second = m
+        .lock_buy(GoodKind::USD, 1.0, 10.0, TRADER_NAME.to_string())
+        .unwrap();
+    assert_ne!(first, second);
+    assert_eq!(m.report().day, 1);
+
+    clock.advance_days(3);
+    assert_eq!(m.sync_clock(), 3);
+    assert_eq!(m.report().day, 4);
+    assert_eq!(m.sync_clock(), 0);
+
+    // Past the duration of the token
+    clock.advance_days(15);
+    drop(m);
+    wait_one_day!(market);
+    let result = market
+        .borrow_mut()
+        .buy(first, &mut Good::new(DEFAULT_GOOD_KIND, 10.0));
+    assert!(matches!(result, Err(BuyError::ExpiredToken { .. })));
+    assert_eq!(market.borrow().report().locks.expired, 2);
+}
+
+#[test]
+/// A real-time market should move by the wall clock, not by the events
+///
+/// SOL group
+fn real_time_clock_should_move_with_the_wall_clock() {
+    let slow = MarketConfig {
+        day_length_ms: Some(3_600_000),
+        ..Default::default()
+    };
+    let market = market_with(slow, 1);
+    for _ in 0..5 {
+        wait_one_day!(market);
+    }
+    assert_eq!(market.borrow().report().day, 1);
+
+    let fast = MarketConfig {
+        day_length_ms: Some(5),
+        ..Default::default()
+    };
+    let market = market_with(fast, 1);
+    thread::sleep(Duration::from_millis(30));
+    let days = market.borrow_mut().sync_clock();
+    assert!(days >= 6);
+    assert_eq!(market.borrow().report().day, 1 + days);
+}
+
+#[test]
+/// Rates should follow the stock even when the day does not change
+///
+/// SOL group
+fn rates_should_follow_the_stock_within_a_day() {
+    let clock = clock();
+    let market = simulated_market(&clock);
+    let before = market.borrow().get_buy_price(GoodKind::USD, 1.0).unwrap();
+
+    let bid = market
+        .borrow()
+        .get_buy_price(GoodKind::USD, 50000.0)
+        .unwrap();
+    market
+        .borrow_mut()
+        .lock_buy(GoodKind::USD, 50000.0, bid, TRADER_NAME.to_string())
+        .unwrap();
+    let after = market.borrow().get_buy_price(GoodKind::USD, 1.0).unwrap();
+
+    assert_eq!(market.borrow().meta.current_day, 1);
+    // The quantity strategy moved the rate, the rate of the day is not reused
+    assert_ne!(after, before);
+}
@@ -125,3 +125,83 @@ fn new_random_should_not_exceeed_starting_capital() {
         );
     }
 }
+
+#[test]
+/// The fallible constructor should return an error instead of panicking when
+/// a quantity is negative
+///
+/// SOL group
+fn try_new_with_quantities_should_reject_negative_quantities() {
+    use crate::sol_market::SOLMarketError;
+
+    let result = SOLMarket::try_new_with_quantities(1.0, 1.0, -3.0, 1.0);
+    match result {
+        Err(SOLMarketError::NegativeQuantity { ticker, quantity }) => {
+            assert_eq!(ticker, "USD");
+            assert_eq!(quantity, -3.0);
+        }
+        _ => panic!("Should have returned a NegativeQuantity error"),
+    }
+    assert!(SOLMarket::try_new_with_quantities(1.0, 1.0, 1.0, 1.0).is_ok());
+}
+
+#[test]
+/// Malformed state files should be reported with the offending line
+///
+/// SOL group
+fn try_new_file_should_report_the_line_of_parse_errors() {
+    use crate::sol_market::SOLMarketError;
+    use std::fs;
+
+    let directory = std::env::temp_dir().join(format!("sol_parse_test_{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    let path = directory.join("broken.sol");
+    fs::write(
+        &path,
+        "# a comment\ngood EUR 10 1\ngood USD ten 1\ngood YEN 10 1\ngood YUAN 10 1\n",
+    )
+    .unwrap();
+
+    let result = SOLMarket::try_new_file(path.to_str().unwrap());
+    match result {
+        Err(SOLMarketError::Parse { line, reason, .. }) => {
+            assert_eq!(line, 3);
+            assert!(reason.contains("ten"));
+        }
+        _ => panic!("Should have returned a Parse error"),
+    }
+
+    fs::write(&path, "good EUR 10 1\ngood USD 10 1\n").unwrap();
+    let result = SOLMarket::try_new_file(path.to_str().unwrap());
+    assert!(matches!(result, Err(SOLMarketError::MissingGood { .. })));
+
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+/// A market saved to a file should be restored with the same quantities
+///
+/// SOL group
+fn try_new_file_should_restore_a_saved_market() {
+    use std::fs;
+    use unitn_market_2022::good::good_kind::GoodKind;
+    use unitn_market_2022::market::Market;
+
+    let directory = std::env::temp_dir().join(format!("sol_restore_test_{}", std::process::id()));
+    let path = directory.join("market.sol");
+    let path_str = path.to_str().unwrap();
+    //The file does not exist yet, so the market is random and saved on drop
+    let quantities: Vec<(GoodKind, f32)> = {
+        let market = SOLMarket::try_new_file(path_str).unwrap();
+        let goods = market.borrow().get_goods();
+        goods.iter().map(|l| (l.good_kind, l.quantity)).collect()
+    };
+    assert!(path.exists());
+
+    let market = SOLMarket::try_new_file(path_str).unwrap();
+    for (good_kind, quantity) in quantities {
+        assert_eq!(market.borrow().get_available_quantity(good_kind), quantity);
+    }
+    drop(market);
+    fs::remove_dir_all(&directory).unwrap();
+}
@@ -0,0 +1,140 @@
+use crate::sol_market::{
+    GoodDefinition, MarketConfig, MarketDefinition, SOLMarket, SOLMarketError, WeightsDefinition,
+    MARKET_DEFINITION_SCHEMA_VERSION,
+};
+use std::{fs, path::PathBuf};
+use unitn_market_2022::{
+    good::good_kind::GoodKind,
+    market::{LockBuyError, Market},
+};
+
+const TRADER_NAME: &str = "foobar";
+
+fn definition(seed: Option<u64>, config: MarketConfig) -> MarketDefinition {
+    let goods = [GoodKind::EUR, GoodKind::YEN, GoodKind::USD, GoodKind::YUAN]
+        .into_iter()
+        .map(|kind| GoodDefinition::new(kind, 10000.0))
+        .collect();
+    MarketDefinition {
+        seed,
+        goods,
+        weights: WeightsDefinition {
+            stocastic: Some(0.5),
+            quantity: Some(0.25),
+            others: None,
+        },
+        config,
+        ..Default::default()
+    }
+}
+
+fn temp_directory(name: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("{name}_{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    directory
+}
+
+#[test]
+/// Definitions should survive a round trip through TOML and JSON files
+///
+/// SOL group
+fn definition_should_roundtrip_through_toml_and_json() {
+    let directory = temp_directory("sol_definition_roundtrip");
+    let config = MarketConfig {
+        token_duration: 3,
+        ..Default::default()
+    };
+    let original = definition(Some(42), config);
+    for file_name in ["market.toml", "market.json"] {
+        let path = directory.join(file_name);
+        original.write_to_file(&path).unwrap();
+        assert_eq!(MarketDefinition::from_file(&path).unwrap(), original);
+    }
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+/// Converting a .sol file to TOML should keep goods and weights
+///
+/// SOL group
+fn sol_files_should_convert_to_toml() {
+    let directory = temp_directory("sol_definition_convert");
+    let sol = "# a comment\ngood EUR 10 1\ngood YEN 20 2\ngood USD 30 3\ngood YUAN 40 4\nweight Quantity 0.5\n";
+    let from_sol = MarketDefinition::from_sol_str(sol).unwrap();
+    let path = directory.join("market.toml");
+    from_sol.write_to_file(&path).unwrap();
+    let from_toml = MarketDefinition::from_file(&path).unwrap();
+    assert_eq!(from_toml, from_sol);
+    assert_eq!(from_toml.weights.quantity, Some(0.5));
+    assert_eq!(
+        MarketDefinition::from_sol_str(&from_toml.to_sol_string()).unwrap(),
+        from_sol
+    );
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+/// The configuration of a definition should drive the market
+///
+/// SOL group
+fn definition_config_should_be_applied() {
+    let config = MarketConfig {
+        lock_limit: 1,
+        ..Default::default()
+    };
+    let market = SOLMarket::try_new_from_definition(&definition(None, config)).unwrap();
+    let mut market = market.borrow_mut();
+    let bid = market.get_buy_price(GoodKind::USD, 1.0).unwrap();
+    market
+        .lock_buy(GoodKind::USD, 1.0, bid * 2.0, TRADER_NAME.to_string())
+        .unwrap();
+    let bid = market.get_buy_price(GoodKind::USD, 1.0).unwrap();
+    let second = market.lock_buy(GoodKind::USD, 1.0, bid * 2.0, TRADER_NAME.to_string());
+    assert_eq!(second, Err(LockBuyError::MaxAllowedLocksReached));
+}
+
+#[test]
+/// Two markets with the same seed should quote the same prices
+///
+/// SOL group
+fn seeded_markets_should_be_reproducible() {
+    let seeded = definition(Some(7), MarketConfig::default());
+    let first = SOLMarket::try_new_from_definition(&seeded).unwrap();
+    let second = SOLMarket::try_new_from_definition(&seeded).unwrap();
+    for _ in 0..10 {
+        for kind in [GoodKind::YEN, GoodKind::USD, GoodKind::YUAN] {
+            assert_eq!(
+                first.borrow().get_buy_price(kind, 1.0).unwrap(),
+                second.borrow().get_buy_price(kind, 1.0).unwrap()
+            );
+        }
+        first
+            .borrow_mut()
+            .lock_buy(GoodKind::USD, 1.0, 1000.0, TRADER_NAME.to_string())
+            .ok();
+        second
+            .borrow_mut()
+            .lock_buy(GoodKind::USD, 1.0, 1000.0, TRADER_NAME.to_string())
+            .ok();
+    }
+}
+
+#[test]
+/// Files written with another schema version should be rejected
+///
+/// SOL group
+fn unknown_schema_versions_should_be_rejected() {
+    let directory = temp_directory("sol_definition_schema");
+    let mut future = definition(None, MarketConfig::default());
+    future.schema_version = MARKET_DEFINITION_SCHEMA_VERSION + 1;
+    let path = directory.join("market.json");
+    future.write_to_file(&path).unwrap();
+    let result = MarketDefinition::from_file(&path);
+    assert!(matches!(
+        result,
+        Err(SOLMarketError::UnsupportedSchemaVersion { found, .. })
+            if found == MARKET_DEFINITION_SCHEMA_VERSION + 1
+    ));
+    assert!(SOLMarket::try_new_file(path.to_str().unwrap()).is_err());
+    fs::remove_dir_all(&directory).unwrap();
+}
@@ -0,0 +1,318 @@
+use super::{definition_with, market_from};
+use crate::sol_market::{
+    BackgroundFlowConfig, EventError, EventLogConfig, ForwardRequest, ForwardSettlement,
+    ForwardSide, ForwardStatus, MarketConfig, MarketEvent, MarketState, SOLMarket, SOLMarketError,
+    TradeSide, ALL_GOOD_KINDS,
+};
+use std::{cell::RefCell, collections::BTreeSet, fs, rc::Rc};
+use unitn_market_2022::{
+    event::notifiable::Notifiable,
+    good::{consts::DEFAULT_GOOD_KIND, good::Good, good_kind::GoodKind},
+    market::Market,
+    wait_one_day,
+};
+
+const TRADER_NAME: &str = "foobar";
+
+/// A market with much more EUR than anything else, so that it trades
+/// internally, where USD earns interest and synthetic traders trade
+fn market(event_log: EventLogConfig) -> Rc<RefCell<SOLMarket>> {
+    let config = MarketConfig {
+        token_duration: 3,
+        background_flow: BackgroundFlowConfig {
+            enabled: true,
+            ..Default::default()
+        },
+        event_log,
+        ..Default::default()
+    };
+    let mut definition = definition_with(config, 3);
+    for good in definition.goods.iter_mut() {
+        if good.kind == Some(DEFAULT_GOOD_KIND) {
+            good.quantity = 1_000_000.0;
+        } else {
+            good.quantity = 1000.0;
+        }
+        if good.kind == Some(GoodKind::USD) {
+            good.daily_interest_rate = Some(0.001);
+        }
+    }
+    market_from(&definition)
+}
+
+/// Buys and sells some USD, and locks some more that it never redeems
+fn trade(market: &mut SOLMarket) {
+    let bid = market.get_buy_price(GoodKind::USD, 10.0).unwrap();
+    let token = market
+        .lock_buy(GoodKind::USD, 10.0, bid, TRADER_NAME.to_string())
+        .unwrap();
+    market
+        .buy(token, &mut Good::new(DEFAULT_GOOD_KIND, bid))
+        .unwrap();
+
+    let offer = market.get_sell_price(GoodKind::USD, 10.0).unwrap();
+    let token = market
+        .lock_sell(GoodKind::USD, 10.0, offer, TRADER_NAME.to_string())
+        .unwrap();
+    market
+        .sell(token, &mut Good::new(GoodKind::USD, 10.0))
+        .unwrap();
+
+    let bid = market.get_buy_price(GoodKind::YEN, 10.0).unwrap();
+    market
+        .lock_buy(GoodKind::YEN, 10.0, bid, TRADER_NAME.to_string())
+        .unwrap();
+}
+
+/// The fold of the events should be the state of the market
+fn assert_fold_matches(market: &SOLMarket) {
+    let state = market.event_state();
+    assert_eq!(state.day, market.meta.current_day);
+    for kind in ALL_GOOD_KINDS {
+        assert_eq!(
+            state.stock[&kind.to_string()],
+            market.get_available_quantity(kind)
+        );
+    }
+    let live: BTreeSet<&String> = market
+        .meta
+        .locked_buys
+        .keys()
+        .chain(market.meta.locked_sells.keys())
+        .collect();
+    assert_eq!(state.locks.keys().collect::<BTreeSet<_>>(), live);
+}
+
+#[test]
+/// Every change to the market should be in its events
+///
+/// SOL group
+fn the_fold_of_the_events_should_be_the_state() {
+    let market = market(Default::default());
+    assert_fold_matches(&market.borrow());
+    for _ in 0..150 {
+        trade(&mut market.borrow_mut());
+        assert_fold_matches(&market.borrow());
+    }
+    let market = market.borrow();
+    let has = |f: fn(&MarketEvent) -> bool| market.events().any(|(_, e)| f(e));
+    assert!(has(|e| matches!(e, MarketEvent::LockExpired { .. })));
+    assert!(has(|e| matches!(e, MarketEvent::InternalTradeDone { .. })));
+    assert!(has(|e| matches!(e, MarketEvent::StockAdjusted { .. })));
+    assert!(has(|e| matches!(e, MarketEvent::DayAdvanced { .. })));
+}
+
+#[test]
+/// A lock and its redemption should be told by the events
+///
+/// SOL group
+fn locks_should_be_told_by_the_events() {
+    let market = market(Default::default());
+    let bid = market.borrow().get_buy_price(GoodKind::USD, 10.0).unwrap();
+    let token = market
+        .borrow_mut()
+        .lock_buy(GoodKind::USD, 10.0, bid, TRADER_NAME.to_string())
+        .unwrap();
+    let reserved = market
+        .borrow()
+        .events()
+        .find_map(|(seq, e)| match e {
+            MarketEvent::GoodReserved {
+                token: t,
+                side: TradeSide::Buy,
+                good,
+                quantity,
+                price,
+                ..
+            } if *t == token && good == "USD" && *quantity == 10.0 && *price == bid => Some(seq),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(
+        market.borrow().event_state().locks[&token].trader_name,
+        TRADER_NAME
+    );
+
+    market
+        .borrow_mut()
+        .buy(token.clone(), &mut Good::new(DEFAULT_GOOD_KIND, bid))
+        .unwrap();
+    let received = market
+        .borrow()
+        .events()
+        .find_map(|(seq, e)| match e {
+            MarketEvent::CashReceived { token: t, cash } if *t == token && *cash == bid => {
+                Some(seq)
+            }
+            _ => None,
+        })
+        .unwrap();
+    assert!(received > reserved);
+    assert!(!market.borrow().event_state().locks.contains_key(&token));
+}
+
+#[test]
+/// Contracts and margin accounts should live in the fold of the events, and
+/// be replayed with it
+///
+/// SOL group
+fn contracts_and_accounts_should_be_told_by_the_events() {
+    let market = market(Default::default());
+    let before_opening = market.borrow().last_event();
+    market
+        .borrow_mut()
+        .open_margin_account(TRADER_NAME, &mut Good::new(DEFAULT_GOOD_KIND, 100.0))
+        .unwrap();
+    let price = market
+        .borrow()
+        .get_forward_buy_price(GoodKind::USD, 10.0, 2)
+        .unwrap();
+    let request = ForwardRequest {
+        trader_name: TRADER_NAME.to_string(),
+        side: ForwardSide::Buy,
+        good_kind: GoodKind::USD,
+        quantity: 10.0,
+        maturity_day: market.borrow().meta.current_day + 2,
+        settlement: ForwardSettlement::Physical,
+        price,
+    };
+    let id = market
+        .borrow_mut()
+        .enter_forward(request, &mut Good::new(DEFAULT_GOOD_KIND, 1000.0))
+        .unwrap();
+    let entered = market.borrow().last_event();
+    for _ in 0..2 {
+        wait_one_day!(market);
+    }
+
+    let market = market.borrow();
+    assert!(market.events().any(|(_, e)| matches!(
+        e,
+        MarketEvent::ForwardSettled { id: i, .. } if *i == id
+    )));
+    let state = market.event_state();
+    assert_eq!(state.margin_accounts[TRADER_NAME].trader_name, TRADER_NAME);
+    assert_eq!(state.forward_contracts[&id].status, ForwardStatus::Settled);
+    assert_eq!(
+        Some(&state.forward_contracts[&id]),
+        market.forward_contract(&id)
+    );
+
+    let before = market.event_state_at(before_opening).unwrap();
+    assert!(before.margin_accounts.is_empty());
+    let at_entry = market.event_state_at(entered).unwrap();
+    assert_eq!(at_entry.forward_contracts[&id].status, ForwardStatus::Open);
+}
+
+#[test]
+/// An event that takes more of a good than the market has should be refused,
+/// and leave the state as it was
+///
+/// SOL group
+fn events_should_not_make_the_stock_negative() {
+    let market = market(Default::default());
+    let mut state = market.borrow().event_state().clone();
+    let before = state.clone();
+    let event = MarketEvent::InternalTradeDone {
+        sold: "USD".to_string(),
+        sold_quantity: 1001.0,
+        bought: "EUR".to_string(),
+        bought_quantity: 1.0,
+        value: 1.0,
+    };
+    assert_eq!(
+        state.apply(&event),
+        Err(EventError::InsufficientStock {
+            good: "USD".to_string(),
+            stock: 1000.0,
+            change: -1001.0,
+        })
+    );
+    assert_eq!(state, before);
+}
+
+#[test]
+/// Past states should be replayed from the snapshots kept, and no further
+///
+/// SOL group
+fn past_states_should_be_replayed_from_the_snapshots() {
+    let market = market(EventLogConfig {
+        snapshot_every: 7,
+        snapshots_to_keep: 3,
+        ..Default::default()
+    });
+    let mut states: Vec<(u64, MarketState)> = vec![(0, market.borrow().event_state().clone())];
+    for _ in 0..20 {
+        trade(&mut market.borrow_mut());
+        wait_one_day!(market);
+        let market = market.borrow();
+        states.push((market.last_event(), market.event_state().clone()));
+    }
+
+    let market = market.borrow();
+    let first = market.events().next().unwrap().0;
+    assert!(first > 1);
+    assert!(market.last_event() - first < 3 * 7);
+    for (seq, state) in states {
+        if seq + 1 < first {
+            assert_eq!(market.event_state_at(seq), None);
+        } else {
+            assert_eq!(market.event_state_at(seq), Some(state));
+        }
+    }
+    assert_eq!(market.event_state_at(market.last_event() + 1), None);
+}
+
+#[test]
+/// The journal should replay every state of the market, from its last start
+///
+/// SOL group
+fn the_journal_should_replay_the_states() {
+    let directory = std::env::temp_dir().join(format!("sol_journal_{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    let path = directory.join("journal.jsonl");
+    let path_str = path.to_str().unwrap();
+    let config = EventLogConfig {
+        journal_file: Some(path_str.to_string()),
+        ..Default::default()
+    };
+
+    let old_market = market(config.clone());
+    trade(&mut old_market.borrow_mut());
+    wait_one_day!(old_market);
+    let old_state = old_market.borrow().event_state().clone();
+    assert_eq!(
+        SOLMarket::replay_journal(path_str, None).unwrap(),
+        old_state
+    );
+
+    //A new market starts the journal over
+    let market = market(config);
+    let mut states: Vec<(u64, MarketState)> = vec![(0, market.borrow().event_state().clone())];
+    for _ in 0..5 {
+        trade(&mut market.borrow_mut());
+        wait_one_day!(market);
+        let market = market.borrow();
+        states.push((market.last_event(), market.event_state().clone()));
+    }
+    for (seq, state) in states {
+        assert_eq!(
+            SOLMarket::replay_journal(path_str, Some(seq)).unwrap(),
+            state
+        );
+    }
+    let last = market.borrow().last_event();
+    assert!(matches!(
+        SOLMarket::replay_journal(path_str, Some(last + 1)),
+        Err(SOLMarketError::Replay { .. })
+    ));
+
+    //Paper copies do not write to the journal
+    let paper = market.borrow().paper_market();
+    trade(&mut paper.borrow_mut());
+    assert_eq!(
+        SOLMarket::replay_journal(path_str, None).unwrap(),
+        *market.borrow().event_state()
+    );
+    fs::remove_dir_all(&directory).unwrap();
+}
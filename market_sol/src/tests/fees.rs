@@ -0,0 +1,199 @@
+use super::{definition_with, market_with};
+use crate::sol_market::{
+    AuctionConfig, AuctionStatus, Fee, FeeSchedule, FeeTier, MarketConfig, MarketDefinition,
+    SOLMarket, TradeSide,
+};
+use std::{cell::RefCell, fs, rc::Rc};
+use unitn_market_2022::{
+    event::notifiable::Notifiable,
+    good::{consts::DEFAULT_GOOD_KIND, good::Good, good_kind::GoodKind},
+    market::{LockBuyError, Market},
+    wait_one_day,
+};
+
+const TRADER_NAME: &str = "foobar";
+
+fn config(fees: FeeSchedule) -> MarketConfig {
+    MarketConfig {
+        fees,
+        ..Default::default()
+    }
+}
+
+fn market(fees: FeeSchedule) -> Rc<RefCell<SOLMarket>> {
+    market_with(config(fees), 3)
+}
+
+/// 1% plus 1 EUR on buys, 2 EUR on sells
+fn schedule() -> FeeSchedule {
+    FeeSchedule {
+        buy: Fee {
+            percentage: 0.01,
+            fixed: 1.0,
+        },
+        sell: Fee {
+            percentage: 0.0,
+            fixed: 2.0,
+        },
+        ..Default::default()
+    }
+}
+
+#[test]
+/// Prices should include the fees, and quotes should report them apart
+///
+/// SOL group
+fn prices_should_include_the_fees() {
+    let free = market(Default::default());
+    let charged = market(schedule());
+    let (free, charged) = (free.borrow(), charged.borrow());
+
+    let buy = free.get_buy_price(GoodKind::USD, 100.0).unwrap();
+    let buy_with_fee = charged.get_buy_price(GoodKind::USD, 100.0).unwrap();
+    assert!((buy_with_fee - (buy * 1.01 + 1.0)).abs() < 1e-4);
+    let sell = free.get_sell_price(GoodKind::USD, 100.0).unwrap();
+    let sell_with_fee = charged.get_sell_price(GoodKind::USD, 100.0).unwrap();
+    assert!((sell_with_fee - (sell - 2.0)).abs() < 1e-4);
+
+    let ladders = charged.get_quote_ladders(&[100.0]);
+    let quote = &ladders[&GoodKind::USD].quotes[0];
+    assert_eq!(quote.buy_price, Ok(buy_with_fee));
+    assert_eq!(quote.sell_price, Ok(sell_with_fee));
+    assert!((quote.buy_fee - (buy * 0.01 + 1.0)).abs() < 1e-4);
+    assert_eq!(quote.sell_fee, 2.0);
+    // Forward contracts do not pay fees
+    let ladders = charged.get_forward_quote_ladders(&[100.0], 10);
+    assert_eq!(ladders[&GoodKind::USD].quotes[0].buy_fee, 0.0);
+}
+
+#[test]
+/// Locks should need the fee, and trades should count it
+///
+/// SOL group
+fn trades_should_pay_the_fees() {
+    let market = market(schedule());
+    let mut m = market.borrow_mut();
+    let trader_name = TRADER_NAME.to_string();
+
+    let bid = m.get_buy_price(GoodKind::USD, 100.0).unwrap();
+    let result = m.lock_buy(GoodKind::USD, 100.0, bid - 0.5, trader_name.clone());
+    assert!(matches!(result, Err(LockBuyError::BidTooLow { .. })));
+    let token = m
+        .lock_buy(GoodKind::USD, 100.0, bid, trader_name.clone())
+        .unwrap();
+    m.buy(token, &mut Good::new(DEFAULT_GOOD_KIND, bid))
+        .unwrap();
+
+    let offer = m.get_sell_price(GoodKind::YEN, 1000.0).unwrap();
+    let token = m
+        .lock_sell(GoodKind::YEN, 1000.0, offer, trader_name)
+        .unwrap();
+    m.sell(token, &mut Good::new(GoodKind::YEN, 1000.0))
+        .unwrap();
+
+    let fees = m.trader_fees(TRADER_NAME);
+    assert!(fees.buy_fees > 1.0);
+    assert_eq!(fees.sell_fees, 2.0);
+    assert!((fees.volume - (bid - fees.buy_fees + offer + 2.0)).abs() < 1e-3);
+    let report = m.report();
+    assert_eq!(report.fees.buy, fees.buy_fees);
+    assert_eq!(report.fees.sell, fees.sell_fees);
+}
+
+#[test]
+/// Traders should get the discount of their tier, while their volume lasts
+///
+/// SOL group
+fn tiers_should_discount_the_fees() {
+    let market = market(FeeSchedule {
+        window_days: 10,
+        tiers: vec![
+            FeeTier {
+                min_volume: 50.0,
+                discount: 0.5,
+            },
+            FeeTier {
+                min_volume: 1e9,
+                discount: 1.0,
+            },
+        ],
+        ..schedule()
+    });
+    let bid = market.borrow().get_buy_price(GoodKind::USD, 100.0).unwrap();
+    {
+        let mut m = market.borrow_mut();
+        let token = m
+            .lock_buy(GoodKind::USD, 100.0, bid, TRADER_NAME.to_string())
+            .unwrap();
+        m.buy(token, &mut Good::new(DEFAULT_GOOD_KIND, bid))
+            .unwrap();
+        assert_eq!(m.trader_fees(TRADER_NAME).discount, 0.5);
+        let fee = m.get_fee(None, TradeSide::Sell, 100.0);
+        let discounted = m.get_fee(Some(TRADER_NAME), TradeSide::Sell, 100.0);
+        assert_eq!(discounted, fee / 2.0);
+        assert_eq!(m.get_fee(Some("barfoo"), TradeSide::Sell, 100.0), fee);
+    }
+
+    for _ in 0..10 {
+        wait_one_day!(market);
+    }
+    let fees = market.borrow().trader_fees(TRADER_NAME);
+    assert_eq!((fees.volume, fees.discount), (0.0, 0.0));
+}
+
+#[test]
+/// Fee schedules should survive a round trip through a TOML file
+///
+/// SOL group
+fn fees_should_roundtrip_through_toml() {
+    let directory = std::env::temp_dir().join(format!("sol_fees_{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    let path = directory.join("market.toml");
+    let fees = FeeSchedule {
+        maker: Some(Fee {
+            percentage: 0.0,
+            fixed: 0.5,
+        }),
+        tiers: vec![FeeTier {
+            min_volume: 1000.0,
+            discount: 0.1,
+        }],
+        ..schedule()
+    };
+    let original = definition_with(config(fees), 3);
+    original.write_to_file(&path).unwrap();
+    assert_eq!(MarketDefinition::from_file(&path).unwrap(), original);
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+/// Auction orders filled at the clearing should pay the maker fee
+///
+/// SOL group
+fn auction_orders_should_pay_the_maker_fee() {
+    let mut config = config(FeeSchedule {
+        maker: Some(Fee {
+            percentage: 0.0,
+            fixed: 0.5,
+        }),
+        ..schedule()
+    });
+    config.auction = AuctionConfig {
+        enabled: true,
+        every_days: 3,
+    };
+    let market = market_with(config, 3);
+    let ask = market.borrow().get_buy_price(GoodKind::USD, 1.0).unwrap();
+    let token = market
+        .borrow_mut()
+        .lock_buy(GoodKind::USD, 100.0, 200.0 * ask, TRADER_NAME.to_string())
+        .unwrap();
+
+    wait_one_day!(market);
+    let market = market.borrow();
+    match market.auction_order(&token).unwrap().status {
+        AuctionStatus::Filled { fee, .. } => assert_eq!(fee, 0.5),
+        status => panic!("The order should be filled, it is {status:?}"),
+    }
+    assert_eq!(market.get_fee(None, TradeSide::Buy, 100.0), 2.0);
+}
@@ -0,0 +1,269 @@
+use crate::sol_market::{
+    ForwardError, ForwardRequest, ForwardSettlement, ForwardSide, ForwardStatus, SOLMarket,
+    SOLMarketError, SimulatedClock,
+};
+use chrono::Local;
+use std::{cell::RefCell, fs, rc::Rc};
+use unitn_market_2022::{
+    good::{consts::DEFAULT_GOOD_KIND, good::Good, good_kind::GoodKind},
+    market::Market,
+    wait_one_day,
+};
+
+const TRADER_NAME: &str = "foobar";
+
+fn request(
+    market: &SOLMarket,
+    side: ForwardSide,
+    settlement: ForwardSettlement,
+    days_ahead: u32,
+) -> ForwardRequest {
+    let quantity = 100.0;
+    let price = match side {
+        ForwardSide::Buy => market
+            .get_forward_buy_price(GoodKind::USD, quantity, days_ahead)
+            .unwrap(),
+        ForwardSide::Sell => market
+            .get_forward_sell_price(GoodKind::USD, quantity, days_ahead)
+            .unwrap(),
+    };
+    ForwardRequest {
+        trader_name: TRADER_NAME.to_string(),
+        side,
+        good_kind: GoodKind::USD,
+        quantity,
+        maturity_day: market.meta.current_day + days_ahead,
+        settlement,
+        price,
+    }
+}
+
+fn wait_days(market: &Rc<RefCell<SOLMarket>>, days: u32) {
+    let dyn_market: Rc<RefCell<dyn Market>> = market.clone();
+    for _ in 0..days {
+        wait_one_day!(dyn_market);
+    }
+}
+
+#[test]
+/// A physical forward should deliver the good on the maturity day
+///
+/// SOL group
+fn physical_forward_should_deliver_at_maturity() {
+    let market = SOLMarket::try_new_with_quantities(10000.0, 10000.0, 10000.0, 10000.0).unwrap();
+    let request = request(
+        &market.borrow(),
+        ForwardSide::Buy,
+        ForwardSettlement::Physical,
+        5,
+    );
+    let price = request.price;
+    let usd_before = market.borrow().get_available_quantity(GoodKind::USD);
+    let mut cash = Good::new(DEFAULT_GOOD_KIND, price + 1.0);
+    let id = market
+        .borrow_mut()
+        .enter_forward(request, &mut cash)
+        .unwrap();
+    assert!((cash.get_qty() - 1.0).abs() < 0.001);
+    assert_eq!(
+        market.borrow().get_available_quantity(GoodKind::USD),
+        usd_before - 100.0
+    );
+
+    //Not settled yet
+    let early = market.borrow_mut().collect_forward(&id);
+    assert!(matches!(
+        early,
+        Err(ForwardError::NothingToCollect {
+            status: ForwardStatus::Open,
+            ..
+        })
+    ));
+
+    wait_days(&market, 5);
+    assert_eq!(
+        market.borrow().forward_contract(&id).unwrap().status,
+        ForwardStatus::Settled
+    );
+    let delivered = market.borrow_mut().collect_forward(&id).unwrap();
+    assert_eq!(delivered.get_kind(), GoodKind::USD);
+    assert_eq!(delivered.get_qty(), 100.0);
+    assert_eq!(
+        market.borrow_mut().collect_forward(&id),
+        Err(ForwardError::UnrecognizedContract { id })
+    );
+}
+
+#[test]
+/// A cash-settled forward should pay at most the two margins
+///
+/// SOL group
+fn cash_forward_should_be_capped_by_the_margins() {
+    let market = SOLMarket::try_new_with_quantities(10000.0, 10000.0, 10000.0, 10000.0).unwrap();
+    let request = request(
+        &market.borrow(),
+        ForwardSide::Sell,
+        ForwardSettlement::Cash,
+        3,
+    );
+    let margin = request.price * market.borrow().meta.config.forward_margin_ratio;
+    let mut cash = Good::new(DEFAULT_GOOD_KIND, margin);
+    let id = market
+        .borrow_mut()
+        .enter_forward(request, &mut cash)
+        .unwrap();
+    assert_eq!(cash.get_qty(), 0.0);
+
+    wait_days(&market, 3);
+    let payout = market.borrow_mut().collect_forward(&id).unwrap();
+    assert_eq!(payout.get_kind(), DEFAULT_GOOD_KIND);
+    assert!(payout.get_qty() >= 0.0);
+    assert!(payout.get_qty() <= margin * 2.0 + 0.001);
+}
+
+#[test]
+/// Invalid requests should be rejected without taking the collateral
+///
+/// SOL group
+fn enter_forward_should_reject_invalid_requests() {
+    let market = SOLMarket::try_new_with_quantities(10000.0, 10000.0, 10000.0, 10000.0).unwrap();
+    let mut market = market.borrow_mut();
+    let mut cash = Good::new(DEFAULT_GOOD_KIND, 1_000_000.0);
+
+    let mut past = request(&market, ForwardSide::Buy, ForwardSettlement::Physical, 1);
+    past.maturity_day = market.meta.current_day;
+    assert!(matches!(
+        market.enter_forward(past, &mut cash),
+        Err(ForwardError::MaturityNotInTheFuture { .. })
+    ));
+
+    let mut cheap = request(&market, ForwardSide::Buy, ForwardSettlement::Physical, 1);
+    cheap.price /= 2.0;
+    assert!(matches!(
+        market.enter_forward(cheap, &mut cash),
+        Err(ForwardError::UnacceptablePrice { .. })
+    ));
+
+    let sell = request(&market, ForwardSide::Sell, ForwardSettlement::Physical, 1);
+    assert_eq!(
+        market.enter_forward(sell.clone(), &mut cash),
+        Err(ForwardError::WrongCollateralKind {
+            expected: GoodKind::USD,
+            provided: DEFAULT_GOOD_KIND,
+        })
+    );
+    let mut few_dollars = Good::new(GoodKind::USD, 1.0);
+    assert!(matches!(
+        market.enter_forward(sell, &mut few_dollars),
+        Err(ForwardError::InsufficientCollateral { .. })
+    ));
+
+    assert_eq!(cash.get_qty(), 1_000_000.0);
+    assert_eq!(few_dollars.get_qty(), 1.0);
+    assert!(market.forward_contracts_of(TRADER_NAME).is_empty());
+}
+
+#[test]
+/// Open contracts should survive in the state file
+///
+/// SOL group
+fn forwards_should_be_persisted() {
+    let directory = std::env::temp_dir().join(format!("sol_forwards_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&directory);
+    let path = directory.join("market.json");
+    let path_str = path.to_str().unwrap();
+
+    let id = {
+        let market = SOLMarket::try_new_file(path_str).unwrap();
+        let request = request(
+            &market.borrow(),
+            ForwardSide::Buy,
+            ForwardSettlement::Cash,
+            10,
+        );
+        let mut cash = Good::new(DEFAULT_GOOD_KIND, request.price);
+        let id = market
+            .borrow_mut()
+            .enter_forward(request, &mut cash)
+            .unwrap();
+        id
+    };
+
+    let market = SOLMarket::try_new_file(path_str).unwrap();
+    let market_ref = market.borrow();
+    let contracts = market_ref.forward_contracts_of(TRADER_NAME);
+    assert_eq!(contracts.len(), 1);
+    assert_eq!(contracts[0].id, id);
+    assert_eq!(contracts[0].status, ForwardStatus::Open);
+    assert!(market_ref.forward_contracts_of("someone else").is_empty());
+
+    drop(market_ref);
+    drop(market);
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+/// A `.sol` state file can not keep contracts, so it should not be saved
+/// without them
+///
+/// SOL group
+fn forwards_should_not_be_dropped_from_sol_files() {
+    let directory = std::env::temp_dir().join(format!("sol_forwards_sol_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(&directory).unwrap();
+    let path = directory.join("market.sol");
+    let contents = "good EUR 10000 1\ngood YEN 10000 1\ngood USD 10000 1\ngood YUAN 10000 1\n";
+    fs::write(&path, contents).unwrap();
+
+    let market = SOLMarket::try_new_file(path.to_str().unwrap()).unwrap();
+    market.borrow().save_snapshot().unwrap();
+    let saved = fs::read_to_string(&path).unwrap();
+    let request = request(
+        &market.borrow(),
+        ForwardSide::Buy,
+        ForwardSettlement::Physical,
+        5,
+    );
+    let mut cash = Good::new(DEFAULT_GOOD_KIND, request.price);
+    market
+        .borrow_mut()
+        .enter_forward(request, &mut cash)
+        .unwrap();
+
+    let result = market.borrow().save_snapshot();
+    assert!(matches!(result, Err(SOLMarketError::LossySolFile { .. })));
+    assert_eq!(fs::read_to_string(&path).unwrap(), saved);
+
+    drop(market);
+    assert_eq!(fs::read_to_string(&path).unwrap(), saved);
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+/// Identical contracts made at the same time should each get their own id,
+/// and keep their own collateral
+///
+/// SOL group
+fn identical_forwards_should_get_their_own_id() {
+    let market = SOLMarket::try_new_with_quantities(10000.0, 10000.0, 10000.0, 10000.0).unwrap();
+    market
+        .borrow_mut()
+        .set_clock(Box::new(SimulatedClock::new(Local::now())));
+    let request = request(
+        &market.borrow(),
+        ForwardSide::Buy,
+        ForwardSettlement::Physical,
+        5,
+    );
+    let mut cash = Good::new(DEFAULT_GOOD_KIND, 2.0 * request.price);
+    let first = market
+        .borrow_mut()
+        .enter_forward(request.clone(), &mut cash)
+        .unwrap();
+    let second = market
+        .borrow_mut()
+        .enter_forward(request, &mut cash)
+        .unwrap();
+    assert_ne!(first, second);
+    assert_eq!(market.borrow().forward_contracts_of(TRADER_NAME).len(), 2);
+}
@@ -0,0 +1,221 @@
+use crate::sol_market::{
+    GoodDefinition, GoodSpec, GoodsRegistry, MarketDefinition, SOLMarket, SOLMarketError,
+    WeightsDefinition,
+};
+use unitn_market_2022::event::event::{Event, EventKind};
+use unitn_market_2022::event::notifiable::Notifiable;
+use unitn_market_2022::good::good_kind::GoodKind;
+use unitn_market_2022::market::Market;
+
+const SOL_FILE: &str = "good EUR 10000 1\ngood YEN 10000 1\ngood USD 10000 1\ngood YUAN 10000 1\n";
+
+#[test]
+/// The default registry should describe the goods of the protocol
+///
+/// SOL group
+fn default_registry_should_match_the_protocol() {
+    let registry = GoodsRegistry::with_protocol_goods();
+    for kind in [GoodKind::EUR, GoodKind::YEN, GoodKind::USD, GoodKind::YUAN] {
+        let ticker = registry.ticker_of(kind);
+        assert_eq!(ticker, kind.to_string());
+        let spec = registry.get(ticker).unwrap();
+        assert_eq!(spec.default_rate, kind.get_default_exchange_rate());
+        assert_eq!(registry.kind_of(ticker), Some(kind));
+    }
+    assert!(registry.get("BTC").is_none());
+    assert_eq!(registry.traded().count(), 4);
+}
+
+#[test]
+/// A good should not be registered twice, under its ticker or its kind
+///
+/// SOL group
+fn try_register_should_reject_duplicates() {
+    let mut registry = GoodsRegistry::new();
+    registry.try_register(GoodSpec::new(GoodKind::USD)).unwrap();
+    assert_eq!(registry.iter().count(), 1);
+
+    let result = registry.try_register(GoodSpec::new(GoodKind::USD));
+    assert!(matches!(result, Err(SOLMarketError::DuplicateGood { .. })));
+    let same_kind = GoodSpec {
+        ticker: String::from("DOLLAR"),
+        ..GoodSpec::new(GoodKind::USD)
+    };
+    assert!(registry.try_register(same_kind).is_err());
+    let same_ticker = GoodSpec {
+        ticker: String::from("USD"),
+        ..GoodSpec::new(GoodKind::YEN)
+    };
+    assert!(registry.try_register(same_ticker).is_err());
+    assert_eq!(registry.iter().count(), 1);
+}
+
+#[test]
+/// The default rate of a good should be where its price starts from
+///
+/// SOL group
+fn default_rate_should_drive_the_price() {
+    let mut definition = MarketDefinition::from_sol_str(SOL_FILE).unwrap();
+    //Only look at the quantity strategy, which starts at the default rate
+    definition.weights = WeightsDefinition {
+        stocastic: Some(0.0),
+        quantity: Some(1.0),
+        others: Some(0.0),
+    };
+    let usd = definition
+        .goods
+        .iter_mut()
+        .find(|g| g.kind == Some(GoodKind::USD))
+        .unwrap();
+    usd.default_rate = Some(2.0);
+    usd.volatility = Some(0.5);
+
+    let market = SOLMarket::try_new_from_definition(&definition).unwrap();
+    let market = market.borrow();
+    assert_eq!(market.get_good_buy_exchange_rate(GoodKind::USD), 2.0);
+    let spec = market.goods_registry().get("USD").unwrap();
+    assert_eq!(spec.volatility, 0.5);
+    assert_eq!(spec.initial_stock, 10000.0);
+
+    //The parameters are saved along with the market
+    let saved = market.to_definition();
+    let saved_usd = saved
+        .goods
+        .iter()
+        .find(|g| g.kind == Some(GoodKind::USD))
+        .unwrap();
+    assert_eq!(saved_usd.default_rate, Some(2.0));
+    assert_eq!(saved_usd.volatility, Some(0.5));
+}
+
+#[test]
+/// Goods should not have a non positive volatility
+///
+/// SOL group
+fn invalid_good_parameters_should_be_rejected() {
+    let mut definition = MarketDefinition::from_sol_str(SOL_FILE).unwrap();
+    definition.goods[1].volatility = Some(0.0);
+    let result = SOLMarket::try_new_from_definition(&definition);
+    match result {
+        Err(SOLMarketError::InvalidGoodParameter {
+            ticker, parameter, ..
+        }) => {
+            assert_eq!(ticker, "YEN");
+            assert_eq!(parameter, "volatility");
+        }
+        _ => panic!("Should have returned an InvalidGoodParameter error"),
+    }
+}
+
+#[test]
+/// A good that is not of the protocol should have a stock and a price, but
+/// traders should not see it
+///
+/// SOL group
+fn instruments_should_be_simulated_but_not_traded() {
+    let mut definition = MarketDefinition::from_sol_str(SOL_FILE).unwrap();
+    definition.goods.push(GoodDefinition {
+        default_rate: Some(2.0),
+        ..GoodDefinition::instrument("GBP", 1000.0)
+    });
+    let market = SOLMarket::try_new_from_definition(&definition).unwrap();
+    let mut market = market.borrow_mut();
+
+    let registry = market.goods_registry();
+    assert_eq!(registry.kind_of("GBP"), None);
+    assert_eq!(registry.get("GBP").unwrap().initial_stock, 1000.0);
+    assert_eq!(registry.traded().count(), 4);
+    assert_eq!(market.get_goods().len(), 4);
+    assert_eq!(market.stock("GBP"), 1000.0);
+    assert!(market.buy_rate("GBP") > 0.0);
+    assert!(market.event_state().stock.contains_key("GBP"));
+
+    //The instrument keeps its stock across the days
+    for _ in 0..10 {
+        market.on_event(Event {
+            kind: EventKind::Wait,
+            good_kind: GoodKind::EUR,
+            quantity: 0.0,
+            price: 0.0,
+        });
+    }
+    assert!(market.stock("GBP") > 0.0);
+    let saved = market.to_definition();
+    let gbp = saved
+        .goods
+        .iter()
+        .find(|g| g.ticker.as_deref() == Some("GBP"))
+        .unwrap();
+    assert_eq!(gbp.kind, None);
+    assert_eq!(gbp.default_rate, Some(2.0));
+}
+
+#[test]
+/// A good of the protocol should be traded under its kind, whatever its
+/// ticker
+///
+/// SOL group
+fn protocol_goods_should_keep_their_ticker() {
+    let mut definition = MarketDefinition::from_sol_str(SOL_FILE).unwrap();
+    let usd = definition
+        .goods
+        .iter_mut()
+        .find(|g| g.kind == Some(GoodKind::USD))
+        .unwrap();
+    usd.ticker = Some(String::from("DOLLAR"));
+    let market = SOLMarket::try_new_from_definition(&definition).unwrap();
+    let market = market.borrow();
+
+    let registry = market.goods_registry();
+    assert_eq!(registry.ticker_of(GoodKind::USD), "DOLLAR");
+    assert_eq!(registry.kind_of("DOLLAR"), Some(GoodKind::USD));
+    assert!(registry.get("USD").is_none());
+    assert_eq!(market.get_available_quantity(GoodKind::USD), 10000.0);
+    assert_eq!(market.event_state().stock["DOLLAR"], 10000.0);
+
+    //TOML and JSON keep the ticker, `.sol` only knows the kind
+    let saved = market.to_definition();
+    let toml = toml::to_string(&saved).unwrap();
+    assert_eq!(toml::from_str::<MarketDefinition>(&toml).unwrap(), saved);
+    let usd = saved
+        .goods
+        .iter()
+        .find(|g| g.kind == Some(GoodKind::USD))
+        .unwrap();
+    assert_eq!(usd.ticker.as_deref(), Some("DOLLAR"));
+    let sol = MarketDefinition::from_sol_str(&saved.to_sol_string()).unwrap();
+    assert!(sol.goods.iter().all(|g| g.ticker.is_none()));
+}
+
+#[test]
+/// `.sol` files should declare instruments by their ticker
+///
+/// SOL group
+fn sol_files_should_declare_instruments() {
+    let contents = format!("{SOL_FILE}good GBP 500 1\n");
+    let definition = MarketDefinition::from_sol_str(&contents).unwrap();
+    let gbp = definition.goods.last().unwrap();
+    assert_eq!(gbp.kind, None);
+    assert_eq!(gbp.ticker(), Some(String::from("GBP")));
+    assert_eq!(gbp.quantity, 500.0);
+    let written = MarketDefinition::from_sol_str(&definition.to_sol_string()).unwrap();
+    assert_eq!(written.goods.len(), 5);
+    assert!(SOLMarket::try_new_from_definition(&definition).is_ok());
+}
+
+#[test]
+/// A good without a kind nor a ticker should be rejected
+///
+/// SOL group
+fn unnamed_goods_should_be_rejected() {
+    let mut definition = MarketDefinition::from_sol_str(SOL_FILE).unwrap();
+    definition.goods.push(GoodDefinition {
+        ticker: None,
+        ..GoodDefinition::instrument("GBP", 1000.0)
+    });
+    let result = SOLMarket::try_new_from_definition(&definition);
+    assert!(matches!(
+        result,
+        Err(SOLMarketError::UnnamedGood { index: 4 })
+    ));
+}
@@ -0,0 +1,83 @@
+use super::{definition_with, market_with};
+use crate::sol_market::{InstanceEvent, LogSink, MarketConfig, SOLMarket, SOLMarketError};
+use std::{cell::RefCell, rc::Rc};
+use unitn_market_2022::{event::event::EventKind, good::good_kind::GoodKind, market::Market};
+
+const TRADER_NAME: &str = "foobar";
+
+fn config(name: &str) -> MarketConfig {
+    MarketConfig {
+        name: name.to_string(),
+        ..Default::default()
+    }
+}
+
+/// A market with the given name, logging in memory
+fn market(name: &str) -> (Rc<RefCell<SOLMarket>>, Rc<RefCell<Vec<String>>>) {
+    let market = market_with(config(name), 13);
+    let lines = Rc::new(RefCell::new(vec![]));
+    market
+        .borrow_mut()
+        .set_log_sink(LogSink::Memory(lines.clone()));
+    (market, lines)
+}
+
+#[test]
+/// Markets should be told apart by their name, id and log
+///
+/// SOL group
+fn markets_should_have_their_own_identity() {
+    let (north, north_log) = market("SOL_NORTH");
+    let (south, south_log) = market("SOL_SOUTH");
+    assert_eq!(north.borrow().get_name(), "SOL_NORTH");
+    assert_eq!(south.borrow().get_name(), "SOL_SOUTH");
+    assert_ne!(north.borrow().instance_id(), south.borrow().instance_id());
+
+    north
+        .borrow_mut()
+        .lock_sell(GoodKind::EUR, 1.0, 1.0, TRADER_NAME.to_string())
+        .unwrap();
+    assert!(south_log.borrow().is_empty());
+    let north_log = north_log.borrow();
+    assert_eq!(north_log.len(), 1);
+    assert!(north_log[0].starts_with("SOL_NORTH|"));
+    assert!(north_log[0].contains("|LOCK_SELL-foobar-"));
+
+    // The name is part of the configuration
+    assert_eq!(north.borrow().to_definition().config.name, "SOL_NORTH");
+}
+
+#[test]
+/// Listeners should get the events along with the market they come from
+///
+/// SOL group
+fn listeners_should_get_the_instance_of_events() {
+    let (market, _) = market("SOL_LISTENED");
+    let events: Rc<RefCell<Vec<InstanceEvent>>> = Rc::new(RefCell::new(vec![]));
+    let received = events.clone();
+    market
+        .borrow_mut()
+        .add_event_listener(Box::new(move |e| received.borrow_mut().push(e.clone())));
+    market
+        .borrow_mut()
+        .lock_sell(GoodKind::EUR, 1.0, 1.0, TRADER_NAME.to_string())
+        .unwrap();
+
+    let events = events.borrow();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].instance_id, market.borrow().instance_id());
+    assert_eq!(events[0].market_name, "SOL_LISTENED");
+    assert_eq!(events[0].day, 1);
+    assert!(matches!(events[0].event.kind, EventKind::LockedSell));
+}
+
+#[test]
+/// Names that would break the log should be rejected
+///
+/// SOL group
+fn invalid_names_should_be_rejected() {
+    for name in ["", "SOL|2", "SOL\n"] {
+        let result = SOLMarket::try_new_from_definition(&definition_with(config(name), 13));
+        assert!(matches!(result, Err(SOLMarketError::InvalidName { .. })));
+    }
+}
@@ -0,0 +1,137 @@
+use super::market_with;
+use crate::sol_market::{LockDepositConfig, MarketConfig, QuotaError, SOLMarket};
+use std::{cell::RefCell, rc::Rc};
+use unitn_market_2022::{
+    event::notifiable::Notifiable,
+    good::{consts::DEFAULT_GOOD_KIND, good::Good, good_kind::GoodKind},
+    market::{LockBuyError, Market},
+    wait_one_day,
+};
+
+const TRADER_NAME: &str = "foobar";
+const TOKEN_DURATION: u32 = 3;
+
+fn market(lock_deposits: LockDepositConfig) -> Rc<RefCell<SOLMarket>> {
+    let config = MarketConfig {
+        token_duration: TOKEN_DURATION,
+        lock_deposits,
+        ..Default::default()
+    };
+    market_with(config, 9)
+}
+
+/// 10% of the value of the lock, doubled by each recent expiry
+fn config() -> LockDepositConfig {
+    LockDepositConfig {
+        enabled: true,
+        rate: 0.1,
+        escalation: 2.0,
+        max_rate: 0.3,
+        window_days: 100,
+    }
+}
+
+/// A market with deposits, and a trader with 1000 EUR of collateral
+fn market_with_account() -> Rc<RefCell<SOLMarket>> {
+    let market = market(config());
+    market
+        .borrow_mut()
+        .open_margin_account(TRADER_NAME, &mut Good::new(DEFAULT_GOOD_KIND, 1000.0))
+        .unwrap();
+    market
+}
+
+fn collateral(market: &SOLMarket) -> f32 {
+    market
+        .margin_account(TRADER_NAME)
+        .unwrap()
+        .balance(DEFAULT_GOOD_KIND)
+        .collateral
+}
+
+/// Locks 100 USD, and returns the token and the bid
+fn lock_usd(market: &mut SOLMarket) -> Result<(String, f32), LockBuyError> {
+    let bid = market.get_buy_price(GoodKind::USD, 100.0).unwrap();
+    market
+        .lock_buy(GoodKind::USD, 100.0, bid, TRADER_NAME.to_string())
+        .map(|token| (token, bid))
+}
+
+#[test]
+/// Without deposits, locks should not need a margin account
+///
+/// SOL group
+fn locks_should_need_no_deposit_by_default() {
+    let market = market(Default::default());
+    assert!(lock_usd(&mut market.borrow_mut()).is_ok());
+    assert_eq!(market.borrow().lock_deposits(TRADER_NAME).held, 0.0);
+}
+
+#[test]
+/// Locks should be rejected when the collateral does not cover the deposit
+///
+/// SOL group
+fn locks_should_need_the_deposit() {
+    let market = market(config());
+    let result = lock_usd(&mut market.borrow_mut());
+    assert_eq!(result.unwrap_err(), LockBuyError::MaxAllowedLocksReached);
+    assert!(matches!(
+        market.borrow().last_quota_rejection(TRADER_NAME),
+        Some(QuotaError::DepositNotCovered { collateral, .. }) if *collateral == 0.0
+    ));
+}
+
+#[test]
+/// Redeeming a lock should give the deposit back
+///
+/// SOL group
+fn deposits_should_be_refunded_on_redemption() {
+    let market = market_with_account();
+    let (token, bid) = lock_usd(&mut market.borrow_mut()).unwrap();
+    let held = market.borrow().lock_deposits(TRADER_NAME).held;
+    assert!(held > 0.0);
+    assert!((held - bid * 0.1).abs() < 0.01 * held);
+    assert_eq!(collateral(&market.borrow()), 1000.0 - held);
+
+    market
+        .borrow_mut()
+        .buy(token, &mut Good::new(DEFAULT_GOOD_KIND, bid))
+        .unwrap();
+    assert_eq!(collateral(&market.borrow()), 1000.0);
+    let deposits = market.borrow().lock_deposits(TRADER_NAME);
+    assert_eq!(deposits.held, 0.0);
+    assert_eq!(deposits.forfeited, 0.0);
+}
+
+#[test]
+/// Expired locks should leave their deposit to the market, and raise the
+/// rate of the trader
+///
+/// SOL group
+fn expiries_should_forfeit_the_deposit_and_raise_the_rate() {
+    let market = market_with_account();
+    lock_usd(&mut market.borrow_mut()).unwrap();
+    let held = market.borrow().lock_deposits(TRADER_NAME).held;
+
+    for _ in 0..TOKEN_DURATION {
+        wait_one_day!(market);
+    }
+
+    let deposits = market.borrow().lock_deposits(TRADER_NAME);
+    assert_eq!(deposits.expiries, 1);
+    assert_eq!(deposits.held, 0.0);
+    assert_eq!(deposits.forfeited, held);
+    assert!((deposits.rate - 0.2).abs() < 1e-6);
+    assert_eq!(collateral(&market.borrow()), 1000.0 - held);
+
+    // The rate stops at the maximum
+    for _ in 0..3 {
+        lock_usd(&mut market.borrow_mut()).unwrap();
+        for _ in 0..TOKEN_DURATION {
+            wait_one_day!(market);
+        }
+    }
+    let deposits = market.borrow().lock_deposits(TRADER_NAME);
+    assert_eq!(deposits.expiries, 4);
+    assert_eq!(deposits.rate, 0.3);
+}
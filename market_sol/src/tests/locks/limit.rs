@@ -1,7 +1,9 @@
-use crate::sol_market::{SOLMarket, LOCK_LIMIT};
+use crate::sol_market::{SOLMarket, LOCK_LIMIT, TOKEN_DURATION};
 use unitn_market_2022::{
+    event::notifiable::Notifiable,
     good::good_kind::GoodKind,
     market::{LockBuyError, LockSellError, Market},
+    wait_one_day,
 };
 
 const TRADER_NAME: &str = "foobar";
@@ -66,3 +68,34 @@ fn should_return_err_if_lock_buy_and_sell_limit_exceeded() {
     let result = market.lock_sell(GoodKind::EUR, 1.0, 1.0, TRADER_NAME.to_string());
     assert_eq!(result.unwrap_err(), LockSellError::MaxAllowedLocksReached);
 }
+
+#[test]
+/// Expired locks should leave the market, and free their place under the
+/// limit
+///
+/// SOL group
+fn expired_locks_should_not_count_towards_the_limit() {
+    let market_ref = SOLMarket::try_new_with_quantities(1000.0, 1000.0, 1000.0, 1000.0).unwrap();
+    for i in 0..LOCK_LIMIT {
+        let r =
+            market_ref
+                .borrow_mut()
+                .lock_buy(GoodKind::EUR, 1.0, f32::MAX, TRADER_NAME.to_string());
+        assert!(r.is_ok(), "Buy lock number {i} should be successful");
+    }
+    assert_eq!(
+        market_ref.borrow().meta.num_of_buy_locks(TRADER_NAME),
+        LOCK_LIMIT
+    );
+
+    for _ in 0..=TOKEN_DURATION {
+        wait_one_day!(market_ref);
+    }
+
+    let mut market = market_ref.borrow_mut();
+    assert!(market.meta.locked_buys.is_empty());
+    assert_eq!(market.meta.num_of_buy_locks(TRADER_NAME), 0);
+    let result = market.lock_buy(GoodKind::EUR, 1.0, f32::MAX, TRADER_NAME.to_string());
+    assert!(result.is_ok());
+    assert_eq!(market.meta.num_of_buy_locks(TRADER_NAME), 1);
+}
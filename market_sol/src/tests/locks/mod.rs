@@ -1,2 +1,3 @@
 mod expiration;
 mod limit;
+mod quotas;
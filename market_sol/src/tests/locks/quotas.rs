@@ -0,0 +1,138 @@
+use crate::sol_market::{MarketConfig, QuotaError};
+use crate::tests::market_with;
+use std::{cell::RefCell, rc::Rc};
+use unitn_market_2022::{
+    good::{good::Good, good_kind::GoodKind},
+    market::{LockBuyError, LockSellError, Market},
+    wait_one_day,
+};
+
+const TRADER_NAME: &str = "foobar";
+
+#[test]
+/// Locks of nothing, or worth less than `min_lock_value`, should be rejected
+///
+/// SOL group
+fn should_reject_zero_and_dust_locks() {
+    let market = market_with(MarketConfig::default(), 6);
+    let mut market = market.borrow_mut();
+
+    let result = market.lock_buy(GoodKind::USD, 0.0, 1.0, TRADER_NAME.to_string());
+    assert_eq!(
+        result.unwrap_err(),
+        LockBuyError::NonPositiveQuantityToBuy {
+            negative_quantity_to_buy: 0.0
+        }
+    );
+
+    let result = market.lock_sell(GoodKind::EUR, 0.001, 0.0, TRADER_NAME.to_string());
+    assert_eq!(result.unwrap_err(), LockSellError::MaxAllowedLocksReached);
+    assert!(matches!(
+        market.last_quota_rejection(TRADER_NAME),
+        Some(QuotaError::DustLock { .. })
+    ));
+}
+
+#[test]
+/// Lock requests beyond `max_locks_per_window` should be rejected until the
+/// window passes
+///
+/// SOL group
+fn should_limit_lock_requests_in_the_window() {
+    let config = MarketConfig {
+        max_locks_per_window: Some(3),
+        quota_window_days: 10,
+        ..Default::default()
+    };
+    let market = market_with(config, 6);
+
+    for i in 0..3 {
+        let r = market
+            .borrow_mut()
+            .lock_sell(GoodKind::EUR, 1.0, 1.0, TRADER_NAME.to_string());
+        assert!(r.is_ok(), "Lock number {i} should be successful");
+    }
+    let result = market
+        .borrow_mut()
+        .lock_sell(GoodKind::EUR, 1.0, 1.0, TRADER_NAME.to_string());
+    assert_eq!(result.unwrap_err(), LockSellError::MaxAllowedLocksReached);
+    assert!(matches!(
+        market.borrow().last_quota_rejection(TRADER_NAME),
+        Some(QuotaError::TooManyLocks { limit: 3, .. })
+    ));
+    assert_eq!(market.borrow().quota_usage(TRADER_NAME).locks_in_window, 4);
+
+    // Other traders have their own quota
+    let other = market
+        .borrow_mut()
+        .lock_sell(GoodKind::EUR, 1.0, 1.0, String::from("other"));
+    assert!(other.is_ok());
+
+    // Once the window has passed, the trader can lock again
+    let dyn_market: Rc<RefCell<dyn Market>> = market.clone();
+    for _ in 0..10 {
+        wait_one_day!(dyn_market);
+    }
+    let result = market
+        .borrow_mut()
+        .lock_sell(GoodKind::EUR, 1.0, 1.0, TRADER_NAME.to_string());
+    assert!(result.is_ok());
+}
+
+#[test]
+/// Locks should not take the value locked by a trader above
+/// `max_locked_value`
+///
+/// SOL group
+fn should_limit_the_locked_value() {
+    let config = MarketConfig {
+        max_locked_value: Some(100.0),
+        ..Default::default()
+    };
+    let market = market_with(config, 6);
+    let mut market = market.borrow_mut();
+
+    assert!(market
+        .lock_sell(GoodKind::EUR, 60.0, 60.0, TRADER_NAME.to_string())
+        .is_ok());
+    let result = market.lock_sell(GoodKind::EUR, 50.0, 50.0, TRADER_NAME.to_string());
+    assert_eq!(result.unwrap_err(), LockSellError::MaxAllowedLocksReached);
+    assert_eq!(
+        market.last_quota_rejection(TRADER_NAME),
+        Some(&QuotaError::LockedValueExceeded {
+            trader_name: TRADER_NAME.to_string(),
+            locked_value: 60.0,
+            requested_value: 50.0,
+            limit: 100.0,
+        })
+    );
+    assert!(market
+        .lock_sell(GoodKind::EUR, 40.0, 40.0, TRADER_NAME.to_string())
+        .is_ok());
+    assert_eq!(market.quota_usage(TRADER_NAME).locked_value, 100.0);
+}
+
+#[test]
+/// Trades should count toward `max_locks_per_window`, for the trader who
+/// made the lock
+///
+/// SOL group
+fn trades_should_count_in_the_window() {
+    let config = MarketConfig {
+        max_locks_per_window: Some(2),
+        quota_window_days: 10,
+        ..Default::default()
+    };
+    let market = market_with(config, 6);
+    let mut market = market.borrow_mut();
+
+    let token = market
+        .lock_sell(GoodKind::USD, 10.0, 1.0, TRADER_NAME.to_string())
+        .unwrap();
+    let mut usd = Good::new(GoodKind::USD, 10.0);
+    assert!(market.sell(token, &mut usd).is_ok());
+    assert_eq!(market.quota_usage(TRADER_NAME).locks_in_window, 2);
+
+    let result = market.lock_sell(GoodKind::USD, 10.0, 1.0, TRADER_NAME.to_string());
+    assert_eq!(result.unwrap_err(), LockSellError::MaxAllowedLocksReached);
+}
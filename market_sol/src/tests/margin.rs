@@ -0,0 +1,186 @@
+use super::market_with;
+use crate::sol_market::{MarginError, MarketConfig, SOLMarket, SOLMarketError};
+use std::{cell::RefCell, fs, rc::Rc};
+use unitn_market_2022::{
+    good::{consts::DEFAULT_GOOD_KIND, good::Good},
+    market::Market,
+    wait_one_day,
+};
+
+const TRADER_NAME: &str = "foobar";
+
+fn eur(quantity: f32) -> Good {
+    Good::new(DEFAULT_GOOD_KIND, quantity)
+}
+
+fn wait_days(market: &Rc<RefCell<SOLMarket>>, days: u32) {
+    let dyn_market: Rc<RefCell<dyn Market>> = market.clone();
+    for _ in 0..days {
+        wait_one_day!(dyn_market);
+    }
+}
+
+#[test]
+/// A trader should be able to borrow up to the leverage limit, pay back and
+/// get the collateral back
+///
+/// SOL group
+fn margin_account_lifecycle() {
+    let market = market_with(MarketConfig::default(), 5);
+    let mut market = market.borrow_mut();
+    let mut collateral = eur(1000.0);
+    market
+        .open_margin_account(TRADER_NAME, &mut collateral)
+        .unwrap();
+    assert_eq!(collateral.get_qty(), 0.0);
+    assert_eq!(
+        market.open_margin_account(TRADER_NAME, &mut eur(1.0)),
+        Err(MarginError::AccountAlreadyOpen {
+            trader_name: TRADER_NAME.to_string()
+        })
+    );
+
+    // With a leverage of 3, the debt can be up to 3/4 of the collateral
+    let mut borrowed = market
+        .margin_borrow(TRADER_NAME, DEFAULT_GOOD_KIND, 700.0)
+        .unwrap();
+    assert_eq!(borrowed.get_qty(), 700.0);
+    assert!(matches!(
+        market.margin_borrow(TRADER_NAME, DEFAULT_GOOD_KIND, 100.0),
+        Err(MarginError::LeverageExceeded { .. })
+    ));
+    assert!(matches!(
+        market.withdraw_collateral(TRADER_NAME, DEFAULT_GOOD_KIND, 100.0),
+        Err(MarginError::LeverageExceeded { .. })
+    ));
+    assert!(matches!(
+        market.close_margin_account(TRADER_NAME),
+        Err(MarginError::OutstandingDebt { .. })
+    ));
+    assert_eq!(market.to_definition().margin_accounts.len(), 1);
+
+    borrowed.merge(eur(50.0)).unwrap();
+    market.margin_repay(TRADER_NAME, &mut borrowed).unwrap();
+    assert_eq!(borrowed.get_qty(), 50.0);
+    let summary = market.margin_summary(TRADER_NAME).unwrap();
+    assert_eq!(summary.debt_value, 0.0);
+
+    let returned = market.close_margin_account(TRADER_NAME).unwrap();
+    assert_eq!(returned.len(), 1);
+    assert_eq!(returned[0].get_qty(), 1000.0);
+    assert!(market.margin_account(TRADER_NAME).is_none());
+}
+
+#[test]
+/// Debts should grow by the daily interest rate
+///
+/// SOL group
+fn margin_debt_should_earn_interest() {
+    let config = MarketConfig {
+        margin_daily_interest_rate: 0.01,
+        ..Default::default()
+    };
+    let market = market_with(config, 5);
+    market
+        .borrow_mut()
+        .open_margin_account(TRADER_NAME, &mut eur(1000.0))
+        .unwrap();
+    market
+        .borrow_mut()
+        .margin_borrow(TRADER_NAME, DEFAULT_GOOD_KIND, 100.0)
+        .unwrap();
+
+    wait_days(&market, 2);
+    let debt = market
+        .borrow()
+        .margin_account(TRADER_NAME)
+        .unwrap()
+        .balance(DEFAULT_GOOD_KIND)
+        .debt;
+    assert!((debt - 100.0 * 1.01 * 1.01).abs() < 0.001);
+}
+
+#[test]
+/// An account below the maintenance margin should be liquidated
+///
+/// SOL group
+fn margin_account_should_be_liquidated() {
+    let config = MarketConfig {
+        margin_daily_interest_rate: 0.5,
+        ..Default::default()
+    };
+    let market = market_with(config, 5);
+    market
+        .borrow_mut()
+        .open_margin_account(TRADER_NAME, &mut eur(1000.0))
+        .unwrap();
+    market
+        .borrow_mut()
+        .margin_borrow(TRADER_NAME, DEFAULT_GOOD_KIND, 700.0)
+        .unwrap();
+
+    // The debt becomes 1050, more than the collateral: nothing is left
+    wait_days(&market, 1);
+    let market = market.borrow();
+    let account = market.margin_account(TRADER_NAME).unwrap();
+    assert!(!account.has_debt());
+    assert_eq!(account.balance(DEFAULT_GOOD_KIND).collateral, 0.0);
+}
+
+#[test]
+/// Operations on a missing account should fail, and dust should be rejected
+///
+/// SOL group
+fn margin_operations_without_account_should_fail() {
+    let market = market_with(MarketConfig::default(), 5);
+    let mut market = market.borrow_mut();
+    let no_account = Err(MarginError::NoAccount {
+        trader_name: TRADER_NAME.to_string(),
+    });
+    assert_eq!(
+        market.margin_borrow(TRADER_NAME, DEFAULT_GOOD_KIND, 1.0),
+        no_account.clone()
+    );
+    assert_eq!(
+        market.deposit_collateral(TRADER_NAME, &mut eur(1.0)),
+        no_account.clone().map(|_: Good| ())
+    );
+    assert_eq!(
+        market.open_margin_account(TRADER_NAME, &mut eur(0.0)),
+        Err(MarginError::NonPositiveQuantity { quantity: 0.0 })
+    );
+    assert!(market.margin_account(TRADER_NAME).is_none());
+}
+
+#[test]
+/// Margin accounts should not be written to a `.sol` file, which can not
+/// keep their collateral
+///
+/// SOL group
+fn margin_accounts_should_not_be_dropped_from_sol_files() {
+    let market = market_with(MarketConfig::default(), 5);
+    let directory = std::env::temp_dir().join(format!("sol_margin_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(&directory).unwrap();
+    let path = directory.join("market.sol");
+
+    market
+        .borrow_mut()
+        .open_margin_account(TRADER_NAME, &mut eur(1000.0))
+        .unwrap();
+    let result = market.borrow().to_definition().write_to_file(&path);
+    assert!(matches!(result, Err(SOLMarketError::LossySolFile { .. })));
+    assert!(!path.exists());
+
+    //Once the account is closed, there is nothing to lose
+    market
+        .borrow_mut()
+        .close_margin_account(TRADER_NAME)
+        .unwrap();
+    market
+        .borrow()
+        .to_definition()
+        .write_to_file(&path)
+        .unwrap();
+    fs::remove_dir_all(&directory).unwrap();
+}
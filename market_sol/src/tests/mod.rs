@@ -1,14 +1,86 @@
 #[cfg(test)]
+mod auction;
+#[cfg(test)]
+mod background_flow;
+#[cfg(test)]
 mod buy;
 #[cfg(test)]
+mod carry;
+#[cfg(test)]
+mod clock;
+#[cfg(test)]
 mod constructor;
 #[cfg(test)]
+mod definition;
+#[cfg(test)]
+mod event_log;
+#[cfg(test)]
+mod fees;
+#[cfg(test)]
+mod forwards;
+#[cfg(test)]
+mod goods_registry;
+#[cfg(test)]
+mod instances;
+#[cfg(test)]
 mod internal_trade;
 #[cfg(test)]
+mod lock_deposits;
+#[cfg(test)]
 mod locks;
 #[cfg(test)]
+mod margin;
+#[cfg(test)]
 mod market;
 #[cfg(test)]
+mod options;
+#[cfg(test)]
+mod paper;
+#[cfg(test)]
 mod prices;
 #[cfg(test)]
+mod quotes;
+#[cfg(test)]
+mod remote;
+#[cfg(test)]
+mod report;
+#[cfg(test)]
 mod sell;
+#[cfg(test)]
+mod simulation;
+#[cfg(test)]
+mod snapshots;
+#[cfg(test)]
+mod surveillance;
+#[cfg(test)]
+mod weight_tuning;
+
+use crate::sol_market::{
+    GoodDefinition, LogSink, MarketConfig, MarketDefinition, SOLMarket, ALL_GOOD_KINDS,
+};
+use std::{cell::RefCell, rc::Rc};
+
+/// The definition of a market with 100000 of each good
+fn definition_with(config: MarketConfig, seed: u64) -> MarketDefinition {
+    MarketDefinition {
+        goods: ALL_GOOD_KINDS
+            .into_iter()
+            .map(|kind| GoodDefinition::new(kind, 100000.0))
+            .collect(),
+        config,
+        seed: Some(seed),
+        ..Default::default()
+    }
+}
+
+/// The market of the definition, with its log disabled
+fn market_from(definition: &MarketDefinition) -> Rc<RefCell<SOLMarket>> {
+    let market = SOLMarket::try_new_from_definition(definition).unwrap();
+    market.borrow_mut().set_log_sink(LogSink::Disabled);
+    market
+}
+
+/// A market with 100000 of each good, with its log disabled
+fn market_with(config: MarketConfig, seed: u64) -> Rc<RefCell<SOLMarket>> {
+    market_from(&definition_with(config, seed))
+}
@@ -0,0 +1,179 @@
+use crate::sol_market::{
+    OptionError, OptionKind, OptionRequest, OptionStatus, SOLMarket, SOLMarketError,
+};
+use std::{cell::RefCell, fs, rc::Rc};
+use unitn_market_2022::{
+    good::{consts::DEFAULT_GOOD_KIND, good::Good, good_kind::GoodKind},
+    market::Market,
+    wait_one_day,
+};
+
+const TRADER_NAME: &str = "foobar";
+
+fn market() -> Rc<RefCell<SOLMarket>> {
+    SOLMarket::try_new_with_quantities(100000.0, 100000.0, 100000.0, 100000.0).unwrap()
+}
+
+/// A request for 100 USD at the given share of their current price
+fn request(market: &SOLMarket, kind: OptionKind, moneyness: f32, days: u32) -> OptionRequest {
+    let value = market
+        .get_forward_buy_price(GoodKind::USD, 100.0, days)
+        .unwrap();
+    let strike = value * moneyness;
+    let expiry_day = market.meta.current_day + days;
+    let premium = market
+        .get_option_premium(kind, GoodKind::USD, 100.0, strike, expiry_day)
+        .unwrap();
+    OptionRequest {
+        trader_name: TRADER_NAME.to_string(),
+        kind,
+        good_kind: GoodKind::USD,
+        quantity: 100.0,
+        strike,
+        expiry_day,
+        premium,
+    }
+}
+
+fn wait_days(market: &Rc<RefCell<SOLMarket>>, days: u32) {
+    let dyn_market: Rc<RefCell<dyn Market>> = market.clone();
+    for _ in 0..days {
+        wait_one_day!(dyn_market);
+    }
+}
+
+#[test]
+/// Premiums should follow the strike and the time to expiry
+///
+/// SOL group
+fn premium_should_depend_on_strike_and_expiry() {
+    let market = market();
+    let market = market.borrow();
+    let premium = |kind, moneyness, days| request(&market, kind, moneyness, days).premium;
+
+    assert!(premium(OptionKind::Call, 1.0, 10) > 0.0);
+    assert!(premium(OptionKind::Call, 0.9, 10) > premium(OptionKind::Call, 1.1, 10));
+    assert!(premium(OptionKind::Put, 1.1, 10) > premium(OptionKind::Put, 0.9, 10));
+    assert!(premium(OptionKind::Call, 1.0, 60) > premium(OptionKind::Call, 1.0, 5));
+}
+
+#[test]
+/// A call deep in the money should be exercised and deliver the good
+///
+/// SOL group
+fn call_in_the_money_should_be_exercised() {
+    let market = market();
+    let request = request(&market.borrow(), OptionKind::Call, 0.01, 5);
+    let mut payment = Good::new(DEFAULT_GOOD_KIND, request.premium);
+    let mut deposit = Good::new(DEFAULT_GOOD_KIND, request.strike);
+    let id = market
+        .borrow_mut()
+        .write_option(request, &mut payment, &mut deposit)
+        .unwrap();
+    assert_eq!(payment.get_qty(), 0.0);
+    assert_eq!(deposit.get_qty(), 0.0);
+    assert_eq!(
+        market.borrow_mut().collect_option(&id),
+        Err(OptionError::NothingToCollect { id: id.clone() })
+    );
+
+    wait_days(&market, 5);
+    assert_eq!(
+        market.borrow().option_contract(&id).unwrap().status,
+        OptionStatus::Exercised
+    );
+    let delivered = market.borrow_mut().collect_option(&id).unwrap();
+    assert_eq!(delivered.get_kind(), GoodKind::USD);
+    assert_eq!(delivered.get_qty(), 100.0);
+    assert!(market.borrow().option_contract(&id).is_none());
+}
+
+#[test]
+/// A put out of the money should expire and give the good back
+///
+/// SOL group
+fn put_out_of_the_money_should_expire() {
+    let market = market();
+    let request = request(&market.borrow(), OptionKind::Put, 0.01, 3);
+    let mut payment = Good::new(DEFAULT_GOOD_KIND, request.premium);
+    let mut deposit = Good::new(GoodKind::USD, 100.0);
+    let id = market
+        .borrow_mut()
+        .write_option(request, &mut payment, &mut deposit)
+        .unwrap();
+    assert_eq!(market.borrow().to_definition().option_contracts.len(), 1);
+
+    wait_days(&market, 3);
+    assert_eq!(
+        market.borrow().option_contract(&id).unwrap().status,
+        OptionStatus::Expired
+    );
+    let returned = market.borrow_mut().collect_option(&id).unwrap();
+    assert_eq!(returned.get_kind(), GoodKind::USD);
+    assert_eq!(returned.get_qty(), 100.0);
+}
+
+#[test]
+/// Invalid requests should be rejected without taking anything
+///
+/// SOL group
+fn write_option_should_reject_invalid_requests() {
+    let market = market();
+    let mut market = market.borrow_mut();
+    let mut payment = Good::new(DEFAULT_GOOD_KIND, 1_000_000.0);
+    let mut deposit = Good::new(DEFAULT_GOOD_KIND, 1_000_000.0);
+
+    assert_eq!(
+        market.get_option_premium(OptionKind::Call, DEFAULT_GOOD_KIND, 1.0, 1.0, 10),
+        Err(OptionError::UnsupportedGood {
+            good_kind: DEFAULT_GOOD_KIND
+        })
+    );
+
+    let mut stingy = request(&market, OptionKind::Call, 1.0, 10);
+    stingy.premium /= 2.0;
+    assert!(matches!(
+        market.write_option(stingy, &mut payment, &mut deposit),
+        Err(OptionError::PremiumTooLow { .. })
+    ));
+
+    let put = request(&market, OptionKind::Put, 1.0, 10);
+    assert_eq!(
+        market.write_option(put, &mut payment, &mut deposit),
+        Err(OptionError::WrongDepositKind {
+            expected: GoodKind::USD,
+            provided: DEFAULT_GOOD_KIND,
+        })
+    );
+
+    assert_eq!(payment.get_qty(), 1_000_000.0);
+    assert_eq!(deposit.get_qty(), 1_000_000.0);
+    assert!(market.option_contracts_of(TRADER_NAME).is_empty());
+}
+
+#[test]
+/// Options should not be written to a `.sol` file, which can not keep them
+///
+/// SOL group
+fn options_should_not_be_dropped_from_sol_files() {
+    let market = market();
+    let request = request(&market.borrow(), OptionKind::Put, 1.0, 5);
+    let mut payment = Good::new(DEFAULT_GOOD_KIND, request.premium);
+    let mut deposit = Good::new(GoodKind::USD, 100.0);
+    market
+        .borrow_mut()
+        .write_option(request, &mut payment, &mut deposit)
+        .unwrap();
+
+    let directory = std::env::temp_dir().join(format!("sol_options_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(&directory).unwrap();
+    let definition = market.borrow().to_definition();
+    let result = definition.write_to_file(&directory.join("market.sol"));
+    assert!(matches!(result, Err(SOLMarketError::LossySolFile { .. })));
+    assert!(!directory.join("market.sol").exists());
+    definition
+        .write_to_file(&directory.join("market.toml"))
+        .unwrap();
+    fs::remove_dir_all(&directory).unwrap();
+}
@@ -0,0 +1,148 @@
+use super::market_with;
+use crate::sol_market::{InstanceEvent, PaperError, SOLMarket, TradeSide, ALL_GOOD_KINDS};
+use std::{cell::RefCell, rc::Rc};
+use unitn_market_2022::{
+    good::{consts::DEFAULT_GOOD_KIND, good::Good, good_kind::GoodKind},
+    market::{LockBuyError, Market},
+};
+
+const TRADER_NAME: &str = "foobar";
+
+fn market() -> Rc<RefCell<SOLMarket>> {
+    market_with(Default::default(), 5)
+}
+
+/// Quantity, buy price and sell price of every good, to compare states
+fn state(market: &SOLMarket) -> Vec<(f32, f32, f32)> {
+    ALL_GOOD_KINDS
+        .into_iter()
+        .map(|kind| {
+            (
+                market.get_available_quantity(kind),
+                market.get_buy_price(kind, 100.0).unwrap(),
+                market.get_sell_price(kind, 100.0).unwrap(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+/// Paper trades should leave the market, its day and its listeners alone
+///
+/// SOL group
+fn paper_trades_should_not_touch_the_market() {
+    let market = market();
+    let events = Rc::new(RefCell::new(0));
+    let counter = events.clone();
+    market
+        .borrow_mut()
+        .add_event_listener(Box::new(move |_: &InstanceEvent| {
+            *counter.borrow_mut() += 1
+        }));
+    let before = state(&market.borrow());
+    let day = market.borrow().meta.current_day;
+
+    let bought = market
+        .borrow()
+        .paper_buy(GoodKind::USD, 10000.0, TRADER_NAME)
+        .unwrap();
+    let sold = market
+        .borrow()
+        .paper_sell(GoodKind::YEN, 10000.0, TRADER_NAME)
+        .unwrap();
+
+    assert_eq!(bought.side, TradeSide::Buy);
+    assert_eq!(sold.side, TradeSide::Sell);
+    assert_eq!(state(&market.borrow()), before);
+    assert_eq!(market.borrow().meta.current_day, day);
+    assert!(market.borrow().meta.locked_buys.is_empty());
+    assert_eq!(*events.borrow(), 0);
+}
+
+#[test]
+/// A paper buy should cost what the real one does, and report the impact of
+/// the quantity strategy on the rates
+///
+/// SOL group
+fn paper_buy_should_match_the_real_one() {
+    let market = market();
+    let quantity = 20000.0;
+    let paper = market
+        .borrow()
+        .paper_buy(GoodKind::USD, quantity, TRADER_NAME)
+        .unwrap();
+
+    let price = market
+        .borrow()
+        .get_buy_price(GoodKind::USD, quantity)
+        .unwrap();
+    assert!((paper.price - price).abs() < 0.001 * price);
+    let paper_usd = &paper.after[&GoodKind::USD];
+    assert_eq!(paper_usd.available_quantity, 100000.0 - quantity);
+    assert!(paper.rate_impact() != 0.0);
+    // Only the traded good moves
+    assert_eq!(
+        paper.before[&GoodKind::YEN].exchange_rate_buy,
+        paper.after[&GoodKind::YEN].exchange_rate_buy
+    );
+
+    // The real trade gets what the paper one said
+    let token = market
+        .borrow_mut()
+        .lock_buy(
+            GoodKind::USD,
+            quantity,
+            paper.price,
+            TRADER_NAME.to_string(),
+        )
+        .unwrap();
+    let mut cash = Good::new(DEFAULT_GOOD_KIND, paper.price);
+    let bought = market.borrow_mut().buy(token, &mut cash).unwrap();
+    assert_eq!(bought.get_qty(), quantity);
+}
+
+#[test]
+/// Paper trades should fail as the real ones would
+///
+/// SOL group
+fn paper_trades_should_fail_like_real_ones() {
+    let market = market();
+    let result = market
+        .borrow()
+        .paper_buy(GoodKind::USD, 200000.0, TRADER_NAME);
+    assert_eq!(
+        result.unwrap_err(),
+        PaperError::LockBuy(LockBuyError::InsufficientGoodQuantityAvailable {
+            requested_good_kind: GoodKind::USD,
+            requested_good_quantity: 200000.0,
+            available_good_quantity: 100000.0,
+        })
+    );
+}
+
+#[test]
+/// A paper market should take several steps away from the real one
+///
+/// SOL group
+fn paper_market_should_be_independent() {
+    let market = market();
+    let before = state(&market.borrow());
+    let paper = market.borrow().paper_market();
+    for _ in 0..3 {
+        let mut paper = paper.borrow_mut();
+        let bid = paper.get_buy_price(GoodKind::YUAN, 1000.0).unwrap();
+        let token = paper
+            .lock_buy(GoodKind::YUAN, 1000.0, bid, TRADER_NAME.to_string())
+            .unwrap();
+        paper
+            .buy(token, &mut Good::new(DEFAULT_GOOD_KIND, bid))
+            .unwrap();
+    }
+
+    assert_eq!(
+        paper.borrow().get_available_quantity(GoodKind::YUAN),
+        97000.0
+    );
+    assert_ne!(paper.borrow().instance_id(), market.borrow().instance_id());
+    assert_eq!(state(&market.borrow()), before);
+}
@@ -0,0 +1,75 @@
+use crate::sol_market::SOLMarket;
+use std::collections::HashMap;
+use unitn_market_2022::{
+    good::{consts::DEFAULT_GOOD_KIND, good_kind::GoodKind},
+    market::{Market, MarketGetterError},
+};
+
+#[test]
+fn quote_ladder_should_match_single_quotes() {
+    let market_start_quantity = 1000.0;
+    let market = SOLMarket::new_with_quantities_and_path(
+        market_start_quantity,
+        market_start_quantity,
+        market_start_quantity,
+        market_start_quantity,
+        None,
+        HashMap::new(),
+    );
+    let market = market.borrow();
+    let quantities = [1.0, 10.0, 100.0, market_start_quantity];
+
+    let ladders = market.get_quote_ladders(&quantities);
+
+    assert_eq!(ladders.len(), 4);
+    for (kind, ladder) in ladders.iter() {
+        assert_eq!(ladder.good_kind, *kind);
+        assert_eq!(ladder.available_quantity, market_start_quantity);
+        assert_eq!(ladder.quotes.len(), quantities.len());
+        for (quote, quantity) in ladder.quotes.iter().zip(quantities) {
+            assert_eq!(quote.quantity, quantity);
+            assert_eq!(quote.buy_price, market.get_buy_price(*kind, quantity));
+            assert_eq!(quote.sell_price, market.get_sell_price(*kind, quantity));
+        }
+    }
+    let eur_ladder = ladders.get(&DEFAULT_GOOD_KIND).unwrap();
+    assert_eq!(eur_ladder.exchange_rate_buy, 1.0);
+    assert_eq!(eur_ladder.exchange_rate_sell, 1.0);
+}
+
+#[test]
+fn quote_ladder_should_report_errors_per_quantity() {
+    let market_start_quantity = 1000.0;
+    let market = SOLMarket::new_with_quantities_and_path(
+        market_start_quantity,
+        market_start_quantity,
+        market_start_quantity,
+        market_start_quantity,
+        None,
+        HashMap::new(),
+    );
+    let too_much = market_start_quantity + 1.0;
+
+    let ladders = market.borrow().get_quote_ladders(&[-1.0, 5.0, too_much]);
+    let usd_quotes = &ladders.get(&GoodKind::USD).unwrap().quotes;
+
+    assert_eq!(
+        usd_quotes[0].buy_price,
+        Err(MarketGetterError::NonPositiveQuantityAsked)
+    );
+    assert_eq!(
+        usd_quotes[0].sell_price,
+        Err(MarketGetterError::NonPositiveQuantityAsked)
+    );
+    assert!(usd_quotes[1].buy_price.is_ok());
+    assert_eq!(
+        usd_quotes[2].buy_price,
+        Err(MarketGetterError::InsufficientGoodQuantityAvailable {
+            requested_good_kind: GoodKind::USD,
+            requested_good_quantity: too_much,
+            available_good_quantity: market_start_quantity,
+        })
+    );
+    //The market can always buy from the trader
+    assert!(usd_quotes[2].sell_price.is_ok());
+}
@@ -0,0 +1,168 @@
+use crate::remote::{SOLMarketClient, SOLMarketEndpoint, SOLMarketServer};
+use crate::sol_market::SOLMarket;
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    io::{BufRead, BufReader, Write},
+    net::TcpStream,
+    rc::Rc,
+    sync::mpsc,
+    thread,
+};
+use unitn_market_2022::{
+    event::{
+        event::{Event, EventKind},
+        notifiable::Notifiable,
+    },
+    good::{consts::DEFAULT_GOOD_KIND, good::Good, good_kind::GoodKind},
+    market::{BuyError, Market},
+    wait_one_day,
+};
+
+const TRADER_NAME: &str = "foobar";
+
+/// Starts a server on a free port, hosting a market with the given quantity
+/// of each good, and returns where to reach it
+fn start_server(quantity: f32) -> SOLMarketEndpoint {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let endpoint: SOLMarketEndpoint = "127.0.0.1:0".parse().unwrap();
+        let server = SOLMarketServer::bind(&endpoint).unwrap();
+        sender.send(server.local_endpoint().unwrap()).unwrap();
+        let market = SOLMarket::new_with_quantities_and_path(
+            quantity,
+            quantity,
+            quantity,
+            quantity,
+            None,
+            HashMap::new(),
+        );
+        server.serve_market(market).unwrap();
+    });
+    receiver.recv().unwrap()
+}
+
+struct EventSpy {
+    kinds: Rc<RefCell<Vec<EventKind>>>,
+}
+
+impl Notifiable for EventSpy {
+    fn add_subscriber(&mut self, _subscriber: Box<dyn Notifiable>) {}
+
+    fn on_event(&mut self, event: Event) {
+        self.kinds.borrow_mut().push(event.kind);
+    }
+}
+
+#[test]
+fn endpoint_should_parse_tcp_and_unix() {
+    let tcp: SOLMarketEndpoint = "127.0.0.1:7654".parse().unwrap();
+    assert_eq!(
+        tcp,
+        SOLMarketEndpoint::Tcp("127.0.0.1:7654".parse().unwrap())
+    );
+    #[cfg(unix)]
+    {
+        let unix: SOLMarketEndpoint = "unix:/tmp/sol.sock".parse().unwrap();
+        assert_eq!(unix, SOLMarketEndpoint::Unix("/tmp/sol.sock".into()));
+        assert_eq!(unix.to_string(), "unix:/tmp/sol.sock");
+    }
+    assert!("unix:".parse::<SOLMarketEndpoint>().is_err());
+    assert!("not an address".parse::<SOLMarketEndpoint>().is_err());
+}
+
+#[test]
+fn client_should_see_the_hosted_market() {
+    let quantity = 1000.0;
+    let client = SOLMarketClient::connect(&start_server(quantity)).unwrap();
+
+    assert_eq!(client.get_name(), "SOL");
+    assert_eq!(client.get_budget(), quantity);
+    let goods = client.get_goods();
+    assert_eq!(goods.len(), 4);
+    assert!(goods.iter().all(|label| label.quantity == quantity));
+    assert!(client.get_buy_price(GoodKind::USD, -1.0).is_err());
+}
+
+#[test]
+fn client_should_buy_through_the_server() {
+    let quantity = 1000.0;
+    let kind = GoodKind::USD;
+    let mut client = SOLMarketClient::connect(&start_server(quantity)).unwrap();
+    let kinds = Rc::new(RefCell::new(vec![]));
+    client.add_subscriber(Box::new(EventSpy {
+        kinds: Rc::clone(&kinds),
+    }));
+
+    let bid = client.get_buy_price(kind, 10.0).unwrap();
+    let token = client
+        .lock_buy(kind, 10.0, bid, TRADER_NAME.to_string())
+        .unwrap();
+    let mut cash = Good::new(DEFAULT_GOOD_KIND, bid + 1.0);
+    let bought = client.buy(token.clone(), &mut cash).unwrap();
+
+    assert_eq!(bought.get_kind(), kind);
+    assert_eq!(bought.get_qty(), 10.0);
+    assert!((cash.get_qty() - 1.0).abs() < 0.001);
+    assert_eq!(
+        *kinds.borrow(),
+        vec![EventKind::LockedBuy, EventKind::Bought]
+    );
+
+    //The token has been used already
+    let err = client.buy(token.clone(), &mut cash).unwrap_err();
+    assert_eq!(
+        err,
+        BuyError::UnrecognizedToken {
+            unrecognized_token: token
+        }
+    );
+}
+
+#[test]
+fn wait_one_day_should_reach_the_server() {
+    let client = SOLMarketClient::connect(&start_server(1000.0)).unwrap();
+    let market: Rc<RefCell<dyn Market>> = Rc::new(RefCell::new(client));
+    let before = market.borrow().get_buy_price(GoodKind::YEN, 1.0).unwrap();
+    wait_one_day!(market);
+    let after = market.borrow().get_buy_price(GoodKind::YEN, 1.0).unwrap();
+    assert_ne!(before, after);
+}
+
+#[test]
+fn invalid_quantities_should_not_stop_the_server() {
+    let endpoint = start_server(1000.0);
+    let mut stream = TcpStream::connect(endpoint.to_string()).unwrap();
+    let request = r#"{"NewWithQuantities":{"eur":-1.0,"yen":1.0,"usd":1.0,"yuan":1.0}}"#;
+    writeln!(stream, "{request}").unwrap();
+    let mut response = String::new();
+    BufReader::new(stream).read_line(&mut response).unwrap();
+    assert!(response.contains("Failure"), "{response}");
+
+    //The server still hosts the old market
+    let client = SOLMarketClient::connect(&endpoint).unwrap();
+    assert_eq!(client.get_budget(), 1000.0);
+}
+
+#[test]
+fn server_should_only_listen_on_the_loopback() {
+    let endpoint: SOLMarketEndpoint = "0.0.0.0:0".parse().unwrap();
+    assert!(SOLMarketServer::bind(&endpoint).is_err());
+    assert!(SOLMarketServer::bind_remote(&endpoint).is_ok());
+}
+
+#[test]
+#[cfg(unix)]
+fn server_should_not_remove_what_is_not_a_socket() {
+    let path = std::env::temp_dir().join(format!("sol_not_a_socket_{}", std::process::id()));
+    std::fs::write(&path, "user data").unwrap();
+    let endpoint = SOLMarketEndpoint::Unix(path.clone());
+    assert!(SOLMarketServer::bind(&endpoint).is_err());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "user data");
+    std::fs::remove_file(&path).unwrap();
+
+    //A socket left behind is replaced
+    drop(SOLMarketServer::bind(&endpoint).unwrap());
+    assert!(SOLMarketServer::bind(&endpoint).is_ok());
+    std::fs::remove_file(&path).unwrap();
+}
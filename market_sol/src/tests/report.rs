@@ -0,0 +1,110 @@
+use crate::sol_market::{MarketDefinition, MarketReport, SOLMarket};
+use std::{cell::RefCell, fs, rc::Rc};
+use unitn_market_2022::{
+    good::{consts::DEFAULT_GOOD_KIND, good::Good, good_kind::GoodKind},
+    market::Market,
+    wait_one_day,
+};
+
+const TRADER_NAME: &str = "foobar";
+
+fn market() -> Rc<RefCell<SOLMarket>> {
+    SOLMarket::try_new_with_quantities(100000.0, 100000.0, 100000.0, 100000.0).unwrap()
+}
+
+#[test]
+/// Locks and trades should be counted, and priced at the rate of the market
+///
+/// SOL group
+fn report_should_count_locks_and_trades() {
+    let market = market();
+    let mut m = market.borrow_mut();
+    let bid = m.get_buy_price(GoodKind::USD, 100.0).unwrap();
+    // Pay 1 EUR more than asked
+    let token = m
+        .lock_buy(GoodKind::USD, 100.0, bid + 1.0, TRADER_NAME.to_string())
+        .unwrap();
+    m.buy(token, &mut Good::new(DEFAULT_GOOD_KIND, bid + 1.0))
+        .unwrap();
+    m.lock_sell(GoodKind::YEN, 100.0, 0.1, TRADER_NAME.to_string())
+        .unwrap();
+
+    let report = m.report();
+    assert_eq!(report.locks.created, 2);
+    assert_eq!(report.locks.redeemed, 1);
+    assert_eq!(report.locks.expired, 0);
+    assert_eq!(report.volumes.len(), 1);
+    let usd = report.volumes[0];
+    assert_eq!(
+        (usd.kind, usd.trades, usd.bought, usd.sold),
+        (GoodKind::USD, 1, 100.0, 0.0)
+    );
+    assert!((usd.bought_value - bid - 1.0).abs() < 1e-3);
+    assert!((report.realized_margin - 1.0).abs() < 1e-3);
+    // One point per day
+    assert_eq!(report.value_history.len(), 3);
+    assert_eq!(report.value_history.last().unwrap().day, report.day);
+    assert_eq!(report.weights.len(), 3);
+    assert_eq!(report.rates.len(), 4);
+}
+
+#[test]
+/// Locks that are not redeemed in time should be counted as expired
+///
+/// SOL group
+fn report_should_count_expired_locks() {
+    let market = market();
+    let token_duration = market.borrow().to_definition().config.token_duration;
+    market
+        .borrow_mut()
+        .lock_sell(GoodKind::YEN, 100.0, 0.1, TRADER_NAME.to_string())
+        .unwrap();
+    let dyn_market: Rc<RefCell<dyn Market>> = market.clone();
+    for _ in 0..token_duration {
+        wait_one_day!(dyn_market);
+    }
+    let report = market.borrow().report();
+    assert_eq!(report.locks.created, 1);
+    assert_eq!(report.locks.expired, 1);
+    assert_eq!(report.locks.redeemed, 0);
+}
+
+#[test]
+/// The report should be renderable as JSON and as text
+///
+/// SOL group
+fn report_should_render_as_json_and_text() {
+    let market = market();
+    let report = market.borrow().report();
+    let json = report.to_json().unwrap();
+    let parsed: MarketReport = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, report);
+    let text = report.to_string();
+    assert!(text.starts_with("SOL market, day 1"));
+    assert!(text.contains("Stocastic"));
+}
+
+#[test]
+/// Dropping a market should write its report next to its state file
+///
+/// SOL group
+fn report_should_be_written_next_to_the_state_file_on_drop() {
+    let directory = std::env::temp_dir().join(format!("sol_report_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(&directory).unwrap();
+    let path = directory.join("market.toml");
+    let definition = MarketDefinition::from_sol_str(
+        "good EUR 10000 1\ngood YEN 10000 1\ngood USD 10000 1\ngood YUAN 10000 1\n",
+    )
+    .unwrap();
+    definition.write_to_file(&path).unwrap();
+    let market = SOLMarket::try_new_file(path.to_str().unwrap()).unwrap();
+    let report_path = market.borrow().report_path().unwrap();
+    assert_eq!(report_path, directory.join("market.report.json"));
+
+    drop(market);
+    let report: MarketReport =
+        serde_json::from_str(&fs::read_to_string(&report_path).unwrap()).unwrap();
+    assert_eq!(report.day, 1);
+    fs::remove_dir_all(directory).unwrap();
+}
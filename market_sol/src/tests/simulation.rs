@@ -0,0 +1,121 @@
+use crate::simulation::{
+    simulate, MarketSummary, SeriesPoint, SimulationConfig, SimulationResult, TraderFlow,
+};
+use crate::sol_market::SOLMarketError;
+use unitn_market_2022::good::good_kind::GoodKind;
+
+#[test]
+/// The same seeds should give the same prices, for every market, good and day
+///
+/// SOL group
+fn simulation_should_be_reproducible() {
+    let config = SimulationConfig {
+        markets: 2,
+        days: 50,
+        seed: 42,
+        ..Default::default()
+    };
+    let result = simulate(&config).unwrap();
+    assert_eq!(result.series.len(), 2 * 51 * 4);
+    assert_eq!(result.markets.len(), 2);
+    // 3 goods per market, and their averages
+    assert_eq!(result.summaries.len(), 2 * 3 + 3);
+    assert_eq!(result.averages().count(), 3);
+    assert_eq!(simulate(&config).unwrap(), result);
+}
+
+#[test]
+/// Synthetic trades should be made, and move the day of the market forward
+///
+/// SOL group
+fn simulation_should_trade_with_a_flow() {
+    let config = SimulationConfig {
+        days: 20,
+        flow: Some(TraderFlow {
+            trades_per_day: 2,
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let result = simulate(&config).unwrap();
+    let market = result.markets[0];
+    assert_eq!(market.trades + market.rejected_trades, 40);
+    assert!(market.trades > 0);
+    assert!(market.final_day > 21);
+}
+
+#[test]
+/// A flow whose trades can not be drawn should be rejected before running
+///
+/// SOL group
+fn simulation_should_reject_an_invalid_flow() {
+    for flow in [
+        TraderFlow {
+            max_trade_value: -1.0,
+            ..Default::default()
+        },
+        TraderFlow {
+            buy_probability: 1.5,
+            ..Default::default()
+        },
+    ] {
+        let config = SimulationConfig {
+            flow: Some(flow),
+            ..Default::default()
+        };
+        assert!(matches!(
+            simulate(&config),
+            Err(SOLMarketError::InvalidConfig { .. })
+        ));
+    }
+}
+
+#[test]
+/// Drift, volatility and drawdown should follow their definitions
+///
+/// SOL group
+fn summary_should_describe_the_prices() {
+    let point = |step: u32, price: f32| SeriesPoint {
+        market: 0,
+        seed: 0,
+        step,
+        day: step + 1,
+        kind: GoodKind::USD,
+        quantity: 1.0,
+        buy_rate: 1.0 / price,
+        sell_rate: 1.0 / price,
+        price,
+    };
+    let series = vec![point(0, 1.0), point(1, 2.0), point(2, 1.0), point(3, 1.5)];
+    let markets = vec![MarketSummary {
+        market: 0,
+        seed: 0,
+        final_day: 4,
+        trades: 0,
+        rejected_trades: 0,
+    }];
+    let result = SimulationResult::new(series, markets);
+    let summary = result.summaries[0];
+    assert_eq!(summary.market, Some(0));
+    assert!((summary.change - 0.5).abs() < 1e-6);
+    assert!((summary.drift - 1.5f32.ln() / 3.0).abs() < 1e-6);
+    assert!((summary.max_drawdown - 0.5).abs() < 1e-6);
+    let ln2 = 2f32.ln();
+    let returns = [ln2, -ln2, 1.5f32.ln()];
+    let variance = returns
+        .iter()
+        .map(|r| (r - summary.drift).powi(2))
+        .sum::<f32>()
+        / 2.0;
+    assert!((summary.volatility - variance.sqrt()).abs() < 1e-6);
+
+    let mut csv = vec![];
+    result.write_summary_csv(&mut csv).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    assert_eq!(csv.lines().count(), 3);
+    assert!(csv
+        .lines()
+        .last()
+        .unwrap()
+        .starts_with("all,USD,1,1.5,0.5,"));
+}
@@ -0,0 +1,155 @@
+use crate::sol_market::{MarketConfig, MarketDefinition, SOLMarket, SOLMarketError};
+use std::{cell::RefCell, fs, path::PathBuf, rc::Rc};
+use unitn_market_2022::{good::good_kind::GoodKind, market::Market, wait_one_day};
+
+const TRADER_NAME: &str = "foobar";
+
+/// Creates a market bound to a TOML state file in a fresh temporary
+/// directory, with the given configuration
+fn market_with_state_file(name: &str, config: MarketConfig) -> (Rc<RefCell<SOLMarket>>, PathBuf) {
+    let directory = std::env::temp_dir().join(format!("{name}_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(&directory).unwrap();
+    let path = directory.join("market.toml");
+    let mut definition = MarketDefinition::from_sol_str(
+        "good EUR 10000 1\ngood YEN 10000 1\ngood USD 10000 1\ngood YUAN 10000 1\n",
+    )
+    .unwrap();
+    definition.config = config;
+    definition.write_to_file(&path).unwrap();
+    let market = SOLMarket::try_new_file(path.to_str().unwrap()).unwrap();
+    (market, path)
+}
+
+#[test]
+/// Markets without a state file have nowhere to save snapshots
+///
+/// SOL group
+fn save_snapshot_should_need_a_state_file() {
+    let market = SOLMarket::try_new_with_quantities(1.0, 1.0, 1.0, 1.0).unwrap();
+    let result = market.borrow().save_snapshot();
+    assert!(matches!(result, Err(SOLMarketError::NoStateFile)));
+}
+
+#[test]
+/// Old snapshots should be deleted according to the retention policy
+///
+/// SOL group
+fn save_snapshot_should_keep_only_the_newest_snapshots() {
+    let config = MarketConfig {
+        snapshots_to_keep: Some(2),
+        ..Default::default()
+    };
+    let (market, path) = market_with_state_file("sol_snapshot_retention", config);
+    //Named like the state file, but not a snapshot
+    let unrelated = path.with_file_name("market2.toml");
+    fs::write(&unrelated, "").unwrap();
+    for _ in 0..4 {
+        market.borrow().save_snapshot().unwrap();
+        //Snapshots are named after the millisecond they are taken
+        std::thread::sleep(std::time::Duration::from_millis(2));
+    }
+    let snapshots = market.borrow().snapshots().unwrap();
+    assert_eq!(snapshots.len(), 2);
+    assert!(path.exists());
+    let directory = path.parent().unwrap();
+    assert!(unrelated.exists());
+    //State file, unrelated file and two snapshots, no leftover temporary files
+    assert_eq!(fs::read_dir(directory).unwrap().count(), 4);
+
+    drop(market);
+    fs::remove_dir_all(directory).unwrap();
+}
+
+#[test]
+/// Restoring a snapshot should bring back goods and day, and forget locks
+///
+/// SOL group
+fn restore_snapshot_should_bring_back_the_saved_state() {
+    let (market, path) = market_with_state_file("sol_snapshot_restore", MarketConfig::default());
+    market.borrow().save_snapshot().unwrap();
+    let day = market.borrow().meta.current_day;
+    let usd = market.borrow().get_available_quantity(GoodKind::USD);
+
+    {
+        let mut market = market.borrow_mut();
+        let bid = market.get_buy_price(GoodKind::USD, 100.0).unwrap();
+        market
+            .lock_buy(GoodKind::USD, 100.0, bid, TRADER_NAME.to_string())
+            .unwrap();
+    }
+    assert!(market.borrow().get_available_quantity(GoodKind::USD) < usd);
+
+    market.borrow_mut().restore_snapshot().unwrap();
+    let market_ref = market.borrow();
+    assert_eq!(market_ref.get_available_quantity(GoodKind::USD), usd);
+    assert_eq!(market_ref.meta.current_day, day);
+    assert!(market_ref.meta.locked_buys.is_empty());
+    assert_eq!(
+        market_ref.meta.file_path.as_deref(),
+        Some(path.to_str().unwrap())
+    );
+
+    drop(market_ref);
+    drop(market);
+    fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+/// Goods reserved by a lock when saving should be back in stock after
+/// restoring, since the lock is forgotten
+///
+/// SOL group
+fn restore_snapshot_should_bring_back_the_locked_goods() {
+    let (market, path) = market_with_state_file("sol_snapshot_locked", MarketConfig::default());
+    let offer = {
+        let mut market = market.borrow_mut();
+        let bid = market.get_buy_price(GoodKind::USD, 100.0).unwrap();
+        market
+            .lock_buy(GoodKind::USD, 100.0, bid, TRADER_NAME.to_string())
+            .unwrap();
+        let offer = market.get_sell_price(GoodKind::YEN, 100.0).unwrap();
+        market
+            .lock_sell(GoodKind::YEN, 100.0, offer, TRADER_NAME.to_string())
+            .unwrap();
+        offer
+    };
+    //The locks reserve 100 USD and `offer` EUR
+    let usd = market.borrow().get_available_quantity(GoodKind::USD) + 100.0;
+    let eur = market.borrow().get_available_quantity(GoodKind::EUR) + offer;
+    market.borrow().save_snapshot().unwrap();
+
+    market.borrow_mut().restore_snapshot().unwrap();
+    let market_ref = market.borrow();
+    assert_eq!(market_ref.get_available_quantity(GoodKind::USD), usd);
+    assert_eq!(market_ref.get_available_quantity(GoodKind::EUR), eur);
+    assert!(market_ref.meta.locked_buys.is_empty());
+    assert!(market_ref.meta.locked_sells.is_empty());
+
+    drop(market_ref);
+    drop(market);
+    fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+/// With autosave enabled, the state file should follow the market days
+///
+/// SOL group
+fn on_event_should_autosave() {
+    let config = MarketConfig {
+        autosave_every_days: Some(5),
+        ..Default::default()
+    };
+    let (market, path) = market_with_state_file("sol_snapshot_autosave", config);
+    let dyn_market: Rc<RefCell<dyn Market>> = market.clone();
+    for _ in 0..11 {
+        wait_one_day!(dyn_market);
+    }
+    let saved = MarketDefinition::from_file(&path).unwrap();
+    assert_eq!(saved.current_day, Some(10));
+    assert_eq!(market.borrow().snapshots().unwrap().len(), 2);
+
+    drop(dyn_market);
+    drop(market);
+    fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
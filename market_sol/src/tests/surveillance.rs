@@ -0,0 +1,157 @@
+use super::market_with;
+use crate::sol_market::{MarketConfig, SOLMarket, SurveillanceConfig, SuspiciousPattern};
+use std::{cell::RefCell, rc::Rc};
+use unitn_market_2022::{
+    good::{consts::DEFAULT_GOOD_KIND, good::Good, good_kind::GoodKind},
+    market::{LockBuyError, LockSellError, Market},
+};
+
+const TRADER_NAME: &str = "foobar";
+
+fn market(surveillance: SurveillanceConfig) -> Rc<RefCell<SOLMarket>> {
+    let config = MarketConfig {
+        surveillance,
+        ..Default::default()
+    };
+    market_with(config, 7)
+}
+
+fn buy(market: &mut SOLMarket, kind: GoodKind, quantity: f32) -> Good {
+    let bid = market.get_buy_price(kind, quantity).unwrap();
+    let token = market
+        .lock_buy(kind, quantity, bid, TRADER_NAME.to_string())
+        .unwrap();
+    market
+        .buy(token, &mut Good::new(DEFAULT_GOOD_KIND, bid))
+        .unwrap()
+}
+
+fn sell(market: &mut SOLMarket, mut good: Good) {
+    let (kind, quantity) = (good.get_kind(), good.get_qty());
+    let offer = market.get_sell_price(kind, quantity).unwrap();
+    let token = market
+        .lock_sell(kind, quantity, offer, TRADER_NAME.to_string())
+        .unwrap();
+    market.sell(token, &mut good).unwrap();
+}
+
+fn patterns(market: &SOLMarket) -> Vec<SuspiciousPattern> {
+    market
+        .surveillance_flags(TRADER_NAME)
+        .iter()
+        .map(|flag| flag.pattern)
+        .collect()
+}
+
+#[test]
+/// Locking without ever trading should be flagged, only once
+///
+/// SOL group
+fn lock_spam_should_be_flagged() {
+    let market = market(SurveillanceConfig::default());
+    let mut market = market.borrow_mut();
+    for _ in 0..10 {
+        market
+            .lock_sell(GoodKind::EUR, 1.0, 1.0, TRADER_NAME.to_string())
+            .unwrap();
+    }
+    assert_eq!(patterns(&market), vec![SuspiciousPattern::LockSpam]);
+    // Penalties are off by default
+    assert!(!market.is_banned(TRADER_NAME));
+}
+
+#[test]
+/// Buying and selling the same quantity back and forth should be flagged
+///
+/// SOL group
+fn wash_trading_should_be_flagged() {
+    let market = market(SurveillanceConfig::default());
+    let mut market = market.borrow_mut();
+    for _ in 0..2 {
+        let usd = buy(&mut market, GoodKind::USD, 100.0);
+        sell(&mut market, usd);
+    }
+    assert_eq!(patterns(&market), vec![SuspiciousPattern::WashTrading]);
+}
+
+#[test]
+/// Selling back a large share of a good should be flagged
+///
+/// SOL group
+fn pump_and_dump_should_be_flagged() {
+    let market = market(SurveillanceConfig {
+        // Any sale above half the price paid
+        pump_min_gain: -0.5,
+        ..Default::default()
+    });
+    let mut market = market.borrow_mut();
+    let usd = buy(&mut market, GoodKind::USD, 10000.0);
+    sell(&mut market, usd);
+    assert_eq!(patterns(&market), vec![SuspiciousPattern::PumpAndDump]);
+}
+
+#[test]
+/// With penalties, a flagged trader should get worse prices, and be banned
+/// when flagged too often
+///
+/// SOL group
+fn penalties_should_apply_to_flagged_traders() {
+    let market = market(SurveillanceConfig {
+        spam_min_locks: 3,
+        penalties: true,
+        ban_after_flags: 2,
+        ..Default::default()
+    });
+    let mut market = market.borrow_mut();
+    for _ in 0..3 {
+        market
+            .lock_sell(GoodKind::EUR, 1.0, 1.0, TRADER_NAME.to_string())
+            .unwrap();
+    }
+    assert!(!market.is_banned(TRADER_NAME));
+
+    // The spread is 5% worse
+    let price = market.get_buy_price(GoodKind::USD, 100.0).unwrap();
+    let result = market.lock_buy(GoodKind::USD, 100.0, price, TRADER_NAME.to_string());
+    assert!(matches!(result, Err(LockBuyError::BidTooLow { .. })));
+    let token = market
+        .lock_buy(GoodKind::USD, 100.0, price * 1.06, TRADER_NAME.to_string())
+        .unwrap();
+    let usd = market
+        .buy(token, &mut Good::new(DEFAULT_GOOD_KIND, price * 1.06))
+        .unwrap();
+    let offer = market.get_sell_price(GoodKind::USD, 100.0).unwrap();
+    let result = market.lock_sell(GoodKind::USD, 100.0, offer, TRADER_NAME.to_string());
+    assert!(matches!(result, Err(LockSellError::OfferTooHigh { .. })));
+
+    // Washing that good makes a second flag, which means a ban
+    sell_with_penalty(&mut market, usd);
+    let usd = buy_with_penalty(&mut market, GoodKind::USD, 100.0);
+    sell_with_penalty(&mut market, usd);
+    assert_eq!(
+        patterns(&market),
+        vec![SuspiciousPattern::LockSpam, SuspiciousPattern::WashTrading]
+    );
+    assert!(market.is_banned(TRADER_NAME));
+    let result = market.lock_sell(GoodKind::EUR, 1.0, 1.0, TRADER_NAME.to_string());
+    assert_eq!(result, Err(LockSellError::MaxAllowedLocksReached));
+}
+
+fn buy_with_penalty(market: &mut SOLMarket, kind: GoodKind, quantity: f32) -> Good {
+    let bid = market.get_buy_price(kind, quantity).unwrap() * 1.06;
+    let token = market
+        .lock_buy(kind, quantity, bid, TRADER_NAME.to_string())
+        .unwrap();
+    market
+        .buy(token, &mut Good::new(DEFAULT_GOOD_KIND, bid))
+        .unwrap()
+}
+
+fn sell_with_penalty(market: &mut SOLMarket, mut good: Good) {
+    let (kind, quantity) = (good.get_kind(), good.get_qty());
+    let offer = market.get_sell_price(kind, quantity).unwrap() / 1.06;
+    let token = market
+        .lock_sell(kind, quantity, offer, TRADER_NAME.to_string())
+        .unwrap();
+    market.sell(token, &mut good).unwrap();
+}
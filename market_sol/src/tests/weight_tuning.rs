@@ -0,0 +1,177 @@
+use super::{definition_with, market_from};
+use crate::sol_market::{
+    MarketConfig, MarketDefinition, SOLMarket, SOLMarketError, TuningObjective, WeightTuningConfig,
+    WeightsDefinition,
+};
+use std::{cell::RefCell, rc::Rc};
+use unitn_market_2022::{
+    good::{good::Good, good_kind::GoodKind},
+    market::Market,
+    wait_one_day,
+};
+
+const TRADER_NAME: &str = "foobar";
+
+fn market(weight_tuning: WeightTuningConfig) -> Rc<RefCell<SOLMarket>> {
+    market_from(&definition(weight_tuning))
+}
+
+fn definition(weight_tuning: WeightTuningConfig) -> MarketDefinition {
+    let config = MarketConfig {
+        weight_tuning,
+        ..Default::default()
+    };
+    MarketDefinition {
+        weights: WeightsDefinition {
+            stocastic: Some(0.5),
+            quantity: Some(0.5),
+            others: Some(0.5),
+        },
+        ..definition_with(config, 11)
+    }
+}
+
+fn wait_days(market: &Rc<RefCell<SOLMarket>>, days: u32) {
+    let dyn_market: Rc<RefCell<dyn Market>> = market.clone();
+    for _ in 0..days {
+        wait_one_day!(dyn_market);
+    }
+}
+
+#[test]
+/// Weights should not change unless the tuning is enabled
+///
+/// SOL group
+fn weights_should_not_change_by_default() {
+    let market = market(WeightTuningConfig::default());
+    wait_days(&market, 100);
+    let market = market.borrow();
+    assert!(market.weight_changes().is_empty());
+    assert_eq!(
+        market.to_definition().weights,
+        WeightsDefinition {
+            stocastic: Some(0.5),
+            quantity: Some(0.5),
+            others: Some(0.5),
+        }
+    );
+}
+
+#[test]
+/// A period should end with a step of one weight, which is saved with the
+/// others
+///
+/// SOL group
+fn weights_should_move_at_the_end_of_each_period() {
+    let market = market(WeightTuningConfig {
+        enabled: true,
+        every_days: 10,
+        ..Default::default()
+    });
+    // The market starts at day 1
+    wait_days(&market, 9);
+    let market = market.borrow();
+    let changes = market.weight_changes();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].strategy, "Others");
+    assert_eq!(changes[0].old_weight, 0.5);
+    // learning_rate * (max_weight - min_weight)
+    assert!((changes[0].new_weight - 0.595).abs() < 1e-6);
+    assert!(!changes[0].reverted);
+    assert_eq!(
+        market.to_definition().weights.others,
+        Some(changes[0].new_weight)
+    );
+}
+
+#[test]
+/// Weights should stay within the bounds
+///
+/// SOL group
+fn weights_should_stay_within_bounds() {
+    let market = market(WeightTuningConfig {
+        enabled: true,
+        objective: TuningObjective::Inventory,
+        learning_rate: 0.7,
+        min_weight: 0.2,
+        max_weight: 0.8,
+        every_days: 2,
+        ..Default::default()
+    });
+    wait_days(&market, 200);
+    let market = market.borrow();
+    assert!(market.weight_changes().len() > 10);
+    for change in market.weight_changes() {
+        assert!((0.2..=0.8).contains(&change.new_weight));
+    }
+}
+
+#[test]
+/// Bounds that can not hold a weight should be rejected when the market is
+/// created
+///
+/// SOL group
+fn invalid_bounds_should_be_rejected() {
+    for (min_weight, max_weight) in [(0.8, 0.2), (f32::NAN, 1.0), (0.0, f32::INFINITY)] {
+        let definition = definition(WeightTuningConfig {
+            enabled: true,
+            min_weight,
+            max_weight,
+            ..Default::default()
+        });
+        let result = SOLMarket::try_new_from_definition(&definition);
+        assert!(matches!(result, Err(SOLMarketError::InvalidConfig { .. })));
+    }
+}
+
+#[test]
+/// A minimum weight of 0 should be rejected, since the tuning could then
+/// bring every weight to 0
+///
+/// SOL group
+fn zero_min_weight_should_be_rejected() {
+    let definition = definition(WeightTuningConfig {
+        enabled: true,
+        min_weight: 0.0,
+        ..Default::default()
+    });
+    let result = SOLMarket::try_new_from_definition(&definition);
+    assert!(matches!(
+        result,
+        Err(SOLMarketError::InvalidConfig {
+            parameter: "weight_tuning.min_weight",
+            ..
+        })
+    ));
+}
+
+#[test]
+/// A step followed by a worse period should be undone
+///
+/// SOL group
+fn worse_steps_should_be_reverted() {
+    let market = market(WeightTuningConfig {
+        enabled: true,
+        every_days: 10,
+        ..Default::default()
+    });
+    // An empty period, then a step
+    wait_days(&market, 9);
+    // The market pays its margin on sells, so this period scores worse
+    {
+        let mut m = market.borrow_mut();
+        let offer = m.get_sell_price(GoodKind::USD, 100.0).unwrap();
+        let token = m
+            .lock_sell(GoodKind::USD, 100.0, offer, TRADER_NAME.to_string())
+            .unwrap();
+        m.sell(token, &mut Good::new(GoodKind::USD, 100.0)).unwrap();
+    }
+    wait_days(&market, 8);
+    let market = market.borrow();
+    let changes = market.weight_changes();
+    assert_eq!(changes.len(), 2);
+    assert!(changes[1].reverted);
+    assert_eq!(changes[1].strategy, "Others");
+    assert_eq!(changes[1].new_weight, 0.5);
+    assert!(changes[1].score < 0.0);
+}